@@ -0,0 +1,314 @@
+use crate::{
+    cli::Configuration,
+    constant::DEFAULT_STACK_SIZE,
+    hook::{LoopDetector, UninitialisedDetector},
+    host::Host,
+    snapshot::Snapshot,
+    utils::{Capture, Reader, SharedBuffer, Writer},
+    Interpreter,
+};
+use std::time::Duration;
+
+/// Builds an interpreter for use as a library, without going through the command line arguments.
+///
+/// By default the interpreter has a 4 megabyte stack, writes to stdout, reads from stdin
+/// and runs without a debugger or any limits.
+///
+/// ```
+/// use kasm::{Interpreter, Register};
+/// use std::io::Cursor;
+///
+/// let mut i = Interpreter::builder()
+///     .input(Cursor::new(b"41".to_vec()))
+///     .capture_output()
+///     .build();
+///
+/// i.run("rdub ra\naddb ra 1\nprrb ra\nstop").unwrap();
+///
+/// assert_eq!(i.register(Register::A), 42);
+/// assert_eq!(i.captured_output(), Some(String::from("ra: 42\n")));
+/// ```
+pub struct InterpreterBuilder {
+    stack_size: usize,
+    config: Configuration,
+}
+
+impl InterpreterBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            stack_size: DEFAULT_STACK_SIZE,
+            config: Configuration::default(),
+        }
+    }
+
+    /// Sets the size of the stack in bytes.
+    #[must_use]
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
+
+    /// Writes the output of print instructions to the writer.
+    #[must_use]
+    pub fn output(mut self, output: impl Writer + 'static) -> Self {
+        self.config.output = Box::new(output);
+        self.config.captured_output = None;
+        self
+    }
+
+    /// Writes reports, such as the reads of unwritten registers, to the writer rather than stderr.
+    #[must_use]
+    pub fn report(mut self, report: impl Writer + 'static) -> Self {
+        self.config.report = Box::new(report);
+        self
+    }
+
+    /// Keeps the output of print instructions in memory, retrieved through [`Interpreter::captured_output`].
+    #[must_use]
+    pub fn capture_output(mut self) -> Self {
+        let buffer = SharedBuffer::default();
+        self.config.output = Box::new(Capture::new(buffer.clone(), None));
+        self.config.captured_output = Some(buffer);
+        self
+    }
+
+    /// Reads the input of read instructions from the reader.
+    #[must_use]
+    pub fn input(mut self, input: impl Reader + 'static) -> Self {
+        self.config.input = Box::new(input);
+        self
+    }
+
+    /// Runs the program in the interactive debugger, which reads its commands from stdin.
+    #[must_use]
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.config.debug = debug;
+        self
+    }
+
+    /// Stops the program with an error once it has executed the amount of instructions.
+    #[must_use]
+    pub fn instruction_limit(mut self, limit: u64) -> Self {
+        self.config.instruction_limit = Some(limit);
+        self
+    }
+
+    /// Stops the program with an error once it has run for the duration.
+    #[must_use]
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.config.time_limit = Some(limit);
+        self
+    }
+
+    /// Stops the program with an error when it returns to a state it has been in before.
+    #[must_use]
+    pub fn detect_loops(mut self) -> Self {
+        self.config.hooks.push(Box::new(LoopDetector::new()));
+        self
+    }
+
+    /// Reports every read of a register that was never written once the program is finished.
+    #[must_use]
+    pub fn detect_uninitialised(mut self) -> Self {
+        self.config
+            .hooks
+            .push(Box::new(UninitialisedDetector::new()));
+        self
+    }
+
+    /// Restores the machine from the snapshot when running, which must have been taken of the same program.
+    #[must_use]
+    pub fn restore(mut self, snapshot: Snapshot) -> Self {
+        self.config.restore = Some(snapshot);
+        self
+    }
+
+    /// Registers a host function, which programs call with `ext name`.
+    ///
+    /// An error returned by the function stops the program with an execute error containing the message.
+    #[must_use]
+    pub fn host_function(
+        mut self,
+        name: &str,
+        function: impl FnMut(&mut Host) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.config
+            .host_functions
+            .register(name, Box::new(function));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Interpreter {
+        Interpreter::with_config(self.stack_size, self.config)
+    }
+}
+
+impl Default for InterpreterBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod build {
+    use crate::{
+        error::{ExecuteError, InterpreterError, PreProcessError},
+        flags::Flag,
+        register::Register,
+        Interpreter,
+    };
+    use std::{
+        io::{stdin, Cursor},
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn state_after_run() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::builder()
+            .input(Cursor::new(b"7\n".to_vec()))
+            .capture_output()
+            .build();
+        let program = ["rdub ra", "pshb ra", "subb ra 7", "prrb ra", "stop"].join("\n");
+
+        i.run(&program)?;
+
+        assert_eq!(i.register(Register::A), 0);
+        assert!(i.flags().get(Flag::Zero));
+        assert_eq!(i.stack(), [7]);
+        assert_eq!(i.instructions_executed(), 5);
+        assert_eq!(i.captured_output(), Some(String::from("ra: 0\n")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn default_input_leaves_stdin_unlocked() {
+        let _i = Interpreter::builder().debug(true).build();
+        let (sender, receiver) = mpsc::channel();
+
+        // the line debugger locks stdin to read its actions, which would never return if the input held the lock
+        thread::spawn(move || {
+            drop(stdin().lock());
+            sender.send(()).unwrap();
+        });
+
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(()));
+    }
+
+    #[test]
+    fn stack_size() {
+        let mut i = Interpreter::builder().stack_size(2).build();
+        let expected = Err(InterpreterError::Execute(3, ExecuteError::StackOverflow));
+
+        let actual = i.run("pshb 1\npshb 2\npshb 3\nstop");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn output_writer() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::builder().output(Vec::new()).build();
+
+        i.run("prrb ra\nstop")?;
+
+        assert_eq!(i.config.output.get_buffer(), Some(String::from("ra: 0\n")));
+        assert_eq!(i.captured_output(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn host_functions() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::builder()
+            .host_function("double_ra", |host| {
+                host.set_register(Register::A, host.register(Register::A) * 2);
+                Ok(())
+            })
+            .host_function("push_sum", |host| {
+                let sum = host.pop_byte()? + host.pop_byte()?;
+                host.push_byte(sum)
+            })
+            .build();
+        let program = [
+            "setb ra 21",
+            "ext double_ra",
+            "pshb 1",
+            "pshb 2",
+            "ext push_sum",
+            "stop",
+        ]
+        .join("\n");
+
+        i.run(&program)?;
+
+        assert_eq!(i.register(Register::A), 42);
+        assert_eq!(i.stack(), [3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn host_function_error() {
+        let mut i = Interpreter::builder()
+            .host_function("fail", |_| Err(String::from("not today")))
+            .build();
+        let expected = Err(InterpreterError::Execute(
+            2,
+            ExecuteError::HostFunction(String::from("fail"), String::from("not today")),
+        ));
+
+        let actual = i.run("setb ra 1\next fail\nstop");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn undefined_external_function_error() {
+        let mut i = Interpreter::builder().build();
+        let expected = Err(InterpreterError::PreProcess(
+            PreProcessError::UndefinedExternalFunction(String::from("missing")),
+        ));
+
+        let actual = i.run("ext missing\nstop");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn instruction_limit_exceeded_error() {
+        let mut i = Interpreter::builder().instruction_limit(10).build();
+        let expected = Err(InterpreterError::InstructionLimitExceeded(1, 10));
+
+        let actual = i.run("addb ra 1\njmp 1");
+
+        assert_eq!(actual, expected);
+        assert_eq!(i.register(Register::A), 5);
+    }
+
+    #[test]
+    fn time_limit_exceeded_error() {
+        let mut i = Interpreter::builder().time_limit(Duration::ZERO).build();
+
+        let actual = i.run("addb ra 1\njmp 1");
+
+        assert!(matches!(
+            actual,
+            Err(InterpreterError::TimeLimitExceeded(1, 0, Duration::ZERO))
+        ));
+    }
+
+    #[test]
+    fn instruction_limit_not_reached() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::builder().instruction_limit(2).build();
+
+        i.run("addb ra 1\nstop")?;
+
+        assert_eq!(i.instructions_executed(), 2);
+
+        Ok(())
+    }
+}
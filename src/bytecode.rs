@@ -0,0 +1,856 @@
+//! The binary encoding of assembled programs.
+//!
+//! An assembled file starts with a header containing the magic bytes, the format version,
+//! a flags byte and a CRC-32 checksum of everything after the header.
+//! The body contains the amount of lines followed by one entry per line of the preprocessed program,
+//! where instructions are stored already decoded, such that running them requires neither preprocessing nor parsing.
+//! When assembled with debug info, the body ends with the source code of every line,
+//! which is shown by the debugger and used in reports in place of the decoded instructions.
+
+use crate::{
+    cli::{read_program, read_program_file, AssembleArguments, DisassembleArguments, ProgramFile},
+    constant::{Byte, Half, Quarter, Word, COMMENT},
+    decode::decode,
+    error::InterpreterError,
+    flags::Flag,
+    instruction::{
+        Addition, And, Assert, Compare, Division, Instruction, Jump, Multiplication, Not, Or, Pop,
+        PrintRegister, PrintStack, Push, Read, Relative, Remainder, Set, Subtraction, Test, Xor,
+    },
+    operand::Operand,
+    preprocess,
+    program::Program,
+    register::Register,
+};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{stdout, Write},
+    path::PathBuf,
+};
+
+/// The bytes every assembled file starts with.
+pub const MAGIC: &[Byte; 4] = b"KASM";
+
+/// The version of the assembled format, incremented whenever the format changes.
+pub const BYTECODE_VERSION: Quarter = 1;
+
+/// The extension of assembled files when no output file is specified.
+pub const ASSEMBLED_EXTENSION: &str = "kbc";
+
+/// The size of the magic bytes, version, flags and checksum.
+const HEADER_SIZE: usize =
+    MAGIC.len() + size_of::<Quarter>() + size_of::<Byte>() + size_of::<Half>();
+
+/// Set in the flags byte if the file ends with a debug info section.
+const DEBUG_INFO: Byte = 0b1;
+
+const COMMENT_TAG: Byte = 0;
+const INSTRUCTION_TAG: Byte = 1;
+const TEXT_TAG: Byte = 2;
+
+/// Returns true if the bytes start like an assembled file, rather than like source code.
+#[must_use]
+pub fn is_assembled(bytes: &[Byte]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Computes the CRC-32 checksum of the bytes, as used by zip and png.
+fn crc32(bytes: &[Byte]) -> Half {
+    const POLYNOMIAL: Half = 0xedb8_8320;
+
+    !bytes.iter().fold(Half::MAX, |crc, byte| {
+        (0..Byte::BITS).fold(crc ^ Half::from(*byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// A line of an assembled program.
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    Comment,
+    Instruction(Instruction),
+    /// A line without an instruction, such as an empty line or a function definition,
+    /// which is kept as is to fail in the same way as the source code would if it is reached.
+    Text(String),
+}
+
+impl Line {
+    /// Assembles a line of the preprocessed program, where the line number is used for errors.
+    fn assemble(number: usize, line: &str) -> Result<Self, InterpreterError> {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with(COMMENT) {
+            return Ok(Line::Comment);
+        }
+
+        if trimmed.is_empty() || trimmed.starts_with("fn ") {
+            return Ok(Line::Text(line.to_string()));
+        }
+
+        decode(line)
+            .map(Line::Instruction)
+            .map_err(|e| InterpreterError::Decode(number, e))
+    }
+
+    /// Gets the text shown for the line when there is no debug info.
+    fn text(&self) -> String {
+        match self {
+            Line::Comment => COMMENT.to_string(),
+            Line::Instruction(instruction) => instruction.to_string(),
+            Line::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// A program in the form it is stored in an assembled file.
+#[derive(Debug, PartialEq)]
+pub struct Assembled {
+    lines: Vec<Line>,
+    /// The source code of every line, if assembled with debug info.
+    debug_info: Option<Vec<String>>,
+}
+
+impl Assembled {
+    /// Preprocesses and decodes every line of the source code.
+    ///
+    /// As no host functions are available when assembling, programs using `ext` cannot be assembled.
+    pub fn assemble(source_code: &str, debug_info: bool) -> Result<Self, InterpreterError> {
+        let program = preprocess(source_code, &[])?;
+        let lines = (1..)
+            .zip(program.lines())
+            .map(|(number, line)| Line::assemble(number, line))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // preprocessing keeps every line in place and only removes the data section at the end
+        let debug_info = debug_info.then(|| {
+            source_code
+                .lines()
+                .take(lines.len())
+                .map(str::to_string)
+                .collect()
+        });
+
+        Ok(Self { lines, debug_info })
+    }
+
+    /// Encodes the program, including the header.
+    pub fn to_bytes(&self) -> Vec<Byte> {
+        let mut body = Vec::new();
+        len(self.lines.len()).encode(&mut body);
+        for line in &self.lines {
+            line.encode(&mut body);
+        }
+        if let Some(debug_info) = &self.debug_info {
+            for line in debug_info {
+                line.encode(&mut body);
+            }
+        }
+
+        let flags = if self.debug_info.is_some() {
+            DEBUG_INFO
+        } else {
+            0
+        };
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + body.len());
+        bytes.extend_from_slice(MAGIC);
+        BYTECODE_VERSION.encode(&mut bytes);
+        flags.encode(&mut bytes);
+        crc32(&body).encode(&mut bytes);
+        bytes.extend_from_slice(&body);
+
+        bytes
+    }
+
+    /// Decodes an assembled file, verifying its version and checksum before decoding any line.
+    pub fn from_bytes(bytes: &[Byte]) -> Result<Self, InterpreterError> {
+        let mut input = Input::new(bytes);
+        if input.take(MAGIC.len())? != MAGIC {
+            return Err(InterpreterError::InvalidAssembledFile(String::from(
+                "it does not start with the magic bytes",
+            )));
+        }
+
+        let version = Quarter::decode(&mut input)?;
+        if version != BYTECODE_VERSION {
+            return Err(InterpreterError::AssembledVersionMismatch(version));
+        }
+
+        let flags = Byte::decode(&mut input)?;
+        let expected = Half::decode(&mut input)?;
+        let actual = crc32(input.rest());
+        if expected != actual {
+            return Err(InterpreterError::AssembledChecksumMismatch(
+                expected, actual,
+            ));
+        }
+
+        let count = Half::decode(&mut input)?;
+        let lines = (0..count)
+            .map(|_| Line::decode(&mut input))
+            .collect::<Result<Vec<_>, _>>()?;
+        let debug_info = if flags & DEBUG_INFO == DEBUG_INFO {
+            Some(
+                (0..count)
+                    .map(|_| String::decode(&mut input))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        } else {
+            None
+        };
+
+        if !input.rest().is_empty() {
+            return Err(input.invalid("unexpected bytes after the last line"));
+        }
+
+        Ok(Self { lines, debug_info })
+    }
+
+    /// Gets the source code, or the disassembled program if assembled without debug info,
+    /// such that snapshots and hooks still tell programs apart.
+    pub fn source(&self) -> String {
+        self.debug_info
+            .as_ref()
+            .map_or_else(|| self.disassemble(), |lines| lines.join("\n"))
+    }
+
+    /// Prints every line as canonical source code, which assembles to the same program.
+    pub fn disassemble(&self) -> String {
+        self.lines.iter().fold(String::new(), |mut source, line| {
+            let _ = writeln!(source, "{}", line.text());
+            source
+        })
+    }
+
+    /// Converts into a program, showing the source code of each line if there is debug info.
+    pub fn into_program(self) -> Program {
+        let text = match self.debug_info {
+            Some(debug_info) => debug_info.into_boxed_slice(),
+            None => self.lines.iter().map(Line::text).collect(),
+        };
+
+        Program::assembled(text, self.lines.into_boxed_slice())
+    }
+}
+
+/// Assembles the program file, writing it to the output file or next to the program file.
+///
+/// # Errors
+/// Returns an error if the program file cannot be read or preprocessed, if any of its lines
+/// is not a valid instruction, or if the assembled file cannot be written.
+pub fn assemble(args: &AssembleArguments) -> Result<PathBuf, InterpreterError> {
+    let source_code = read_program_file(&args.file_name).map_err(InterpreterError::Argument)?;
+    let assembled = Assembled::assemble(&source_code, args.debug_info)?;
+
+    let path = args
+        .output
+        .clone()
+        .unwrap_or_else(|| args.file_name.with_extension(ASSEMBLED_EXTENSION));
+    fs::write(&path, assembled.to_bytes())
+        .map_err(|err| InterpreterError::FailedAssembledFileCreation(err.to_string()))?;
+
+    Ok(path)
+}
+
+/// Disassembles the assembled file, writing its source code to the output file or stdout.
+///
+/// # Errors
+/// Returns an error if the file cannot be read, does not contain an assembled program or is corrupt,
+/// or if the source code cannot be written.
+pub fn disassemble(args: &DisassembleArguments) -> Result<(), InterpreterError> {
+    let ProgramFile::Assembled(bytes) =
+        read_program(&args.file_name).map_err(InterpreterError::Argument)?
+    else {
+        return Err(InterpreterError::NotAssembled(args.file_name.clone()));
+    };
+    let source_code = Assembled::from_bytes(&bytes)?.disassemble();
+
+    match &args.output {
+        Some(path) => fs::write(path, source_code),
+        None => stdout().write_all(source_code.as_bytes()),
+    }
+    .map_err(|err| InterpreterError::FailedDisassemblyWrite(err.to_string()))
+}
+
+/// Converts a length to the 32-bit length used by the format.
+fn len(length: usize) -> Half {
+    Half::try_from(length).expect("programs and lines are far smaller than 4 GiB")
+}
+
+/// The bytes of an assembled file, read from front to back.
+struct Input<'a> {
+    bytes: &'a [Byte],
+    offset: usize,
+}
+
+impl<'a> Input<'a> {
+    fn new(bytes: &'a [Byte]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn invalid(&self, reason: &str) -> InterpreterError {
+        InterpreterError::InvalidAssembledFile(format!("{reason} at byte {}", self.offset))
+    }
+
+    fn rest(&self) -> &'a [Byte] {
+        self.bytes.get(self.offset..).unwrap_or_default()
+    }
+
+    fn take(&mut self, amount: usize) -> Result<&'a [Byte], InterpreterError> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset.saturating_add(amount))
+            .ok_or_else(|| self.invalid("unexpected end of file"))?;
+        self.offset += amount;
+
+        Ok(bytes)
+    }
+
+    /// Reads the tag of a variant, failing if it is not below the amount of variants.
+    fn tag(&mut self, variants: usize, kind: &str) -> Result<Byte, InterpreterError> {
+        let tag = Byte::decode(self)?;
+        if usize::from(tag) >= variants {
+            self.offset -= 1;
+            return Err(self.invalid(&format!("unknown {kind} {tag}")));
+        }
+
+        Ok(tag)
+    }
+}
+
+trait Encode {
+    fn encode(&self, bytes: &mut Vec<Byte>);
+}
+
+trait Decode: Sized {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError>;
+}
+
+macro_rules! integer_codec {
+    ($($type:ty),+) => {
+        $(
+            impl Encode for $type {
+                fn encode(&self, bytes: &mut Vec<Byte>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+
+            impl Decode for $type {
+                fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+                    let bytes = input.take(size_of::<$type>())?;
+                    let bytes = bytes.try_into().expect("exactly the size of the integer is taken");
+
+                    Ok(<$type>::from_le_bytes(bytes))
+                }
+            }
+        )+
+    };
+}
+
+integer_codec!(Byte, Quarter, Half, Word);
+
+/// Implements the encoding of an enum where every variant has fields, as a tag followed by the fields.
+macro_rules! enum_codec {
+    ($type:ident { $($variant:ident($($field:ident),+) = $tag:literal,)+ }) => {
+        impl Encode for $type {
+            fn encode(&self, bytes: &mut Vec<Byte>) {
+                match self {
+                    $($type::$variant($($field),+) => {
+                        bytes.push($tag);
+                        $($field.encode(bytes);)+
+                    })+
+                }
+            }
+        }
+
+        impl Decode for $type {
+            fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+                let variants = [$($tag),+].len();
+                match input.tag(variants, stringify!($type))? {
+                    $($tag => Ok($type::$variant($(enum_codec!(@field input $field)),+)),)+
+                    _ => unreachable!("the tag is checked to be below the amount of variants"),
+                }
+            }
+        }
+    };
+    (@field $input:ident $field:ident) => {
+        Decode::decode($input)?
+    };
+}
+
+enum_codec!(Addition { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(And { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Division { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Multiplication { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Or { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Remainder { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Set { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Subtraction { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Xor { Byte(r, o) = 0, Quarter(r, o) = 1, Half(r, o) = 2, Word(r, o) = 3, });
+enum_codec!(Compare { Byte(a, b) = 0, Quarter(a, b) = 1, Half(a, b) = 2, Word(a, b) = 3, });
+enum_codec!(Test { Byte(a, b) = 0, Quarter(a, b) = 1, Half(a, b) = 2, Word(a, b) = 3, });
+enum_codec!(Not { Byte(r) = 0, Quarter(r) = 1, Half(r) = 2, Word(r) = 3, });
+enum_codec!(Pop { Byte(r) = 0, Quarter(r) = 1, Half(r) = 2, Word(r) = 3, });
+enum_codec!(PrintRegister { Byte(r) = 0, Quarter(r) = 1, Half(r) = 2, Word(r) = 3, });
+enum_codec!(Push { Byte(o) = 0, Quarter(o) = 1, Half(o) = 2, Word(o) = 3, });
+enum_codec!(PrintStack { Byte(o) = 0, Quarter(o) = 1, Half(o) = 2, Word(o) = 3, Str(o) = 4, });
+enum_codec!(Assert {
+    Byte(a, b) = 0,
+    Quarter(a, b) = 1,
+    Half(a, b) = 2,
+    Word(a, b) = 3,
+    Flag(f, set) = 4,
+    StackDepth(o) = 5,
+});
+enum_codec!(Read {
+    UnsignedByte(r) = 0,
+    UnsignedQuarter(r) = 1,
+    UnsignedHalf(r) = 2,
+    UnsignedWord(r) = 3,
+    SignedByte(r) = 4,
+    SignedQuarter(r) = 5,
+    SignedHalf(r) = 6,
+    SignedWord(r) = 7,
+    Character(r) = 8,
+    Line(r) = 9,
+});
+impl<T: Encode> Encode for Operand<T> {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        match self {
+            Operand::Register(register) => {
+                bytes.push(0);
+                register.encode(bytes);
+            }
+            Operand::Immediate(value) => {
+                bytes.push(1);
+                value.encode(bytes);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Operand<T> {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        Ok(match input.tag(2, "operand")? {
+            0 => Operand::Register(Decode::decode(input)?),
+            _ => Operand::Immediate(Decode::decode(input)?),
+        })
+    }
+}
+
+impl Encode for Line {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        match self {
+            Line::Comment => bytes.push(COMMENT_TAG),
+            Line::Instruction(instruction) => {
+                bytes.push(INSTRUCTION_TAG);
+                instruction.encode(bytes);
+            }
+            Line::Text(text) => {
+                bytes.push(TEXT_TAG);
+                text.encode(bytes);
+            }
+        }
+    }
+}
+
+impl Decode for Line {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        Ok(match input.tag(3, "line")? {
+            COMMENT_TAG => Line::Comment,
+            INSTRUCTION_TAG => Line::Instruction(Decode::decode(input)?),
+            _ => Line::Text(Decode::decode(input)?),
+        })
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        bytes.push(Byte::from(*self));
+    }
+}
+
+impl Decode for bool {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        Ok(input.tag(2, "boolean")? == 1)
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        len(self.len()).encode(bytes);
+        bytes.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Decode for String {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        let length = Half::decode(input)? as usize;
+        let bytes = input.take(length)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| input.invalid("text is not valid UTF-8"))
+    }
+}
+
+impl Encode for Register {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        let index = Register::ALL
+            .iter()
+            .position(|register| register == self)
+            .and_then(|index| Byte::try_from(index).ok())
+            .expect("every register is part of all registers");
+        bytes.push(index);
+    }
+}
+
+impl Decode for Register {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        let index = input.tag(Register::ALL.len(), "register")?;
+
+        Ok(Register::ALL
+            .get(usize::from(index))
+            .copied()
+            .expect("the tag is checked to be below the amount of registers"))
+    }
+}
+
+const FLAGS: [Flag; 3] = [Flag::Sign, Flag::Overflow, Flag::Zero];
+
+impl Encode for Flag {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        let index = FLAGS
+            .iter()
+            .position(|flag| flag == self)
+            .and_then(|index| Byte::try_from(index).ok())
+            .expect("every flag is part of all flags");
+        bytes.push(index);
+    }
+}
+
+impl Decode for Flag {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        let index = input.tag(FLAGS.len(), "flag")?;
+
+        Ok(FLAGS
+            .get(usize::from(index))
+            .copied()
+            .expect("the tag is checked to be below the amount of flags"))
+    }
+}
+
+impl Encode for Jump {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        bytes.push(match self {
+            Jump::Unconditional => 0,
+            Jump::IfZero => 1,
+            Jump::IfNotZero => 2,
+            Jump::IfSign => 3,
+            Jump::IfNotSign => 4,
+            Jump::IfOverflow => 5,
+            Jump::IfNotOverflow => 6,
+            Jump::IfGreater => 7,
+            Jump::IfLesser => 8,
+            Jump::IfGreaterOrEqual => 9,
+            Jump::IfLesserOrEqual => 10,
+        });
+    }
+}
+
+impl Decode for Jump {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        Ok(match input.tag(11, "jump")? {
+            0 => Jump::Unconditional,
+            1 => Jump::IfZero,
+            2 => Jump::IfNotZero,
+            3 => Jump::IfSign,
+            4 => Jump::IfNotSign,
+            5 => Jump::IfOverflow,
+            6 => Jump::IfNotOverflow,
+            7 => Jump::IfGreater,
+            8 => Jump::IfLesser,
+            9 => Jump::IfGreaterOrEqual,
+            _ => Jump::IfLesserOrEqual,
+        })
+    }
+}
+
+impl Encode for Option<Relative> {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        bytes.push(match self {
+            None => 0,
+            Some(Relative::Positive) => 1,
+            Some(Relative::Negative) => 2,
+        });
+    }
+}
+
+impl Decode for Option<Relative> {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        Ok(match input.tag(3, "relative jump")? {
+            0 => None,
+            1 => Some(Relative::Positive),
+            _ => Some(Relative::Negative),
+        })
+    }
+}
+
+impl Encode for Instruction {
+    fn encode(&self, bytes: &mut Vec<Byte>) {
+        use Instruction::*;
+
+        bytes.push(match self {
+            Addition(_) => 0,
+            And(_) => 1,
+            Assert(_) => 2,
+            Call(_) => 3,
+            Compare(_) => 4,
+            Division(_) => 5,
+            External(_) => 6,
+            Jump(_, _, _) => 7,
+            Multiplication(_) => 8,
+            Not(_) => 9,
+            Or(_) => 10,
+            Pop(_) => 11,
+            PrintRegister(_) => 12,
+            PrintStack(_) => 13,
+            Push(_) => 14,
+            Read(_) => 15,
+            Remainder(_) => 16,
+            Return => 17,
+            Set(_) => 18,
+            Stop => 19,
+            Subtraction(_) => 20,
+            Test(_) => 21,
+            Xor(_) => 22,
+        });
+
+        match self {
+            Addition(i) => i.encode(bytes),
+            And(i) => i.encode(bytes),
+            Assert(i) => i.encode(bytes),
+            Call(i) => i.encode(bytes),
+            Compare(i) => i.encode(bytes),
+            Division(i) => i.encode(bytes),
+            External(i) => i.encode(bytes),
+            Jump(jump, operand, relative) => {
+                jump.encode(bytes);
+                operand.encode(bytes);
+                relative.encode(bytes);
+            }
+            Multiplication(i) => i.encode(bytes),
+            Not(i) => i.encode(bytes),
+            Or(i) => i.encode(bytes),
+            Pop(i) => i.encode(bytes),
+            PrintRegister(i) => i.encode(bytes),
+            PrintStack(i) => i.encode(bytes),
+            Push(i) => i.encode(bytes),
+            Read(i) => i.encode(bytes),
+            Remainder(i) => i.encode(bytes),
+            Set(i) => i.encode(bytes),
+            Subtraction(i) => i.encode(bytes),
+            Test(i) => i.encode(bytes),
+            Xor(i) => i.encode(bytes),
+            Return | Stop => {}
+        }
+    }
+}
+
+impl Decode for Instruction {
+    fn decode(input: &mut Input<'_>) -> Result<Self, InterpreterError> {
+        use Instruction::*;
+
+        Ok(
+            match input.tag(Instruction::VARIANT_COUNT, "instruction")? {
+                0 => Addition(Decode::decode(input)?),
+                1 => And(Decode::decode(input)?),
+                2 => Assert(Decode::decode(input)?),
+                3 => Call(Decode::decode(input)?),
+                4 => Compare(Decode::decode(input)?),
+                5 => Division(Decode::decode(input)?),
+                6 => External(Decode::decode(input)?),
+                7 => Jump(
+                    Decode::decode(input)?,
+                    Decode::decode(input)?,
+                    Decode::decode(input)?,
+                ),
+                8 => Multiplication(Decode::decode(input)?),
+                9 => Not(Decode::decode(input)?),
+                10 => Or(Decode::decode(input)?),
+                11 => Pop(Decode::decode(input)?),
+                12 => PrintRegister(Decode::decode(input)?),
+                13 => PrintStack(Decode::decode(input)?),
+                14 => Push(Decode::decode(input)?),
+                15 => Read(Decode::decode(input)?),
+                16 => Remainder(Decode::decode(input)?),
+                17 => Return,
+                18 => Set(Decode::decode(input)?),
+                19 => Stop,
+                20 => Subtraction(Decode::decode(input)?),
+                21 => Test(Decode::decode(input)?),
+                _ => Xor(Decode::decode(input)?),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod crc32 {
+    use super::crc32;
+
+    #[test]
+    fn check_value() {
+        let actual = crc32(b"123456789");
+
+        assert_eq!(actual, 0xcbf4_3926);
+    }
+}
+
+#[cfg(test)]
+mod round_trip {
+    use super::{Assembled, Decode, Encode, Input, Line};
+    use crate::{
+        constant::Word,
+        instruction::{Instruction, EVERY_INSTRUCTION},
+    };
+
+    #[test]
+    fn every_instruction() {
+        let expected = Assembled::assemble(EVERY_INSTRUCTION, false).unwrap();
+
+        let actual = Assembled::from_bytes(&expected.to_bytes()).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn external() {
+        let expected = Line::Instruction(Instruction::External(Word::MAX));
+        let mut bytes = Vec::new();
+        expected.encode(&mut bytes);
+
+        let actual = Line::decode(&mut Input::new(&bytes)).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn disassembled_assembles_to_same_program() {
+        let expected = Assembled::assemble(EVERY_INSTRUCTION, false).unwrap();
+
+        let actual = Assembled::assemble(&expected.disassemble(), false).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn debug_info() {
+        let source = "setb ra FIVE\nprrb ra\nstop\n\nDATA:\n  FIVE 5";
+        let expected = Some(vec![
+            String::from("setb ra FIVE"),
+            String::from("prrb ra"),
+            String::from("stop"),
+        ]);
+
+        let actual = Assembled::from_bytes(&Assembled::assemble(source, true).unwrap().to_bytes())
+            .unwrap()
+            .debug_info;
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn source_without_debug_info() {
+        let expected = "setb ra 5\nprrb ra\nstop\n";
+
+        let actual = Assembled::from_bytes(
+            &Assembled::assemble("setb ra FIVE\nprrb  ra\nstop\n\nDATA:\n  FIVE 5", false)
+                .unwrap()
+                .to_bytes(),
+        )
+        .unwrap()
+        .source();
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod from_bytes {
+    use super::{Assembled, BYTECODE_VERSION, HEADER_SIZE, MAGIC};
+    use crate::error::{DecodeError, InterpreterError};
+
+    fn assembled() -> Vec<u8> {
+        Assembled::assemble("setb ra 5\nprrb ra\nstop", true)
+            .unwrap()
+            .to_bytes()
+    }
+
+    #[test]
+    fn version_mismatch_error() {
+        let mut bytes = assembled();
+        let version = (BYTECODE_VERSION + 1).to_le_bytes();
+        bytes.splice(MAGIC.len()..MAGIC.len() + 2, version);
+        let expected = Err(InterpreterError::AssembledVersionMismatch(
+            BYTECODE_VERSION + 1,
+        ));
+
+        let actual = Assembled::from_bytes(&bytes);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn corrupt_body_checksum_error() {
+        let mut bytes = assembled();
+        if let Some(byte) = bytes.get_mut(HEADER_SIZE + 6) {
+            *byte ^= 0xff;
+        }
+
+        let actual = Assembled::from_bytes(&bytes);
+
+        assert!(matches!(
+            actual,
+            Err(InterpreterError::AssembledChecksumMismatch(expected, actual)) if expected != actual
+        ));
+    }
+
+    #[test]
+    fn truncated_error() {
+        let bytes = assembled();
+        let expected = Err(InterpreterError::InvalidAssembledFile(String::from(
+            "unexpected end of file at byte 6",
+        )));
+
+        let actual = Assembled::from_bytes(bytes.get(..6).unwrap());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn missing_magic_error() {
+        let expected = Err(InterpreterError::InvalidAssembledFile(String::from(
+            "it does not start with the magic bytes",
+        )));
+
+        let actual = Assembled::from_bytes(b"setb ra 5");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_instruction_when_assembling_error() {
+        let expected = Err(InterpreterError::Decode(
+            2,
+            DecodeError::InvalidRegister(String::from("rx")),
+        ));
+
+        let actual = Assembled::assemble("setb ra 5\nprrb rx\nstop", false);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,202 @@
+//! Tracking of function calls alongside the stack, such that the call chain can be reported.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Maps the first line of each function to the name of the function.
+pub type Functions = BTreeMap<usize, String>;
+
+/// The name used for code that is not part of any function.
+pub const TOP_LEVEL: &str = "<top level>";
+
+/// A single function call that has not yet returned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// The first line of the called function.
+    pub entry: usize,
+    /// The line of the call instruction.
+    pub call_site: usize,
+    /// The stack pointer right after the return address was pushed.
+    pub stack_pointer: usize,
+}
+
+/// A shadow of the return addresses pushed onto the stack by call instructions.
+///
+/// Unlike the stack, it is not accessible to the program, and as such it cannot be corrupted by it.
+#[derive(Debug)]
+pub struct CallStack {
+    frames: Vec<Frame>,
+    max_depth: usize,
+}
+
+impl CallStack {
+    pub fn new() -> Self {
+        Self {
+            frames: Vec::new(),
+            max_depth: 0,
+        }
+    }
+
+    /// Creates a call stack of calls that have not yet returned, where the oldest call is first.
+    pub fn from_frames(frames: Vec<Frame>) -> Self {
+        Self {
+            max_depth: frames.len(),
+            frames,
+        }
+    }
+
+    pub fn push(&mut self, entry: usize, call_site: usize, stack_pointer: usize) {
+        self.frames.push(Frame {
+            entry,
+            call_site,
+            stack_pointer,
+        });
+        self.max_depth = self.max_depth.max(self.frames.len());
+    }
+
+    pub fn pop(&mut self) -> Option<Frame> {
+        self.frames.pop()
+    }
+
+    /// Gets the most recent call.
+    pub fn last(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
+    /// Gets all calls that have not yet returned, where the oldest call is first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Gets the largest depth the call stack has had at any point.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Builds a backtrace given the line currently being executed.
+    pub fn backtrace(&self, line: usize, functions: &Functions) -> Backtrace {
+        let mut locations = Vec::with_capacity(self.frames.len() + 1);
+        let mut line = line;
+
+        for frame in self.frames.iter().rev() {
+            locations.push((function_name(frame.entry, functions), line));
+            line = frame.call_site;
+        }
+        locations.push((TOP_LEVEL.to_string(), line));
+
+        Backtrace(locations)
+    }
+}
+
+/// Gets the name of the function starting at the given line.
+///
+/// Calls to a line number rather than a function name may not start at a known function,
+/// in which case the line number itself is used.
+pub fn function_name(entry: usize, functions: &Functions) -> String {
+    match functions.get(&entry) {
+        Some(name) => name.clone(),
+        None => format!("<line {entry}>"),
+    }
+}
+
+/// The chain of calls leading to a line, where the most recent call is first.
+#[derive(Debug, PartialEq)]
+pub struct Backtrace(Vec<(String, usize)>);
+
+impl Display for Backtrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "backtrace (most recent call first):")?;
+
+        for (index, (function, line)) in self.0.iter().enumerate() {
+            write!(f, "\n  {index}: {function} at line {line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod max_depth {
+    use super::CallStack;
+
+    #[test]
+    fn kept_after_returning() {
+        let mut c = CallStack::new();
+        c.push(4, 1, 8);
+        c.push(10, 6, 16);
+        c.pop();
+        c.pop();
+        c.push(4, 2, 8);
+
+        assert_eq!(c.depth(), 1);
+        assert_eq!(c.max_depth(), 2);
+    }
+}
+
+#[cfg(test)]
+mod backtrace {
+    use super::{Backtrace, CallStack, Functions};
+
+    fn functions() -> Functions {
+        Functions::from([(4, String::from("outer")), (10, String::from("inner"))])
+    }
+
+    #[test]
+    fn top_level_only() {
+        let c = CallStack::new();
+        let expected = Backtrace(vec![(String::from("<top level>"), 7)]);
+
+        let actual = c.backtrace(7, &functions());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn nested_calls() {
+        let mut c = CallStack::new();
+        c.push(4, 1, 8);
+        c.push(10, 6, 16);
+        let expected = Backtrace(vec![
+            (String::from("inner"), 11),
+            (String::from("outer"), 6),
+            (String::from("<top level>"), 1),
+        ]);
+
+        let actual = c.backtrace(11, &functions());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn call_to_line_number() {
+        let mut c = CallStack::new();
+        c.push(20, 1, 8);
+        let expected = Backtrace(vec![
+            (String::from("<line 20>"), 21),
+            (String::from("<top level>"), 1),
+        ]);
+
+        let actual = c.backtrace(21, &functions());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn display() {
+        let mut c = CallStack::new();
+        c.push(4, 1, 8);
+        let expected = [
+            "backtrace (most recent call first):",
+            "  0: outer at line 5",
+            "  1: <top level> at line 1",
+        ]
+        .join("\n");
+
+        let actual = c.backtrace(5, &functions()).to_string();
+
+        assert_eq!(actual, expected);
+    }
+}
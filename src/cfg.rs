@@ -0,0 +1,822 @@
+//! The control-flow graph of a program, built from its decoded instructions.
+//!
+//! Lines are grouped into basic blocks, which are only entered at their first line and only
+//! left after their last line. Calls do not end a block, as execution continues after them once
+//! the function returns, but are kept separately to form the call graph between functions.
+
+use crate::{
+    call_stack::{Functions, TOP_LEVEL},
+    cli::{read_program_file, GraphArguments},
+    constant::Word,
+    error::InterpreterError,
+    instruction::{Instruction, Jump, Relative},
+    operand::Operand,
+    preprocess,
+    program::Program,
+};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Write,
+    fs,
+    path::PathBuf,
+};
+
+/// The name used for the top level in file names, where `<top level>` is not allowed.
+const TOP_LEVEL_FILE_NAME: &str = "top_level";
+
+/// The amount of times the state at the start of a block may change before it is widened,
+/// so that loops changing the state on every iteration are analysed in finite time.
+const WIDENING_THRESHOLD: usize = 3;
+
+/// Gets the line a jump or call goes to, if it is known without running the program.
+pub fn jump_target(
+    line: usize,
+    destination: &Operand<Word>,
+    relative: Option<&Relative>,
+) -> Option<usize> {
+    let Operand::Immediate(value) = destination else {
+        return None;
+    };
+    let value = usize::try_from(*value).ok()?;
+
+    match relative {
+        None => Some(value),
+        Some(Relative::Positive) => line.checked_add(value),
+        Some(Relative::Negative) => line.checked_sub(value),
+    }
+}
+
+/// Returns true if execution never continues on the next line after the instruction.
+pub fn is_terminator(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Stop | Instruction::Return | Instruction::Jump(Jump::Unconditional, _, _)
+    )
+}
+
+/// What a line of the program contains, as far as control flow is concerned.
+#[derive(Debug)]
+enum Line {
+    Instruction(Instruction),
+    /// Execution continues on the next line.
+    Comment,
+    /// Execution fails on this line, such as on empty lines and function definitions.
+    Invalid,
+}
+
+/// A sequence of lines which is only entered at its first line and only left after its last line.
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    /// The last line of the block, which contains an instruction.
+    pub end: usize,
+    /// The first line of the function the block belongs to, or none at the top level.
+    pub function: Option<usize>,
+    /// True if the block ends in a jump to a line computed at runtime.
+    pub computed_jump: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeKind {
+    /// Execution continues on the next line.
+    Next,
+    /// An unconditional jump.
+    Jump,
+    /// A conditional jump which is performed.
+    Taken,
+    /// A conditional jump which is not performed, so execution continues on the next line.
+    NotTaken,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Edge {
+    /// The index of the block the edge leaves.
+    pub from: usize,
+    /// The index of the block the edge enters.
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Call {
+    pub line: usize,
+    /// The first line of the calling function, or none at the top level.
+    pub caller: Option<usize>,
+    /// The first line of the called function, or none if it is computed at runtime.
+    pub callee: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ControlFlowGraph {
+    lines: Vec<Line>,
+    functions: Functions,
+    blocks: Vec<Block>,
+    edges: Vec<Edge>,
+    calls: Vec<Call>,
+}
+
+impl ControlFlowGraph {
+    #[must_use]
+    pub fn new(program: &Program) -> Self {
+        let lines = (1..=program.lines().len())
+            .map(|line| match program.instruction(line) {
+                Ok(Some(instruction)) => Line::Instruction(instruction),
+                Ok(None) => Line::Comment,
+                Err(_) => Line::Invalid,
+            })
+            .collect();
+
+        let mut cfg = Self {
+            lines,
+            functions: program.functions(),
+            blocks: Vec::new(),
+            edges: Vec::new(),
+            calls: Vec::new(),
+        };
+        cfg.build_blocks();
+        cfg.build_edges();
+        cfg.build_calls();
+
+        cfg
+    }
+
+    #[must_use]
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    #[must_use]
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    #[must_use]
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// Gets the call on the line, if it contains one.
+    #[must_use]
+    pub fn call(&self, line: usize) -> Option<&Call> {
+        self.calls.iter().find(|call| call.line == line)
+    }
+
+    /// Gets the functions by the line of their first instruction.
+    #[must_use]
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+
+    /// Gets the amount of lines of the program, including those without an instruction.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Gets the instruction on the line, if it contains one.
+    #[must_use]
+    pub fn instruction(&self, line: usize) -> Option<&Instruction> {
+        match self.lines.get(line.checked_sub(1)?)? {
+            Line::Instruction(instruction) => Some(instruction),
+            _ => None,
+        }
+    }
+
+    /// Gets every line with an instruction, along with the instruction.
+    pub fn instructions(&self) -> impl Iterator<Item = (usize, &Instruction)> {
+        (1..)
+            .zip(&self.lines)
+            .filter_map(|(line, entry)| match entry {
+                Line::Instruction(instruction) => Some((line, instruction)),
+                _ => None,
+            })
+    }
+
+    /// Gets the first line of the function the line belongs to, or none at the top level.
+    #[must_use]
+    pub fn function(&self, line: usize) -> Option<usize> {
+        // the definition of a function is the line before its first line
+        self.functions
+            .range(..=line + 1)
+            .next_back()
+            .map(|(entry, _)| *entry)
+    }
+
+    /// Gets the name of the function starting at the line, or the name of the top level.
+    pub fn function_name(&self, function: Option<usize>) -> &str {
+        function
+            .and_then(|entry| self.functions.get(&entry))
+            .map_or(TOP_LEVEL, String::as_str)
+    }
+
+    /// Gets the index of the block execution starts at in the function, or at the top level.
+    #[must_use]
+    pub fn entry(&self, function: Option<usize>) -> Option<usize> {
+        self.resolve(function.unwrap_or(1))
+            .and_then(|line| self.block_at(line))
+    }
+
+    /// Gets the index of the block starting at the line.
+    #[must_use]
+    pub fn block_at(&self, line: usize) -> Option<usize> {
+        self.blocks.iter().position(|block| block.start == line)
+    }
+
+    /// Gets the edges leaving the block.
+    pub fn successors(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == block)
+    }
+
+    /// Finds the state at the start of every block of the function reached from its entry,
+    /// by applying the transfer to every instruction along every path until the states settle.
+    ///
+    /// Joining states may only lose information, so that they settle in finite time.
+    pub fn dataflow<S: Clone + PartialEq>(
+        &self,
+        function: Option<usize>,
+        entry: S,
+        transfer: impl Fn(&mut S, usize, &Instruction),
+        join: impl Fn(&S, &S) -> S,
+    ) -> HashMap<usize, S> {
+        self.dataflow_with_widening(function, entry, transfer, join, |_, joined| joined.clone())
+    }
+
+    /// Finds the state at the start of every block like [`Self::dataflow`], for states which
+    /// may keep changing along loops.
+    ///
+    /// Once the state of a block has changed too often, the widening is given the previous and the
+    /// joined state, and must lose enough information for the states to settle in finite time.
+    pub fn dataflow_with_widening<S: Clone + PartialEq>(
+        &self,
+        function: Option<usize>,
+        entry: S,
+        transfer: impl Fn(&mut S, usize, &Instruction),
+        join: impl Fn(&S, &S) -> S,
+        widen: impl Fn(&S, &S) -> S,
+    ) -> HashMap<usize, S> {
+        let mut states = HashMap::new();
+        let mut changes: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        if let Some(block) = self.entry(function) {
+            states.insert(block, entry);
+            queue.push_back(block);
+        }
+
+        while let Some(index) = queue.pop_front() {
+            let (Some(block), Some(mut state)) =
+                (self.blocks.get(index), states.get(&index).cloned())
+            else {
+                continue;
+            };
+            for line in block.start..=block.end {
+                if let Some(instruction) = self.instruction(line) {
+                    transfer(&mut state, line, instruction);
+                }
+            }
+
+            for edge in self.successors(index) {
+                let same_function = self
+                    .blocks
+                    .get(edge.to)
+                    .is_some_and(|block| block.function == function);
+                if !same_function {
+                    continue;
+                }
+
+                let joined = match states.get(&edge.to) {
+                    Some(previous) => {
+                        let mut joined = join(previous, &state);
+                        let count = changes.entry(edge.to).or_default();
+                        if joined != *previous {
+                            *count += 1;
+                        }
+                        if *count > WIDENING_THRESHOLD {
+                            joined = widen(previous, &joined);
+                        }
+                        if joined == *previous {
+                            continue;
+                        }
+                        joined
+                    }
+                    None => state.clone(),
+                };
+                states.insert(edge.to, joined);
+                queue.push_back(edge.to);
+            }
+        }
+
+        states
+    }
+
+    /// Gets the line execution actually continues on when it reaches the line,
+    /// which skips comments, or none if execution fails on the way.
+    fn resolve(&self, line: usize) -> Option<usize> {
+        (line..=self.lines.len())
+            .find(|line| !matches!(self.lines.get(line - 1), Some(Line::Comment)))
+            .filter(|line| self.instruction(*line).is_some())
+    }
+
+    /// Gets the line execution continues on after the instruction on the line.
+    fn next(&self, line: usize) -> Option<usize> {
+        self.resolve(line + 1)
+    }
+
+    fn build_blocks(&mut self) {
+        let mut leaders: BTreeSet<usize> = self.functions.keys().copied().collect();
+        for (line, instruction) in self.instructions() {
+            if let Instruction::Jump(_, destination, relative) = instruction {
+                if let Some(target) = jump_target(line, destination, relative.as_ref()) {
+                    leaders.extend(self.resolve(target));
+                }
+                leaders.extend(self.next(line));
+            } else if is_terminator(instruction) {
+                leaders.extend(self.next(line));
+            }
+        }
+
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut previous: Option<usize> = None;
+        for (line, instruction) in self.instructions() {
+            // a block also ends before a line that is not reached from the line before it
+            let continues = previous.is_some_and(|previous| self.next(previous) == Some(line));
+            match blocks.last_mut() {
+                Some(block) if continues && !leaders.contains(&line) => block.end = line,
+                _ => blocks.push(Block {
+                    start: line,
+                    end: line,
+                    function: self.function(line),
+                    computed_jump: false,
+                }),
+            }
+
+            if let (Instruction::Jump(_, Operand::Register(_), _), Some(block)) =
+                (instruction, blocks.last_mut())
+            {
+                block.computed_jump = true;
+            }
+            previous = Some(line);
+        }
+
+        self.blocks = blocks;
+    }
+
+    fn build_edges(&mut self) {
+        let mut edges = Vec::new();
+        for (from, block) in self.blocks.iter().enumerate() {
+            let Some(instruction) = self.instruction(block.end) else {
+                continue;
+            };
+            let next = self.next(block.end).and_then(|line| self.block_at(line));
+            let target = |destination, relative: Option<&Relative>| {
+                jump_target(block.end, destination, relative)
+                    .and_then(|line| self.resolve(line))
+                    .and_then(|line| self.block_at(line))
+            };
+
+            let mut push = |to: Option<usize>, kind| {
+                if let Some(to) = to {
+                    edges.push(Edge { from, to, kind });
+                }
+            };
+            match instruction {
+                Instruction::Stop | Instruction::Return => {}
+                Instruction::Jump(Jump::Unconditional, destination, relative) => {
+                    push(target(destination, relative.as_ref()), EdgeKind::Jump);
+                }
+                Instruction::Jump(_, destination, relative) => {
+                    push(target(destination, relative.as_ref()), EdgeKind::Taken);
+                    push(next, EdgeKind::NotTaken);
+                }
+                _ => push(next, EdgeKind::Next),
+            }
+        }
+
+        self.edges = edges;
+    }
+
+    fn build_calls(&mut self) {
+        self.calls = self
+            .instructions()
+            .filter_map(|(line, instruction)| match instruction {
+                Instruction::Call(destination) => Some(Call {
+                    line,
+                    caller: self.function(line),
+                    callee: jump_target(line, destination, None),
+                }),
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// Exports the control-flow graph of the function, or of the top level, in the DOT format.
+    ///
+    /// Every node is labelled with its lines and their instructions, and blocks ending
+    /// the function by a return or stop instruction are drawn with a double border.
+    /// Blocks of other functions reached by an edge are drawn as plain text naming their function and line.
+    #[must_use]
+    pub fn function_dot(&self, function: Option<usize>) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(
+            dot,
+            "digraph \"{}\" {{",
+            escape(self.function_name(function))
+        );
+        let _ = writeln!(dot, "    node [shape=box, fontname=\"monospace\"];");
+
+        let blocks: Vec<usize> = (0..self.blocks.len())
+            .filter(|index| {
+                self.blocks
+                    .get(*index)
+                    .is_some_and(|b| b.function == function)
+            })
+            .collect();
+
+        for index in &blocks {
+            let Some(block) = self.blocks.get(*index) else {
+                continue;
+            };
+            let mut label = format!("{}\\l", line_range(block.start, block.end));
+            for line in block.start..=block.end {
+                if let Some(instruction) = self.instruction(line) {
+                    let _ = write!(label, "{line}: {}\\l", escape(&instruction.to_string()));
+                }
+            }
+
+            let exits = self
+                .instruction(block.end)
+                .is_some_and(|i| matches!(i, Instruction::Stop | Instruction::Return));
+            let peripheries = if exits { ", peripheries=2" } else { "" };
+            let _ = writeln!(dot, "    b{index} [label=\"{label}\"{peripheries}];");
+
+            if block.computed_jump {
+                let _ = writeln!(dot, "    b{index}_computed [label=\"?\", shape=plaintext];");
+                let _ = writeln!(dot, "    b{index} -> b{index}_computed [style=dashed];");
+            }
+        }
+
+        // blocks of other functions reached by an edge are declared as stubs, labelled with where they are
+        let mut stubs: Vec<usize> = self
+            .edges
+            .iter()
+            .filter(|edge| blocks.contains(&edge.from) && !blocks.contains(&edge.to))
+            .map(|edge| edge.to)
+            .collect();
+        stubs.sort_unstable();
+        stubs.dedup();
+        for index in stubs {
+            let Some(block) = self.blocks.get(index) else {
+                continue;
+            };
+            let _ = writeln!(
+                dot,
+                "    b{index} [label=\"{}, line {}\", shape=plaintext];",
+                escape(self.function_name(block.function)),
+                block.start
+            );
+        }
+
+        for edge in self.edges.iter().filter(|edge| blocks.contains(&edge.from)) {
+            let attributes = match edge.kind {
+                EdgeKind::Next => "",
+                EdgeKind::Jump => " [label=\"jump\"]",
+                EdgeKind::Taken => " [label=\"taken\", color=darkgreen]",
+                EdgeKind::NotTaken => " [label=\"not taken\", color=darkred]",
+            };
+            let _ = writeln!(dot, "    b{} -> b{}{attributes};", edge.from, edge.to);
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Exports the calls between the functions in the DOT format, where every node is labelled
+    /// with the lines of the function.
+    #[must_use]
+    pub fn call_graph_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n    node [shape=box];\n");
+
+        let mut nodes: Vec<Option<usize>> = vec![None];
+        nodes.extend(self.functions.keys().map(|entry| Some(*entry)));
+        for function in &nodes {
+            let lines: Vec<usize> = self
+                .blocks
+                .iter()
+                .filter(|block| block.function == *function)
+                .flat_map(|block| [block.start, block.end])
+                .collect();
+            let range = match (lines.iter().min(), lines.iter().max()) {
+                (Some(start), Some(end)) => format!("\\n{}", line_range(*start, *end)),
+                _ => String::new(),
+            };
+
+            let name = escape(self.function_name(*function));
+            let _ = writeln!(dot, "    \"{name}\" [label=\"{name}{range}\"];");
+        }
+
+        let mut seen = HashSet::new();
+        for call in &self.calls {
+            if !seen.insert((call.caller, call.callee)) {
+                continue;
+            }
+
+            let from = escape(self.function_name(call.caller));
+            match call.callee {
+                Some(callee) => {
+                    let to = escape(self.function_name(self.function(callee)));
+                    let _ = writeln!(dot, "    \"{from}\" -> \"{to}\";");
+                }
+                None => {
+                    let _ = writeln!(dot, "    \"{from}\" -> \"?\" [style=dashed];");
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Describes the lines from the start to the end, inclusive.
+fn line_range(start: usize, end: usize) -> String {
+    if start == end {
+        format!("line {start}")
+    } else {
+        format!("lines {start}-{end}")
+    }
+}
+
+/// Escapes the text for use within a quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes the control-flow graph of every function and the call graph of the program as DOT files,
+/// next to the program file or in the output directory.
+///
+/// Returns the paths of the written files.
+///
+/// # Errors
+/// Returns an error if the program cannot be read or preprocessed, or a graph cannot be written.
+pub fn export_graphs(args: &GraphArguments) -> Result<Vec<PathBuf>, InterpreterError> {
+    let source_code = read_program_file(&args.file_name).map_err(InterpreterError::Argument)?;
+    let program = preprocess(&source_code, &[])?;
+    let cfg = ControlFlowGraph::new(&program);
+
+    let directory = match &args.output {
+        Some(directory) => directory.clone(),
+        None => args
+            .file_name
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_default(),
+    };
+    fs::create_dir_all(&directory)
+        .map_err(|err| InterpreterError::FailedGraphWrite(err.to_string()))?;
+    let stem = args
+        .file_name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut graphs = vec![(format!("{stem}.calls.dot"), cfg.call_graph_dot())];
+    let mut functions: Vec<Option<usize>> = vec![None];
+    functions.extend(cfg.functions().keys().map(|entry| Some(*entry)));
+    for function in functions {
+        let name = match function {
+            Some(_) => cfg.function_name(function),
+            None => TOP_LEVEL_FILE_NAME,
+        };
+        graphs.push((format!("{stem}.{name}.dot"), cfg.function_dot(function)));
+    }
+
+    let mut paths = Vec::with_capacity(graphs.len());
+    for (name, dot) in graphs {
+        let path = directory.join(name);
+        fs::write(&path, dot).map_err(|err| InterpreterError::FailedGraphWrite(err.to_string()))?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod graph {
+    use super::{Call, ControlFlowGraph, Edge, EdgeKind};
+    use crate::preprocess;
+
+    fn cfg(source: &str) -> ControlFlowGraph {
+        ControlFlowGraph::new(&preprocess(source, &[]).unwrap())
+    }
+
+    /// Gets the first and last line of every block.
+    fn blocks(cfg: &ControlFlowGraph) -> Vec<(usize, usize)> {
+        cfg.blocks()
+            .iter()
+            .map(|block| (block.start, block.end))
+            .collect()
+    }
+
+    #[test]
+    fn straight_line_is_one_block() {
+        let cfg = cfg("setb ra 1\n// a comment\naddb ra 1\nstop\n");
+        let expected = vec![(1, 4)];
+
+        let actual = blocks(&cfg);
+
+        assert_eq!(actual, expected);
+        assert_eq!(cfg.edges(), []);
+    }
+
+    #[test]
+    fn conditional_jump_splits_blocks() {
+        let cfg = cfg("setb ra 3\nsubb ra 1\njnz -1\nstop\n");
+        let expected_blocks = vec![(1, 1), (2, 3), (4, 4)];
+        let expected_edges = [
+            Edge {
+                from: 0,
+                to: 1,
+                kind: EdgeKind::Next,
+            },
+            Edge {
+                from: 1,
+                to: 1,
+                kind: EdgeKind::Taken,
+            },
+            Edge {
+                from: 1,
+                to: 2,
+                kind: EdgeKind::NotTaken,
+            },
+        ];
+
+        let actual_blocks = blocks(&cfg);
+
+        assert_eq!(actual_blocks, expected_blocks);
+        assert_eq!(cfg.edges(), expected_edges);
+    }
+
+    #[test]
+    fn unconditional_jump_has_no_fall_through() {
+        let cfg = cfg("jmp 3\nsetb ra 1\nstop\n");
+        let expected = [
+            Edge {
+                from: 0,
+                to: 2,
+                kind: EdgeKind::Jump,
+            },
+            Edge {
+                from: 1,
+                to: 2,
+                kind: EdgeKind::Next,
+            },
+        ];
+
+        let actual = cfg.edges();
+
+        assert_eq!(blocks(&cfg), vec![(1, 1), (2, 2), (3, 3)]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn jump_to_comment_enters_next_instruction() {
+        let cfg = cfg("jmp 3\nsetb ra 1\n// skipped\nstop\n");
+        let expected = [
+            Edge {
+                from: 0,
+                to: 2,
+                kind: EdgeKind::Jump,
+            },
+            Edge {
+                from: 1,
+                to: 2,
+                kind: EdgeKind::Next,
+            },
+        ];
+
+        let actual = cfg.edges();
+
+        assert_eq!(blocks(&cfg), vec![(1, 1), (2, 2), (4, 4)]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn computed_jump_has_no_edges() {
+        let cfg = cfg("setb ra 3\njmp ra\nstop\n");
+
+        let actual: Vec<bool> = cfg.blocks().iter().map(|b| b.computed_jump).collect();
+
+        assert_eq!(actual, vec![true, false]);
+        assert_eq!(cfg.edges(), []);
+    }
+
+    #[test]
+    fn calls_do_not_end_blocks() {
+        let cfg = cfg("call inc\ncall inc\nstop\n\nfn inc:\n  addb ra 1\n  ret\n");
+        let expected_calls = [
+            Call {
+                line: 1,
+                caller: None,
+                callee: Some(6),
+            },
+            Call {
+                line: 2,
+                caller: None,
+                callee: Some(6),
+            },
+        ];
+
+        let actual_blocks = blocks(&cfg);
+
+        assert_eq!(actual_blocks, vec![(1, 3), (6, 7)]);
+        assert_eq!(cfg.calls(), expected_calls);
+        assert_eq!(cfg.edges(), []);
+    }
+
+    #[test]
+    fn blocks_belong_to_functions() {
+        let cfg = cfg("call f\nstop\n\nfn f:\n  call g\n  ret\n\nfn g:\n  ret\n");
+        let expected = vec![None, Some(5), Some(9)];
+
+        let actual: Vec<Option<usize>> = cfg.blocks().iter().map(|b| b.function).collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn block_before_function_has_no_fall_through() {
+        let cfg = cfg("setb ra 1\n\nfn f:\n  ret\n");
+
+        let actual = cfg.edges();
+
+        assert_eq!(blocks(&cfg), vec![(1, 1), (4, 4)]);
+        assert_eq!(actual, []);
+    }
+}
+
+#[cfg(test)]
+mod dot {
+    use super::ControlFlowGraph;
+    use crate::preprocess;
+
+    const SOURCE: &str =
+        "call f\nstop\n\nfn f:\n  cmpb ra 0\n  jiz +2\n  call g\n  ret\n\nfn g:\n  ret\n";
+
+    #[test]
+    fn function() {
+        let cfg = ControlFlowGraph::new(&preprocess(SOURCE, &[]).unwrap());
+        let expected = "\
+digraph \"f\" {
+    node [shape=box, fontname=\"monospace\"];
+    b1 [label=\"lines 5-6\\l5: cmpb ra 0\\l6: jiz +2\\l\"];
+    b2 [label=\"line 7\\l7: call 11\\l\"];
+    b3 [label=\"line 8\\l8: ret\\l\", peripheries=2];
+    b1 -> b3 [label=\"taken\", color=darkgreen];
+    b1 -> b2 [label=\"not taken\", color=darkred];
+    b2 -> b3;
+}
+";
+
+        let actual = cfg.function_dot(Some(5));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn edge_into_other_function() {
+        let cfg = ControlFlowGraph::new(
+            &preprocess("jmp 5\n\nfn f:\n  setb ra 1\n  stop\n", &[]).unwrap(),
+        );
+        let expected = "\
+digraph \"<top level>\" {
+    node [shape=box, fontname=\"monospace\"];
+    b0 [label=\"line 1\\l1: jmp 5\\l\"];
+    b2 [label=\"f, line 5\", shape=plaintext];
+    b0 -> b2 [label=\"jump\"];
+}
+";
+
+        let actual = cfg.function_dot(None);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn call_graph() {
+        let cfg = ControlFlowGraph::new(&preprocess(SOURCE, &[]).unwrap());
+        let expected = "\
+digraph calls {
+    node [shape=box];
+    \"<top level>\" [label=\"<top level>\\nlines 1-2\"];
+    \"f\" [label=\"f\\nlines 5-8\"];
+    \"g\" [label=\"g\\nline 11\"];
+    \"<top level>\" -> \"f\";
+    \"f\" -> \"g\";
+}
+";
+
+        let actual = cfg.call_graph_dot();
+
+        assert_eq!(actual, expected);
+    }
+}
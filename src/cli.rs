@@ -1,16 +1,45 @@
 use crate::{
-    constant::{GIGA_BYTE, KILO_BYTE, MEGA_BYTE},
+    bytecode::is_assembled,
+    constant::{Byte, GIGA_BYTE, KILO_BYTE, MEGA_BYTE},
     error::{ArgumentError, InterpreterError},
-    utils::Writer,
+    hook::{
+        Coverage, Hook, LoopDetector, Profiler, Snapshotter, TraceFilter, TraceFormat, Tracer,
+        UninitialisedDetector,
+    },
+    host::HostFunctions,
+    snapshot::Snapshot,
+    utils::{stdin_reader, Capture, Reader, SharedBuffer, Writer},
+};
+use clap::{Args, Parser, Subcommand};
+use std::{
+    fs::File,
+    io::{stderr, stdout, BufReader, BufWriter, Cursor, ErrorKind, Read},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
 };
-use clap::Parser;
-use std::{fs::File, io::stdout, path::PathBuf};
 
 pub struct Configuration {
     pub print_instructions_executed: bool,
     pub instructions_executed: u64,
     pub output: Box<dyn Writer>,
+    /// Where reports of checked mode and hooks are written, kept apart from the output
+    /// such that they are never compared against the expected output.
+    pub report: Box<dyn Writer>,
+    pub input: Box<dyn Reader>,
     pub debug: bool,
+    pub tui: bool,
+    pub checked: bool,
+    /// The amount of instructions after which execution is stopped with an error.
+    pub instruction_limit: Option<u64>,
+    /// The time after which execution is stopped with an error.
+    pub time_limit: Option<Duration>,
+    /// A copy of everything written to the output, only kept when running the terminal user interface.
+    pub captured_output: Option<SharedBuffer>,
+    pub hooks: Vec<Box<dyn Hook>>,
+    pub host_functions: HostFunctions,
+    /// The snapshot the machine is restored from before running.
+    pub restore: Option<Snapshot>,
 }
 
 impl Configuration {
@@ -20,7 +49,58 @@ impl Configuration {
             print_instructions_executed: false,
             instructions_executed: 0,
             output: Box::new(Vec::new()),
+            report: Box::new(Vec::new()),
+            input: Box::new(Cursor::new(Vec::new())),
+            debug: false,
+            tui: false,
+            checked: false,
+            instruction_limit: None,
+            time_limit: None,
+            captured_output: None,
+            hooks: Vec::new(),
+            host_functions: HostFunctions::default(),
+            restore: None,
+        }
+    }
+
+    /// Creates a configuration that writes all output to the buffer, as used when running tests.
+    pub fn captured(buffer: SharedBuffer) -> Self {
+        Self {
+            print_instructions_executed: false,
+            instructions_executed: 0,
+            output: Box::new(Capture::new(buffer, None)),
+            report: Box::new(stderr()),
+            input: Box::new(Cursor::new(Vec::new())),
+            debug: false,
+            tui: false,
+            checked: false,
+            instruction_limit: None,
+            time_limit: None,
+            captured_output: None,
+            hooks: Vec::new(),
+            host_functions: HostFunctions::default(),
+            restore: None,
+        }
+    }
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            print_instructions_executed: false,
+            instructions_executed: 0,
+            output: Box::new(stdout()),
+            report: Box::new(stderr()),
+            input: Box::new(stdin_reader()),
             debug: false,
+            tui: false,
+            checked: false,
+            instruction_limit: None,
+            time_limit: None,
+            captured_output: None,
+            hooks: Vec::new(),
+            host_functions: HostFunctions::default(),
+            restore: None,
         }
     }
 }
@@ -35,21 +115,213 @@ impl TryFrom<Arguments> for Configuration {
                 .write(true)
                 .truncate(true)
                 .open(path)
-                .map(|f| Box::new(f) as Box<dyn Writer>)
+                .map(|f| Some(Box::new(f) as Box<dyn Writer>))
                 .map_err(|err| InterpreterError::FailedOutputFileCreation(err.to_string()))?,
-            None => Box::new(stdout()),
+            None => None,
+        };
+
+        // the terminal user interface owns the screen, and expected output is compared afterwards,
+        // so output is captured and only forwarded if it is not meant for stdout
+        let (output, captured_output) = if args.tui || args.golden.expect {
+            let buffer = SharedBuffer::default();
+            let capture = Capture::new(buffer.clone(), output);
+            (Box::new(capture) as Box<dyn Writer>, Some(buffer))
+        } else {
+            (output.unwrap_or_else(|| Box::new(stdout())), None)
+        };
+
+        let input = match args.input {
+            Some(path) => File::open(path)
+                .map(|f| Box::new(BufReader::new(f)) as Box<dyn Reader>)
+                .map_err(|err| InterpreterError::FailedInputFileOpen(err.to_string()))?,
+            None => Box::new(stdin_reader()),
+        };
+
+        let mut hooks: Vec<Box<dyn Hook>> = Vec::new();
+        if let Some(path) = args.trace.trace {
+            let file = File::create(path)
+                .map_err(|err| InterpreterError::FailedTraceFileCreation(err.to_string()))?;
+            let filter = TraceFilter {
+                functions: args.trace.trace_functions,
+                lines: args.trace.trace_lines,
+                sample: args.trace.trace_sample.unwrap_or(1),
+                limit: args.trace.trace_limit,
+            };
+
+            hooks.push(Box::new(Tracer::new(
+                Box::new(BufWriter::new(file)),
+                args.trace.trace_format,
+                filter,
+            )));
+        }
+
+        if args.profile.profile || args.profile.flamegraph.is_some() {
+            let folded = match args.profile.flamegraph {
+                Some(path) => File::create(path)
+                    .map(|f| Some(Box::new(BufWriter::new(f)) as Box<dyn Writer>))
+                    .map_err(|err| {
+                        InterpreterError::FailedFlamegraphFileCreation(err.to_string())
+                    })?,
+                None => None,
+            };
+
+            hooks.push(Box::new(Profiler::new(args.profile.profile, folded)));
+        }
+
+        if args.detect_loops {
+            hooks.push(Box::new(LoopDetector::new()));
+        }
+
+        if args.detect_uninitialised {
+            hooks.push(Box::new(UninitialisedDetector::new()));
+        }
+
+        if let (Some(path), Some(line)) = (args.snapshot.snapshot, args.snapshot.snapshot_at) {
+            hooks.push(Box::new(Snapshotter::new(path, line)));
+        }
+
+        if let Some(tracefile) = args.coverage.coverage {
+            hooks.push(Box::new(Coverage::new(
+                args.file_name.display().to_string(),
+                tracefile,
+                args.coverage.coverage_listing,
+            )));
+        }
+
+        let restore = match args.snapshot.restore {
+            Some(path) => Some(Snapshot::read(&path)?),
+            None => None,
         };
 
         let c = Self {
             print_instructions_executed: args.instructions,
             instructions_executed: 0,
             output,
+            report: Box::new(stderr()),
+            input,
             debug: args.debug,
+            tui: args.tui,
+            checked: args.checked,
+            instruction_limit: args.limits.max_instructions,
+            time_limit: args.limits.time_limit,
+            captured_output,
+            hooks,
+            host_functions: HostFunctions::default(),
+            restore,
         };
         Ok(c)
     }
 }
 
+#[derive(Debug, Parser, PartialEq)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum Command {
+    /// Interprets a program
+    Run(Box<Arguments>),
+    /// Runs the test functions of one or more programs, which are functions named 'test_*'
+    Test(TestArguments),
+    /// Assembles a program into a binary file, which can be run in place of its source code
+    Assemble(AssembleArguments),
+    /// Prints an assembled program as source code
+    Disasm(DisassembleArguments),
+    /// Formats one or more programs in place
+    Fmt(FormatArguments),
+    /// Warns about common mistakes in one or more programs, without running them
+    Lint(LintArguments),
+    /// Writes the control-flow graph of every function and the call graph of a program as DOT files
+    Graph(GraphArguments),
+    /// Estimates the stack usage of every function of a program, without running it
+    Stack(StackArguments),
+    /// Runs a language server over stdio, for editors supporting the Language Server Protocol
+    Lsp,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct StackArguments {
+    #[arg(value_name = "FILE")]
+    pub file_name: PathBuf,
+
+    /// The size of the stack to compare the usage with; requires a size suffix: b/B = byte, k/K = kilobyte, m/M = megabyte, g/G = gigabyte
+    #[arg(long = "stack", short = 's', value_name = "SIZE", default_value = "4m")]
+    pub stack_size: String,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct GraphArguments {
+    #[arg(value_name = "FILE")]
+    pub file_name: PathBuf,
+
+    /// The directory to write the graphs to, which defaults to the directory of the program
+    #[arg(short = 'o', long = "output", value_name = "DIRECTORY")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct LintArguments {
+    #[arg(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct FormatArguments {
+    #[arg(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Only checks whether the programs are formatted, printing the changes formatting would make
+    #[arg(long = "check")]
+    pub check: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct AssembleArguments {
+    #[arg(required = true, value_name = "FILE")]
+    pub file_name: PathBuf,
+
+    /// Writes the assembled program to the specified file, otherwise the program file with a '.kbc' extension is used
+    #[arg(long = "output", short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Includes the source code of every line, such that the debugger and reports show it
+    #[arg(long = "debug-info", short = 'g')]
+    pub debug_info: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct TestArguments {
+    #[arg(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Only runs the tests whose name contains the filter
+    #[arg(long = "filter", short = 'f', value_name = "NAME")]
+    pub filter: Option<String>,
+
+    /// Writes a report of the test results to the specified file as JSON
+    #[arg(long = "json", value_name = "FILE")]
+    pub json: Option<PathBuf>,
+
+    /// The size of the stack of each test; requires a size suffix: b/B = byte, k/K = kilobyte, m/M = megabyte, g/G = gigabyte
+    #[arg(long = "stack", short = 's', value_name = "SIZE", default_value = "4m")]
+    pub stack_size: String,
+
+    #[command(flatten)]
+    pub limits: LimitArguments,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct DisassembleArguments {
+    #[arg(required = true, value_name = "FILE")]
+    pub file_name: PathBuf,
+
+    /// Writes the source code to the specified file, otherwise stdout is used
+    #[arg(long = "output", short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
 #[derive(Debug, Parser, PartialEq)]
 pub struct Arguments {
     #[arg(required = true, value_name = "FILE")]
@@ -63,19 +335,168 @@ pub struct Arguments {
     #[arg(long = "output", short = 'o', value_name = "FILE")]
     output: Option<PathBuf>,
 
+    /// Uses the specified file as input for read instructions, otherwise stdin is used
+    #[arg(long = "input", value_name = "FILE")]
+    input: Option<PathBuf>,
+
     /// Interprets the program in debug mode
     #[arg(long = "debug", short = 'd')]
     debug: bool,
 
+    /// Interprets the program in debug mode using a full-screen terminal user interface
+    #[arg(long = "tui", short = 't', conflicts_with = "debug")]
+    tui: bool,
+
+    /// Checks that every return matches its call, and prints the maximum call depth to stderr after the program is finished
+    #[arg(long = "checked", short = 'c')]
+    checked: bool,
+
+    /// Stops with an error when the program returns to a state it has been in before, as it would then loop forever;
+    /// only loops repeating twice within 65536 backward jumps or returns are detected
+    #[arg(long = "detect-loops")]
+    detect_loops: bool,
+
+    /// Prints every read of a register that was never written to stderr after the program is finished, along with its line
+    #[arg(long = "detect-uninitialised")]
+    detect_uninitialised: bool,
+
+    /// Writes the state of the machine to the specified file if an instruction fails to execute, and as JSON to the file with '.json' appended
+    #[arg(long = "dump-on-error", value_name = "PATH")]
+    pub dump_on_error: Option<PathBuf>,
+
     /// The size of the stack; requires a size suffix: b/B = byte, k/K = kilobyte, m/M = megabyte, g/G = gigabyte
     #[arg(long = "stack", short = 's', value_name = "SIZE", default_value = "4m")]
     pub(super) stack_size: String,
+
+    #[command(flatten)]
+    trace: TraceArguments,
+
+    #[command(flatten)]
+    profile: ProfileArguments,
+
+    #[command(flatten)]
+    coverage: CoverageArguments,
+
+    #[command(flatten)]
+    pub golden: GoldenArguments,
+
+    #[command(flatten)]
+    pub limits: LimitArguments,
+
+    #[command(flatten)]
+    snapshot: SnapshotArguments,
+}
+
+#[derive(Args, Debug, Default, PartialEq)]
+struct SnapshotArguments {
+    /// Writes the complete state of the machine to the specified file, the first time the line given by --snapshot-at is reached
+    #[arg(long = "snapshot", value_name = "FILE", requires = "snapshot_at")]
+    snapshot: Option<PathBuf>,
+
+    /// The line before which the snapshot is taken
+    #[arg(long = "snapshot-at", value_name = "LINE", requires = "snapshot")]
+    snapshot_at: Option<usize>,
+
+    /// Resumes execution from the snapshot in the specified file, which must have been taken of the same program
+    #[arg(long = "restore", value_name = "FILE")]
+    restore: Option<PathBuf>,
+}
+
+/// Limits on the execution, which are not applied when debugging.
+#[derive(Args, Clone, Copy, Debug, Default, PartialEq)]
+pub struct LimitArguments {
+    /// Stops with an error after executing the specified amount of instructions
+    #[arg(
+        long = "max-instructions",
+        value_name = "COUNT",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub max_instructions: Option<u64>,
+
+    /// Stops with an error after running for the specified duration, given in ms, s or m, such as '500ms'
+    #[arg(long = "time-limit", value_name = "DURATION", value_parser = parse_duration)]
+    pub time_limit: Option<Duration>,
+}
+
+#[derive(Args, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GoldenArguments {
+    /// Compares the output against the '// expect:' comment block or the sidecar '.out' file, instead of printing it
+    #[arg(long = "expect", short = 'e', conflicts_with_all = ["debug", "tui"])]
+    pub expect: bool,
+
+    /// Replaces the expected output with the actual output
+    #[arg(long = "bless", requires = "expect")]
+    pub bless: bool,
+}
+
+#[derive(Args, Debug, Default, PartialEq)]
+struct CoverageArguments {
+    /// Writes the executed lines and jumps to the specified file in the lcov format, adding to it if it already exists
+    #[arg(long = "coverage", value_name = "FILE")]
+    coverage: Option<PathBuf>,
+
+    /// Writes the source annotated with the execution count of every line to the specified file
+    #[arg(long = "coverage-listing", value_name = "FILE", requires = "coverage")]
+    coverage_listing: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Default, PartialEq)]
+struct ProfileArguments {
+    /// Prints the instructions executed per function and the most executed lines to stderr after the program is finished
+    #[arg(long = "profile", short = 'p')]
+    profile: bool,
+
+    /// Writes the instructions executed per chain of calls to the specified file, in the folded format used by flamegraph tools
+    #[arg(long = "flamegraph", value_name = "FILE")]
+    flamegraph: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Default, PartialEq)]
+struct TraceArguments {
+    /// Writes a record of every executed instruction to the specified file
+    #[arg(long = "trace", value_name = "FILE")]
+    trace: Option<PathBuf>,
+
+    /// The format of the trace records
+    #[arg(
+        long = "trace-format",
+        value_name = "FORMAT",
+        default_value = "human",
+        requires = "trace"
+    )]
+    trace_format: TraceFormat,
+
+    /// Only traces instructions executed directly within the named function, may be repeated
+    #[arg(long = "trace-function", value_name = "NAME", requires = "trace")]
+    trace_functions: Vec<String>,
+
+    /// Only traces instructions on the specified lines, given as 'start-end' or 'line'
+    #[arg(
+        long = "trace-lines",
+        value_name = "RANGE",
+        value_parser = parse_line_range,
+        requires = "trace"
+    )]
+    trace_lines: Option<RangeInclusive<usize>>,
+
+    /// Only traces every n-th instruction matching the other trace filters
+    #[arg(
+        long = "trace-sample",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "trace"
+    )]
+    trace_sample: Option<u64>,
+
+    /// Stops tracing after the specified amount of records
+    #[arg(long = "trace-limit", value_name = "N", requires = "trace")]
+    trace_limit: Option<u64>,
 }
 
 /// Parses the indicated stack size by the stack size flag.
 ///
 /// Ensures correct format and numeric values for the underlying architecture.
-pub(super) fn parse_stack_size(s: &str) -> Result<usize, ArgumentError> {
+pub(crate) fn parse_stack_size(s: &str) -> Result<usize, ArgumentError> {
     let (num, size_suffix) = s
         .split_at_checked(s.len() - 1)
         .ok_or(ArgumentError::CouldNotSplitSuffix)?;
@@ -101,12 +522,108 @@ pub(super) fn parse_stack_size(s: &str) -> Result<usize, ArgumentError> {
     Ok(stack_size)
 }
 
+/// The contents of a program file, which is either source code or an assembled program.
+#[derive(Debug, PartialEq)]
+pub enum ProgramFile {
+    Source(String),
+    Assembled(Vec<Byte>),
+}
+
+/// Reads a program file, detecting whether it contains source code or an assembled program.
+///
+/// # Errors
+/// Returns an error if the file does not exist or cannot be read, or if source code is not valid UTF-8.
+pub fn read_program(file_name: &Path) -> Result<ProgramFile, ArgumentError> {
+    let content = read_program_bytes(file_name)?;
+    if is_assembled(&content) {
+        return Ok(ProgramFile::Assembled(content));
+    }
+
+    String::from_utf8(content)
+        .map(ProgramFile::Source)
+        .map_err(|_| ArgumentError::ProgramFileInvalidEncoding)
+}
+
+/// Reads a program file containing source code.
+///
+/// # Errors
+/// Returns an error if the file does not exist, cannot be read, or is not valid UTF-8.
+pub fn read_program_file(file_name: &Path) -> Result<String, ArgumentError> {
+    String::from_utf8(read_program_bytes(file_name)?)
+        .map_err(|_| ArgumentError::ProgramFileInvalidEncoding)
+}
+
+fn read_program_bytes(file_name: &Path) -> Result<Vec<Byte>, ArgumentError> {
+    if !file_name.is_file() {
+        return Err(ArgumentError::NotAFile(file_name.to_path_buf()));
+    }
+
+    let mut file = File::options()
+        .read(true)
+        .write(false)
+        .truncate(false)
+        .create(false)
+        .open(file_name)
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => ArgumentError::FileNotFound(file_name.to_path_buf()),
+            ErrorKind::PermissionDenied => {
+                ArgumentError::LackingPermissions("open the specified program file".to_string())
+            }
+            _ => ArgumentError::UnknownProgramFileIssue(err.to_string()),
+        })?;
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)
+        .map_err(|err| ArgumentError::UnknownProgramFileIssue(err.to_string()))?;
+
+    Ok(content)
+}
+
+/// Parses an inclusive range of lines, given as 'start-end' or as a single line.
+pub(super) fn parse_line_range(s: &str) -> Result<RangeInclusive<usize>, ArgumentError> {
+    let invalid = || ArgumentError::InvalidLineRange(s.to_string());
+    let (start, end) = s.split_once('-').unwrap_or((s, s));
+
+    let start = start.parse::<usize>().map_err(|_| invalid())?;
+    let end = end.parse::<usize>().map_err(|_| invalid())?;
+
+    if start > end {
+        return Err(invalid());
+    }
+
+    Ok(start..=end)
+}
+
+/// Parses a duration given as a number followed by 'ms', 's' or 'm'.
+pub(super) fn parse_duration(s: &str) -> Result<Duration, ArgumentError> {
+    let invalid = || ArgumentError::InvalidDuration(s.to_string());
+    let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = s.split_at(split);
+    let amount = amount.parse::<u64>().map_err(|_| invalid())?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "s" => Ok(Duration::from_secs(amount)),
+        "m" => amount
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
 #[cfg(test)]
 mod regression {
     mod debug {
         use std::path::PathBuf;
 
-        use crate::Arguments;
+        use crate::{
+            cli::{
+                CoverageArguments, GoldenArguments, LimitArguments, ProfileArguments,
+                SnapshotArguments, TraceArguments,
+            },
+            Arguments,
+        };
         use clap::Parser;
 
         #[test]
@@ -116,8 +633,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: None,
+                input: None,
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -132,8 +661,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: None,
+                input: None,
                 debug: true,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -148,8 +689,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: None,
+                input: None,
                 debug: true,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -161,7 +714,13 @@ mod regression {
     mod instructions {
         use std::path::PathBuf;
 
-        use crate::Arguments;
+        use crate::{
+            cli::{
+                CoverageArguments, GoldenArguments, LimitArguments, ProfileArguments,
+                SnapshotArguments, TraceArguments,
+            },
+            Arguments,
+        };
         use clap::Parser;
 
         #[test]
@@ -171,8 +730,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: None,
+                input: None,
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -187,8 +758,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: true,
                 output: None,
+                input: None,
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -203,8 +786,61 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: true,
                 output: None,
+                input: None,
+                debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
+
+            let actual = Arguments::parse_from(args);
+
+            assert_eq!(actual, expected);
+        }
+    }
+
+    mod input {
+        use std::path::PathBuf;
+
+        use crate::{
+            cli::{
+                CoverageArguments, GoldenArguments, LimitArguments, ProfileArguments,
+                SnapshotArguments, TraceArguments,
+            },
+            Arguments,
+        };
+        use clap::Parser;
+
+        #[test]
+        fn long() {
+            let args = ["", "file.kasm", "--input", "input.txt"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: Some(PathBuf::from("input.txt")),
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -216,7 +852,13 @@ mod regression {
     mod output {
         use std::path::PathBuf;
 
-        use crate::Arguments;
+        use crate::{
+            cli::{
+                CoverageArguments, GoldenArguments, LimitArguments, ProfileArguments,
+                SnapshotArguments, TraceArguments,
+            },
+            Arguments,
+        };
         use clap::Parser;
 
         #[test]
@@ -226,8 +868,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: None,
+                input: None,
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -242,8 +896,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: Some(PathBuf::from("file.txt")),
+                input: None,
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -258,8 +924,20 @@ mod regression {
                 file_name: PathBuf::from("file.kasm"),
                 instructions: false,
                 output: Some(PathBuf::from("file.txt")),
+                input: None,
                 debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
                 stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
             };
 
             let actual = Arguments::parse_from(args);
@@ -267,76 +945,814 @@ mod regression {
             assert_eq!(actual, expected);
         }
     }
-}
 
-#[cfg(test)]
-mod parse_stack_size {
-    use super::parse_stack_size;
-    use crate::{
-        constant::{GIGA_BYTE, KILO_BYTE, MEGA_BYTE},
-        error::ArgumentError,
-    };
-
-    #[test]
-    fn could_not_split_suffix_error() {
-        let input = String::from("500ø");
-        let expected = Err(ArgumentError::CouldNotSplitSuffix);
-
-        let actual = parse_stack_size(&input);
+    mod tui {
+        use std::path::PathBuf;
 
-        assert_eq!(actual, expected);
-    }
+        use crate::{
+            cli::{
+                CoverageArguments, GoldenArguments, LimitArguments, ProfileArguments,
+                SnapshotArguments, TraceArguments,
+            },
+            Arguments,
+        };
+        use clap::{error::ErrorKind, Parser};
 
-    #[test]
-    fn invalid_stack_size_suffix_error() {
-        let input = String::from("500l");
-        let expected = Err(ArgumentError::InvalidStackSizeSuffix("l".to_string()));
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: None,
+                debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
 
-        let actual = parse_stack_size(&input);
+            let actual = Arguments::parse_from(args);
 
-        assert_eq!(actual, expected);
-    }
+            assert_eq!(actual, expected);
+        }
 
-    #[test]
-    fn invalid_initial_stack_size_error() {
-        let input = format!("{}0b", usize::MAX);
-        let expected = Err(ArgumentError::InvalidInitialStackSize(format!(
-            "{}0",
-            usize::MAX
-        )));
+        #[test]
+        fn long() {
+            let args = ["", "file.kasm", "--tui"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: None,
+                debug: false,
+                tui: true,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
 
-        let actual = parse_stack_size(&input);
+            let actual = Arguments::parse_from(args);
 
-        assert_eq!(actual, expected);
-    }
+            assert_eq!(actual, expected);
+        }
 
-    #[test]
-    fn invalid_computed_stack_size_error() {
-        let input = format!("{}k", usize::MAX);
-        let expected = Err(ArgumentError::InvalidComputedStackSize);
+        #[test]
+        fn short() {
+            let args = ["", "file.kasm", "-t"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: None,
+                debug: false,
+                tui: true,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
 
-        let actual = parse_stack_size(&input);
+            let actual = Arguments::parse_from(args);
 
-        assert_eq!(actual, expected);
-    }
+            assert_eq!(actual, expected);
+        }
 
-    #[test]
-    fn lowercase_byte() -> Result<(), ArgumentError> {
-        let input = String::from("2b");
-        let expected = 2;
+        #[test]
+        fn conflicts_with_debug() {
+            let args = ["", "file.kasm", "--tui", "--debug"];
+            let expected = ErrorKind::ArgumentConflict;
 
-        let actual = parse_stack_size(&input)?;
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
 
-        assert_eq!(actual, expected);
-        Ok(())
+            assert_eq!(actual, Err(expected));
+        }
     }
 
-    #[test]
-    fn uppercase_byte() -> Result<(), ArgumentError> {
-        let input = String::from("2B");
-        let expected = 2;
+    mod checked {
+        use std::path::PathBuf;
 
-        let actual = parse_stack_size(&input)?;
+        use crate::{
+            cli::{
+                CoverageArguments, GoldenArguments, LimitArguments, ProfileArguments,
+                SnapshotArguments, TraceArguments,
+            },
+            Arguments,
+        };
+        use clap::Parser;
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: None,
+                debug: false,
+                tui: false,
+                checked: false,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
+
+            let actual = Arguments::parse_from(args);
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn long() {
+            let args = ["", "file.kasm", "--checked"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: None,
+                debug: false,
+                tui: false,
+                checked: true,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
+
+            let actual = Arguments::parse_from(args);
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn short() {
+            let args = ["", "file.kasm", "-c"];
+            let expected = Arguments {
+                file_name: PathBuf::from("file.kasm"),
+                instructions: false,
+                output: None,
+                input: None,
+                debug: false,
+                tui: false,
+                checked: true,
+                detect_loops: false,
+                detect_uninitialised: false,
+                dump_on_error: None,
+                stack_size: String::from("4m"),
+                trace: TraceArguments::default(),
+                profile: ProfileArguments::default(),
+                coverage: CoverageArguments::default(),
+                golden: GoldenArguments::default(),
+                limits: LimitArguments::default(),
+                snapshot: SnapshotArguments::default(),
+            };
+
+            let actual = Arguments::parse_from(args);
+
+            assert_eq!(actual, expected);
+        }
+    }
+
+    mod trace {
+        use std::path::PathBuf;
+
+        use crate::{cli::TraceArguments, hook::TraceFormat, Arguments};
+        use clap::{error::ErrorKind, Parser};
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = TraceArguments::default();
+
+            let actual = Arguments::parse_from(args).trace;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn all_options() {
+            let args = [
+                "",
+                "file.kasm",
+                "--trace",
+                "trace.jsonl",
+                "--trace-format",
+                "json",
+                "--trace-function",
+                "inc_ra",
+                "--trace-function",
+                "dec_ra",
+                "--trace-lines",
+                "10-20",
+                "--trace-sample",
+                "5",
+                "--trace-limit",
+                "100",
+            ];
+            let expected = TraceArguments {
+                trace: Some(PathBuf::from("trace.jsonl")),
+                trace_format: TraceFormat::Json,
+                trace_functions: vec![String::from("inc_ra"), String::from("dec_ra")],
+                trace_lines: Some(10..=20),
+                trace_sample: Some(5),
+                trace_limit: Some(100),
+            };
+
+            let actual = Arguments::parse_from(args).trace;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn filter_requires_trace() {
+            let args = ["", "file.kasm", "--trace-lines", "10-20"];
+            let expected = ErrorKind::MissingRequiredArgument;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual, Err(expected));
+        }
+
+        #[test]
+        fn zero_sample_is_invalid() {
+            let args = ["", "file.kasm", "--trace", "t.txt", "--trace-sample", "0"];
+            let expected = ErrorKind::ValueValidation;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual, Err(expected));
+        }
+    }
+
+    mod profile {
+        use std::path::PathBuf;
+
+        use crate::{cli::ProfileArguments, Arguments};
+        use clap::Parser;
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = ProfileArguments::default();
+
+            let actual = Arguments::parse_from(args).profile;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn short() {
+            let args = ["", "file.kasm", "-p"];
+            let expected = ProfileArguments {
+                profile: true,
+                flamegraph: None,
+            };
+
+            let actual = Arguments::parse_from(args).profile;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn flamegraph() {
+            let args = ["", "file.kasm", "--flamegraph", "out.folded"];
+            let expected = ProfileArguments {
+                profile: false,
+                flamegraph: Some(PathBuf::from("out.folded")),
+            };
+
+            let actual = Arguments::parse_from(args).profile;
+
+            assert_eq!(actual, expected);
+        }
+    }
+
+    mod coverage {
+        use std::path::PathBuf;
+
+        use crate::{cli::CoverageArguments, Arguments};
+        use clap::{error::ErrorKind, Parser};
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = CoverageArguments::default();
+
+            let actual = Arguments::parse_from(args).coverage;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn with_listing() {
+            let args = [
+                "",
+                "file.kasm",
+                "--coverage",
+                "lcov.info",
+                "--coverage-listing",
+                "file.txt",
+            ];
+            let expected = CoverageArguments {
+                coverage: Some(PathBuf::from("lcov.info")),
+                coverage_listing: Some(PathBuf::from("file.txt")),
+            };
+
+            let actual = Arguments::parse_from(args).coverage;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn listing_requires_coverage() {
+            let args = ["", "file.kasm", "--coverage-listing", "file.txt"];
+            let expected = ErrorKind::MissingRequiredArgument;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual, Err(expected));
+        }
+    }
+
+    mod commands {
+        use std::path::PathBuf;
+
+        use crate::{
+            cli::{
+                AssembleArguments, DisassembleArguments, FormatArguments, GraphArguments,
+                LimitArguments, LintArguments, StackArguments,
+            },
+            Cli, Command, TestArguments,
+        };
+        use clap::{error::ErrorKind, Parser};
+
+        #[test]
+        fn run() {
+            let args = ["", "run", "file.kasm", "-i"];
+
+            let actual = Cli::parse_from(args).command;
+
+            assert!(
+                matches!(actual, Command::Run(args) if args.file_name.as_os_str() == "file.kasm" && args.instructions)
+            );
+        }
+
+        #[test]
+        fn test() {
+            let args = [
+                "",
+                "test",
+                "a.kasm",
+                "b.kasm",
+                "-f",
+                "add",
+                "--json",
+                "report.json",
+            ];
+            let expected = Command::Test(TestArguments {
+                files: vec![PathBuf::from("a.kasm"), PathBuf::from("b.kasm")],
+                filter: Some(String::from("add")),
+                json: Some(PathBuf::from("report.json")),
+                stack_size: String::from("4m"),
+                limits: LimitArguments::default(),
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn assemble() {
+            let args = ["", "assemble", "file.kasm", "-o", "file.bin", "-g"];
+            let expected = Command::Assemble(AssembleArguments {
+                file_name: PathBuf::from("file.kasm"),
+                output: Some(PathBuf::from("file.bin")),
+                debug_info: true,
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn disasm() {
+            let args = ["", "disasm", "file.kbc"];
+            let expected = Command::Disasm(DisassembleArguments {
+                file_name: PathBuf::from("file.kbc"),
+                output: None,
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn fmt() {
+            let args = ["", "fmt", "a.kasm", "b.kasm", "--check"];
+            let expected = Command::Fmt(FormatArguments {
+                files: vec![PathBuf::from("a.kasm"), PathBuf::from("b.kasm")],
+                check: true,
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn lint() {
+            let args = ["", "lint", "a.kasm"];
+            let expected = Command::Lint(LintArguments {
+                files: vec![PathBuf::from("a.kasm")],
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn graph() {
+            let args = ["", "graph", "a.kasm", "-o", "graphs"];
+            let expected = Command::Graph(GraphArguments {
+                file_name: PathBuf::from("a.kasm"),
+                output: Some(PathBuf::from("graphs")),
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn stack() {
+            let args = ["", "stack", "a.kasm", "--stack", "1k"];
+            let expected = Command::Stack(StackArguments {
+                file_name: PathBuf::from("a.kasm"),
+                stack_size: String::from("1k"),
+            });
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn lsp() {
+            let args = ["", "lsp"];
+            let expected = Command::Lsp;
+
+            let actual = Cli::parse_from(args).command;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn test_requires_files() {
+            let args = ["", "test"];
+            let expected = ErrorKind::MissingRequiredArgument;
+
+            let actual = Cli::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual.map(|_| ()), Err(expected));
+        }
+    }
+
+    mod detect_loops {
+        use crate::Arguments;
+        use clap::Parser;
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+
+            let actual = Arguments::parse_from(args);
+
+            assert!(!actual.detect_loops);
+        }
+
+        #[test]
+        fn long() {
+            let args = ["", "file.kasm", "--detect-loops"];
+
+            let actual = Arguments::parse_from(args);
+
+            assert!(actual.detect_loops);
+        }
+    }
+
+    mod detect_uninitialised {
+        use crate::Arguments;
+        use clap::Parser;
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+
+            let actual = Arguments::parse_from(args);
+
+            assert!(!actual.detect_uninitialised);
+        }
+
+        #[test]
+        fn long() {
+            let args = ["", "file.kasm", "--detect-uninitialised"];
+
+            let actual = Arguments::parse_from(args);
+
+            assert!(actual.detect_uninitialised);
+        }
+    }
+
+    mod snapshot {
+        use crate::{cli::SnapshotArguments, Arguments};
+        use clap::{error::ErrorKind, Parser};
+        use std::path::PathBuf;
+
+        #[test]
+        fn snapshot_at_line() {
+            let args = [
+                "",
+                "file.kasm",
+                "--snapshot",
+                "state.json",
+                "--snapshot-at",
+                "12",
+            ];
+            let expected = SnapshotArguments {
+                snapshot: Some(PathBuf::from("state.json")),
+                snapshot_at: Some(12),
+                restore: None,
+            };
+
+            let actual = Arguments::parse_from(args).snapshot;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn restore() {
+            let args = ["", "file.kasm", "--restore", "state.json"];
+            let expected = SnapshotArguments {
+                snapshot: None,
+                snapshot_at: None,
+                restore: Some(PathBuf::from("state.json")),
+            };
+
+            let actual = Arguments::parse_from(args).snapshot;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn snapshot_requires_line() {
+            let args = ["", "file.kasm", "--snapshot", "state.json"];
+            let expected = ErrorKind::MissingRequiredArgument;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual.map(|_| ()), Err(expected));
+        }
+    }
+
+    mod dump_on_error {
+        use crate::Arguments;
+        use clap::Parser;
+        use std::path::PathBuf;
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+
+            let actual = Arguments::parse_from(args);
+
+            assert_eq!(actual.dump_on_error, None);
+        }
+
+        #[test]
+        fn long() {
+            let args = ["", "file.kasm", "--dump-on-error", "crash.txt"];
+
+            let actual = Arguments::parse_from(args);
+
+            assert_eq!(actual.dump_on_error, Some(PathBuf::from("crash.txt")));
+        }
+    }
+
+    mod limits {
+        use crate::{cli::LimitArguments, Arguments, Cli, Command};
+        use clap::{error::ErrorKind, Parser};
+        use std::time::Duration;
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = LimitArguments::default();
+
+            let actual = Arguments::parse_from(args).limits;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn both() {
+            let args = [
+                "",
+                "file.kasm",
+                "--max-instructions",
+                "1000",
+                "--time-limit",
+                "500ms",
+            ];
+            let expected = LimitArguments {
+                max_instructions: Some(1000),
+                time_limit: Some(Duration::from_millis(500)),
+            };
+
+            let actual = Arguments::parse_from(args).limits;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn test_command() {
+            let args = ["", "test", "a.kasm", "--time-limit", "2s"];
+
+            let actual = Cli::parse_from(args).command;
+
+            assert!(
+                matches!(actual, Command::Test(args) if args.limits.time_limit == Some(Duration::from_secs(2)))
+            );
+        }
+
+        #[test]
+        fn zero_instructions_error() {
+            let args = ["", "file.kasm", "--max-instructions", "0"];
+            let expected = ErrorKind::ValueValidation;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual.map(|_| ()), Err(expected));
+        }
+    }
+
+    mod golden {
+        use crate::{cli::GoldenArguments, Arguments};
+        use clap::{error::ErrorKind, Parser};
+
+        #[test]
+        fn undefined() {
+            let args = ["", "file.kasm"];
+            let expected = GoldenArguments::default();
+
+            let actual = Arguments::parse_from(args).golden;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn bless() {
+            let args = ["", "file.kasm", "-e", "--bless"];
+            let expected = GoldenArguments {
+                expect: true,
+                bless: true,
+            };
+
+            let actual = Arguments::parse_from(args).golden;
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn bless_requires_expect() {
+            let args = ["", "file.kasm", "--bless"];
+            let expected = ErrorKind::MissingRequiredArgument;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual, Err(expected));
+        }
+
+        #[test]
+        fn conflicts_with_debug() {
+            let args = ["", "file.kasm", "-e", "-d"];
+            let expected = ErrorKind::ArgumentConflict;
+
+            let actual = Arguments::try_parse_from(args).map_err(|err| err.kind());
+
+            assert_eq!(actual, Err(expected));
+        }
+    }
+}
+
+#[cfg(test)]
+mod parse_stack_size {
+    use super::parse_stack_size;
+    use crate::{
+        constant::{GIGA_BYTE, KILO_BYTE, MEGA_BYTE},
+        error::ArgumentError,
+    };
+
+    #[test]
+    fn could_not_split_suffix_error() {
+        let input = String::from("500ø");
+        let expected = Err(ArgumentError::CouldNotSplitSuffix);
+
+        let actual = parse_stack_size(&input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_stack_size_suffix_error() {
+        let input = String::from("500l");
+        let expected = Err(ArgumentError::InvalidStackSizeSuffix("l".to_string()));
+
+        let actual = parse_stack_size(&input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_initial_stack_size_error() {
+        let input = format!("{}0b", usize::MAX);
+        let expected = Err(ArgumentError::InvalidInitialStackSize(format!(
+            "{}0",
+            usize::MAX
+        )));
+
+        let actual = parse_stack_size(&input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_computed_stack_size_error() {
+        let input = format!("{}k", usize::MAX);
+        let expected = Err(ArgumentError::InvalidComputedStackSize);
+
+        let actual = parse_stack_size(&input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lowercase_byte() -> Result<(), ArgumentError> {
+        let input = String::from("2b");
+        let expected = 2;
+
+        let actual = parse_stack_size(&input)?;
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn uppercase_byte() -> Result<(), ArgumentError> {
+        let input = String::from("2B");
+        let expected = 2;
+
+        let actual = parse_stack_size(&input)?;
 
         assert_eq!(actual, expected);
         Ok(())
@@ -408,3 +1824,85 @@ mod parse_stack_size {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod parse_line_range {
+    use super::parse_line_range;
+    use crate::error::ArgumentError;
+
+    #[test]
+    fn single_line() {
+        let input = "7";
+        let expected = Ok(7..=7);
+
+        let actual = parse_line_range(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn start_and_end() {
+        let input = "7-12";
+        let expected = Ok(7..=12);
+
+        let actual = parse_line_range(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn start_after_end_error() {
+        let input = "12-7";
+        let expected = Err(ArgumentError::InvalidLineRange(input.to_string()));
+
+        let actual = parse_line_range(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn not_a_number_error() {
+        let input = "a-7";
+        let expected = Err(ArgumentError::InvalidLineRange(input.to_string()));
+
+        let actual = parse_line_range(input);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod parse_duration {
+    use super::parse_duration;
+    use crate::error::ArgumentError;
+    use std::time::Duration;
+
+    #[test]
+    fn units() -> Result<(), ArgumentError> {
+        assert_eq!(parse_duration("250ms")?, Duration::from_millis(250));
+        assert_eq!(parse_duration("3s")?, Duration::from_secs(3));
+        assert_eq!(parse_duration("2m")?, Duration::from_secs(120));
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_unit_error() {
+        let input = "30";
+        let expected = Err(ArgumentError::InvalidDuration(String::from("30")));
+
+        let actual = parse_duration(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn unknown_unit_error() {
+        let input = "1h";
+        let expected = Err(ArgumentError::InvalidDuration(String::from("1h")));
+
+        let actual = parse_duration(input);
+
+        assert_eq!(actual, expected);
+    }
+}
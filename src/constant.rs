@@ -20,9 +20,18 @@ pub const MEGA_BYTE: usize = KILO_BYTE * KILO_BYTE;
 
 pub const GIGA_BYTE: usize = KILO_BYTE * KILO_BYTE * KILO_BYTE;
 
+/// The stack size used when none is specified, matching the default of the stack size flag.
+pub const DEFAULT_STACK_SIZE: usize = 4 * MEGA_BYTE;
+
 #[cfg(test)]
 pub const TEST_STACK_SIZE: usize = Quarter::MAX as usize;
 
+/// The amount of instructions executed between each check of the time limit, as reading the clock is comparatively slow.
+pub const TIME_LIMIT_CHECK_INTERVAL: u64 = 1024;
+
+/// The amount of instructions resumed between each check for an interruption of the debugger, such as a key press.
+pub const DEBUGGER_INTERRUPT_CHECK_INTERVAL: u64 = 1024;
+
 type DecodeFn = fn(Parameters) -> Result<Instruction, DecodeError>;
 pub type DecodeTable = Map<&'static str, DecodeFn>;
 
@@ -30,10 +39,22 @@ pub const COMMENT: &str = "//";
 
 pub const DEBUG_HELP: &str = r"You can take the following actions:
   - 'next'/'n' will advance the interpretation by one instruction
-  - 'stop'/'s' will stop the interpretation\n
+  - 'continue'/'c' will advance the interpretation until a breakpoint is hit
+  - 'break'/'b' followed by a line number will toggle a breakpoint on that line
+  - 'backtrace'/'bt' will print the chain of function calls leading to the current line
+  - 'stop'/'s' will stop the interpretation
 ";
 
 pub const DEBUG_INITIAL: &str = r"You are running the program in debug mode.
 Use the 'help'/'h' action for more information.";
 
+/// The amount of backward transfers after which the loop detector forgets the states it has recorded.
+pub const LOOP_DETECTION_HISTORY: usize = 1 << 16;
+
+/// The amount of lines listed in the hot spot table of the profiler.
+pub const PROFILE_HOT_LINES: usize = 20;
+
+pub const TUI_HELP: &str =
+    "s/space: step  c: continue (any key pauses)  b: breakpoint  j/k: move  q: quit";
+
 pub type Parameters<'a> = SplitAsciiWhitespace<'a>;
@@ -0,0 +1,72 @@
+use super::{Debugger, Pause};
+use crate::{
+    constant::{DEBUG_HELP, DEBUG_INITIAL},
+    program::Program,
+    Interpreter, InterpreterError,
+};
+use std::io::stdin;
+
+impl Interpreter {
+    /// Interprets the program one action at a time, reading actions from stdin.
+    pub(crate) fn debug(&mut self, program: &Program) -> Result<(), InterpreterError> {
+        let mut debugger = Debugger::new();
+        println!("{DEBUG_INITIAL}");
+        print_next(program, self.pc());
+
+        while self.running {
+            let mut action = String::new();
+            // reaching the end of stdin means no more actions can be taken
+            if stdin().read_line(&mut action).unwrap() == 0 {
+                debugger.stop(self)?;
+                break;
+            }
+
+            let mut action_iter = action.split_ascii_whitespace();
+            match action_iter.next().unwrap_or_default() {
+                "help" | "h" => {
+                    println!("{DEBUG_HELP}");
+                }
+                "next" | "n" => {
+                    debugger.step(self, program)?;
+                }
+                "continue" | "c" => {
+                    if let Pause::Breakpoint(line) = debugger.resume(self, program)? {
+                        println!("hit breakpoint on line {line}");
+                    }
+                }
+                "break" | "b" => match action_iter.next().map(str::parse::<usize>) {
+                    Some(Ok(line)) => {
+                        if debugger.toggle_breakpoint(line) {
+                            println!("set breakpoint on line {line}");
+                        } else {
+                            println!("removed breakpoint on line {line}");
+                        }
+                    }
+                    _ => println!("expected a line number after 'break'"),
+                },
+                "backtrace" | "bt" => {
+                    println!("{}", self.backtrace());
+                }
+                "stop" | "s" => {
+                    debugger.stop(self)?;
+                    break;
+                }
+                unknown => println!("unknown action: '{unknown}'"),
+            }
+
+            println!("pc: {}, sp: {}", self.pc(), self.stack.sp());
+            if self.running {
+                print_next(program, self.pc());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the instruction on the line as it was decoded, rather than its source code.
+fn print_next(program: &Program, line: usize) {
+    if let Ok(Some(instruction)) = program.instruction(line) {
+        println!("next: {instruction}");
+    }
+}
@@ -0,0 +1,260 @@
+//! The debugger engine shared by the line-oriented and the terminal user interface frontends.
+
+use crate::{
+    constant::DEBUGGER_INTERRUPT_CHECK_INTERVAL, instruction::Instruction, program::Program,
+    Interpreter, InterpreterError,
+};
+use std::collections::BTreeSet;
+
+mod line;
+mod tui;
+
+/// The reason the debugger handed control back to the frontend.
+#[derive(Debug, PartialEq)]
+pub enum Pause {
+    /// A single instruction was advanced.
+    Step,
+    /// The program counter reached a line with a breakpoint.
+    Breakpoint(usize),
+    /// The frontend interrupted resuming the program.
+    Interrupted,
+    /// The program is no longer running.
+    Finished,
+}
+
+pub struct Debugger {
+    breakpoints: BTreeSet<usize>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    /// Toggles a breakpoint on the given line.
+    ///
+    /// Returns true if the breakpoint is set after toggling.
+    pub fn toggle_breakpoint(&mut self, line: usize) -> bool {
+        if self.breakpoints.remove(&line) {
+            false
+        } else {
+            self.breakpoints.insert(line);
+            true
+        }
+    }
+
+    pub fn has_breakpoint(&self, line: usize) -> bool {
+        self.breakpoints.contains(&line)
+    }
+
+    /// Advances the interpretation by a single instruction.
+    pub fn step(
+        &self,
+        interpreter: &mut Interpreter,
+        program: &Program,
+    ) -> Result<Pause, InterpreterError> {
+        if !interpreter.running {
+            return Ok(Pause::Finished);
+        }
+
+        interpreter.advance(program)?;
+
+        if interpreter.running {
+            Ok(Pause::Step)
+        } else {
+            Ok(Pause::Finished)
+        }
+    }
+
+    /// Advances the interpretation until a breakpoint is reached or the program stops.
+    ///
+    /// At least one instruction is always advanced, such that resuming from a
+    /// breakpoint does not immediately pause on the same breakpoint.
+    pub fn resume(
+        &self,
+        interpreter: &mut Interpreter,
+        program: &Program,
+    ) -> Result<Pause, InterpreterError> {
+        self.resume_until(interpreter, program, || false)
+    }
+
+    /// Advances the interpretation like [`Debugger::resume`], but also pauses once interrupted.
+    ///
+    /// Whether to interrupt is only checked every few instructions, such that checking may be slow.
+    pub fn resume_until(
+        &self,
+        interpreter: &mut Interpreter,
+        program: &Program,
+        mut interrupted: impl FnMut() -> bool,
+    ) -> Result<Pause, InterpreterError> {
+        let mut steps: u64 = 0;
+        loop {
+            if self.step(interpreter, program)? == Pause::Finished {
+                return Ok(Pause::Finished);
+            }
+
+            if self.has_breakpoint(interpreter.pc()) {
+                return Ok(Pause::Breakpoint(interpreter.pc()));
+            }
+
+            steps += 1;
+            if steps.is_multiple_of(DEBUGGER_INTERRUPT_CHECK_INTERVAL) && interrupted() {
+                return Ok(Pause::Interrupted);
+            }
+        }
+    }
+
+    /// Stops the interpretation as if a stop instruction was executed.
+    pub fn stop(&self, interpreter: &mut Interpreter) -> Result<Pause, InterpreterError> {
+        interpreter
+            .execute(Instruction::Stop)
+            .map_err(|err| InterpreterError::Execute(interpreter.pc(), err))?;
+
+        Ok(Pause::Finished)
+    }
+}
+
+#[cfg(test)]
+mod toggle_breakpoint {
+    use super::Debugger;
+
+    #[test]
+    fn set_and_unset() {
+        let mut d = Debugger::new();
+
+        assert!(d.toggle_breakpoint(3));
+        assert!(d.has_breakpoint(3));
+        assert!(!d.toggle_breakpoint(3));
+        assert!(!d.has_breakpoint(3));
+    }
+}
+
+#[cfg(test)]
+mod step {
+    use super::{Debugger, Pause};
+    use crate::{
+        constant::Byte, program::Program, register::Register, registers::RegisterOperations,
+        Interpreter, InterpreterError,
+    };
+
+    fn program(lines: &[&str]) -> Program {
+        Program::new(lines.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn advances_one_instruction() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let d = Debugger::new();
+        let p = program(&["setb ra 1", "setb ra 2", "stop"]);
+
+        let actual = d.step(&mut i, &p)?;
+
+        assert_eq!(actual, Pause::Step);
+        assert_eq!(i.registers.get::<Byte>(Register::A), 1);
+        assert_eq!(i.pc(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn finished_after_stop() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let d = Debugger::new();
+        let p = program(&["stop"]);
+
+        let first = d.step(&mut i, &p)?;
+        let second = d.step(&mut i, &p)?;
+
+        assert_eq!(first, Pause::Finished);
+        assert_eq!(second, Pause::Finished);
+        assert_eq!(i.config.instructions_executed, 1);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod resume {
+    use super::{Debugger, Pause};
+    use crate::{
+        constant::{Byte, DEBUGGER_INTERRUPT_CHECK_INTERVAL},
+        program::Program,
+        register::Register,
+        registers::RegisterOperations,
+        Interpreter, InterpreterError,
+    };
+
+    fn program(lines: &[&str]) -> Program {
+        Program::new(lines.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn pauses_on_breakpoint() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let mut d = Debugger::new();
+        d.toggle_breakpoint(3);
+        let p = program(&["setb ra 1", "setb ra 2", "setb ra 3", "stop"]);
+
+        let actual = d.resume(&mut i, &p)?;
+
+        assert_eq!(actual, Pause::Breakpoint(3));
+        assert_eq!(i.registers.get::<Byte>(Register::A), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_pause_on_current_breakpoint() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let mut d = Debugger::new();
+        d.toggle_breakpoint(2);
+        let p = program(&["addb ra 1", "jmp 1"]);
+
+        let first = d.resume(&mut i, &p)?;
+        let second = d.resume(&mut i, &p)?;
+
+        assert_eq!(first, Pause::Breakpoint(2));
+        assert_eq!(second, Pause::Breakpoint(2));
+        assert_eq!(i.registers.get::<Byte>(Register::A), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interrupts_infinite_loop() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let d = Debugger::new();
+        let p = program(&["addb ra 1", "jmp 1"]);
+        let mut checks = 0;
+
+        let actual = d.resume_until(&mut i, &p, || {
+            checks += 1;
+            checks == 3
+        })?;
+
+        assert_eq!(actual, Pause::Interrupted);
+        assert_eq!(
+            i.instructions_executed(),
+            3 * DEBUGGER_INTERRUPT_CHECK_INTERVAL
+        );
+        assert!(i.running);
+
+        Ok(())
+    }
+
+    #[test]
+    fn runs_until_finished_without_breakpoints() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let d = Debugger::new();
+        let p = program(&["setb ra 1", "setb ra 2", "stop"]);
+
+        let actual = d.resume(&mut i, &p)?;
+
+        assert_eq!(actual, Pause::Finished);
+        assert!(!i.running);
+
+        Ok(())
+    }
+}
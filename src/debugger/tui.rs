@@ -0,0 +1,602 @@
+use super::{Debugger, Pause};
+use crate::{
+    constant::{Byte, Word, TUI_HELP},
+    flags::Flags,
+    program::Program,
+    register::Register,
+    registers::{RegisterOperations, Registers},
+    Interpreter, InterpreterError,
+};
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::{
+    fmt::Display,
+    io::{stdout, Write},
+    time::Duration,
+};
+
+/// The amount of bytes shown on each row of the stack pane.
+const STACK_ROW_WIDTH: usize = 8;
+
+/// A single row of a pane, which may be highlighted when drawn.
+#[derive(Debug, PartialEq)]
+struct Row {
+    text: String,
+    highlight: bool,
+}
+
+impl Row {
+    fn plain(text: String) -> Self {
+        Self {
+            text,
+            highlight: false,
+        }
+    }
+
+    fn new(text: String, highlight: bool) -> Self {
+        Self { text, highlight }
+    }
+}
+
+/// A rectangular area of the screen.
+#[derive(Clone, Copy)]
+struct Area {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+}
+
+/// Puts the terminal into raw mode on the alternate screen, and restores it when dropped.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self, InterpreterError> {
+        terminal::enable_raw_mode().map_err(terminal_error)?;
+        execute!(stdout(), EnterAlternateScreen, Hide).map_err(terminal_error)?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // nothing sensible can be done if restoring the terminal fails
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// The state of the terminal user interface in between steps.
+struct View {
+    debugger: Debugger,
+    /// The source line selected for toggling breakpoints.
+    cursor: usize,
+    previous_registers: Registers,
+    previous_flags: Flags,
+    status: String,
+    error: Option<InterpreterError>,
+}
+
+impl Interpreter {
+    /// Interprets the program in a full-screen terminal user interface.
+    pub(crate) fn tui(&mut self, program: &Program) -> Result<(), InterpreterError> {
+        let mut view = View {
+            debugger: Debugger::new(),
+            cursor: self.pc(),
+            previous_registers: self.registers,
+            previous_flags: self.flags.clone(),
+            status: String::from("ready"),
+            error: None,
+        };
+
+        let guard = TerminalGuard::enter()?;
+
+        loop {
+            self.draw(program, &view)?;
+
+            let Event::Key(key) = event::read().map_err(terminal_error)? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('s' | 'n' | ' ') => {
+                    self.tui_advance(program, &mut view, Debugger::step);
+                }
+                KeyCode::Char('c') => {
+                    self.tui_advance(program, &mut view, |debugger, interpreter, program| {
+                        debugger.resume_until(interpreter, program, key_pressed)
+                    });
+                }
+                KeyCode::Char('b') => {
+                    view.status = if view.debugger.toggle_breakpoint(view.cursor) {
+                        format!("set breakpoint on line {}", view.cursor)
+                    } else {
+                        format!("removed breakpoint on line {}", view.cursor)
+                    };
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    view.cursor = view.cursor.saturating_sub(1).max(1);
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    view.cursor = (view.cursor + 1).min(program.lines().len().max(1));
+                }
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                _ => {}
+            }
+        }
+
+        drop(guard);
+
+        if let Some(err) = view.error {
+            return Err(err);
+        }
+
+        if self.running {
+            view.debugger.stop(self)?;
+        }
+
+        Ok(())
+    }
+
+    fn tui_advance(
+        &mut self,
+        program: &Program,
+        view: &mut View,
+        action: impl FnOnce(&Debugger, &mut Interpreter, &Program) -> Result<Pause, InterpreterError>,
+    ) {
+        if view.error.is_some() || !self.running {
+            view.status = String::from("the program is no longer running, press q to quit");
+            return;
+        }
+
+        view.previous_registers = self.registers;
+        view.previous_flags = self.flags.clone();
+
+        view.status = match action(&view.debugger, self, program) {
+            Ok(Pause::Step) => format!("stepped to line {}", self.pc()),
+            Ok(Pause::Breakpoint(line)) => format!("hit breakpoint on line {line}"),
+            Ok(Pause::Interrupted) => format!("interrupted on line {}", self.pc()),
+            Ok(Pause::Finished) => String::from("the program finished, press q to quit"),
+            Err(err) => {
+                let status = err.to_string();
+                view.error = Some(err);
+                status
+            }
+        };
+        view.cursor = self.pc();
+    }
+
+    fn draw(&self, program: &Program, view: &View) -> Result<(), InterpreterError> {
+        let (width, height) = terminal::size().map_err(terminal_error)?;
+        let mut out = stdout();
+        queue!(out, Clear(ClearType::All)).map_err(terminal_error)?;
+
+        let status_height = 1;
+        let output_height = (height / 4).max(3);
+        let top_height = height.saturating_sub(output_height + status_height);
+        let left_width = width * 3 / 5;
+        let right_x = left_width + 1;
+        let right_width = width.saturating_sub(right_x);
+        let register_height = u16::try_from(Register::VARIANT_COUNT + 1).unwrap_or(u16::MAX);
+        let flags_height = 4;
+
+        let source = Area {
+            x: 0,
+            y: 0,
+            width: left_width,
+            height: top_height,
+        };
+        let registers = Area {
+            x: right_x,
+            y: 0,
+            width: right_width,
+            height: register_height.min(top_height),
+        };
+        let flags = Area {
+            x: right_x,
+            y: registers.height,
+            width: right_width,
+            height: flags_height.min(top_height.saturating_sub(registers.height)),
+        };
+        let stack = Area {
+            x: right_x,
+            y: registers.height + flags.height,
+            width: right_width,
+            height: top_height.saturating_sub(registers.height + flags.height),
+        };
+        let output = Area {
+            x: 0,
+            y: top_height,
+            width,
+            height: output_height.min(height.saturating_sub(status_height)),
+        };
+
+        let content_height = |area: Area| usize::from(area.height.saturating_sub(1));
+
+        let source_rows = source_rows(
+            program,
+            self.pc(),
+            &view.debugger,
+            view.cursor,
+            content_height(source),
+        );
+        let register_rows = register_rows(&self.registers, &view.previous_registers);
+        let flag_rows = flag_rows(&self.flags, &view.previous_flags);
+        let stack_rows = stack_rows(self.stack.bytes(), self.stack.sp(), content_height(stack));
+        let output_rows = match &self.config.captured_output {
+            Some(buffer) => output_rows(&buffer.borrow(), content_height(output)),
+            None => Vec::new(),
+        };
+
+        draw_pane(&mut out, source, "Source", &source_rows)?;
+        draw_pane(&mut out, registers, "Registers", &register_rows)?;
+        draw_pane(&mut out, flags, "Flags", &flag_rows)?;
+        draw_pane(&mut out, stack, "Stack", &stack_rows)?;
+        draw_pane(&mut out, output, "Output", &output_rows)?;
+
+        for y in 0..top_height {
+            queue!(out, MoveTo(left_width, y), Print('│')).map_err(terminal_error)?;
+        }
+
+        let status = format!(
+            "pc: {} sp: {} depth: {} | {} | {TUI_HELP}",
+            self.pc(),
+            self.stack.sp(),
+            self.call_stack.depth(),
+            view.status
+        );
+        queue!(
+            out,
+            MoveTo(0, height.saturating_sub(1)),
+            SetAttribute(Attribute::Reverse),
+            Print(fit(&status, usize::from(width))),
+            SetAttribute(Attribute::Reset),
+        )
+        .map_err(terminal_error)?;
+
+        out.flush().map_err(terminal_error)
+    }
+}
+
+/// Consumes the pending events, returning true if any of them is a key press,
+/// which interrupts continuing such that an infinite loop can still be paused.
+fn key_pressed() -> bool {
+    let mut pressed = false;
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        if let Ok(Event::Key(key)) = event::read() {
+            pressed |= key.kind == KeyEventKind::Press;
+        }
+    }
+
+    pressed
+}
+
+fn terminal_error(err: impl Display) -> InterpreterError {
+    InterpreterError::Terminal(err.to_string())
+}
+
+/// Truncates or pads the text with spaces such that it is exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted: String = text.chars().take(width).collect();
+    let length = fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', width - length));
+
+    fitted
+}
+
+fn draw_pane(
+    out: &mut impl Write,
+    area: Area,
+    title: &str,
+    rows: &[Row],
+) -> Result<(), InterpreterError> {
+    if area.height == 0 || area.width == 0 {
+        return Ok(());
+    }
+
+    let width = usize::from(area.width);
+    queue!(
+        out,
+        MoveTo(area.x, area.y),
+        SetAttribute(Attribute::Bold),
+        Print(fit(&format!("── {title} {}", "─".repeat(width)), width)),
+        SetAttribute(Attribute::Reset),
+    )
+    .map_err(terminal_error)?;
+
+    for (y, row) in (area.y + 1..area.y + area.height).zip(rows) {
+        let attribute = if row.highlight {
+            Attribute::Reverse
+        } else {
+            Attribute::Reset
+        };
+
+        queue!(
+            out,
+            MoveTo(area.x, y),
+            SetAttribute(attribute),
+            Print(fit(&row.text, width)),
+            SetAttribute(Attribute::Reset),
+        )
+        .map_err(terminal_error)?;
+    }
+
+    Ok(())
+}
+
+/// Builds the rows of the source pane, scrolled such that the cursor is visible.
+///
+/// The line of the program counter is highlighted, breakpoints are marked with `*`
+/// and the cursor is marked with `>`.
+fn source_rows(
+    program: &Program,
+    pc: usize,
+    debugger: &Debugger,
+    cursor: usize,
+    height: usize,
+) -> Vec<Row> {
+    let lines = program.lines();
+    let start = cursor
+        .saturating_sub(1 + height / 2)
+        .min(lines.len().saturating_sub(height));
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .skip(start)
+        .take(height)
+        .map(|(number, line)| {
+            let breakpoint = if debugger.has_breakpoint(number) {
+                '*'
+            } else {
+                ' '
+            };
+            let selected = if number == cursor { '>' } else { ' ' };
+
+            Row::new(
+                format!("{breakpoint}{selected}{number:>5}  {line}"),
+                number == pc,
+            )
+        })
+        .collect()
+}
+
+/// Builds the rows of the register pane, highlighting registers changed since the previous step.
+fn register_rows(registers: &Registers, previous: &Registers) -> Vec<Row> {
+    Register::ALL
+        .into_iter()
+        .map(|register| {
+            let value = registers.get::<Word>(register);
+            Row::new(
+                format!("{register}  {value:#018x}  {value}"),
+                value != previous.get::<Word>(register),
+            )
+        })
+        .collect()
+}
+
+/// Builds the rows of the flags pane, highlighting flags changed since the previous step.
+fn flag_rows(flags: &Flags, previous: &Flags) -> Vec<Row> {
+    [
+        ("of", flags.overflow, previous.overflow),
+        ("zf", flags.zero, previous.zero),
+        ("sf", flags.sign, previous.sign),
+    ]
+    .into_iter()
+    .map(|(name, value, previous)| {
+        Row::new(format!("{name}  {}", u8::from(value)), value != previous)
+    })
+    .collect()
+}
+
+/// Builds a hex dump of the stack, scrolled such that the row of the stack pointer is centered.
+///
+/// The row containing the stack pointer is highlighted.
+fn stack_rows(bytes: &[Byte], sp: usize, height: usize) -> Vec<Row> {
+    let sp_row = sp / STACK_ROW_WIDTH;
+    let total_rows = bytes.len().div_ceil(STACK_ROW_WIDTH);
+    let start = sp_row
+        .saturating_sub(height / 2)
+        .min(total_rows.saturating_sub(height));
+
+    bytes
+        .chunks(STACK_ROW_WIDTH)
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(row, chunk)| {
+            let address = row * STACK_ROW_WIDTH;
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+            let marker = if row == sp_row { " <- sp" } else { "" };
+
+            Row::new(
+                format!("{address:#08x}  {}{marker}", hex.join(" ")),
+                row == sp_row,
+            )
+        })
+        .collect()
+}
+
+/// Builds the rows of the output pane, showing the last lines written.
+fn output_rows(buffer: &[Byte], height: usize) -> Vec<Row> {
+    let output = String::from_utf8_lossy(buffer);
+    let lines: Vec<&str> = output.lines().collect();
+
+    lines
+        .iter()
+        .skip(lines.len().saturating_sub(height))
+        .map(|line| Row::plain(line.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod fit {
+    use super::fit;
+
+    #[test]
+    fn pads_short_text() {
+        assert_eq!(fit("ra", 4), "ra  ");
+    }
+
+    #[test]
+    fn truncates_long_text() {
+        assert_eq!(fit("setb ra 10", 4), "setb");
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        assert_eq!(fit("──", 3), "── ");
+    }
+}
+
+#[cfg(test)]
+mod source_rows {
+    use super::{source_rows, Row};
+    use crate::{debugger::Debugger, program::Program};
+
+    fn program(lines: usize) -> Program {
+        Program::new((1..=lines).map(|l| format!("setb ra {l}")).collect())
+    }
+
+    #[test]
+    fn highlights_program_counter_and_marks_breakpoint() {
+        let mut d = Debugger::new();
+        d.toggle_breakpoint(2);
+        let expected = vec![
+            Row::new(String::from("      1  setb ra 1"), false),
+            Row::new(String::from("*>    2  setb ra 2"), true),
+            Row::new(String::from("      3  setb ra 3"), false),
+        ];
+
+        let actual = source_rows(&program(3), 2, &d, 2, 10);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn scrolls_to_cursor() {
+        let d = Debugger::new();
+
+        let actual = source_rows(&program(100), 1, &d, 50, 10);
+
+        assert_eq!(actual.len(), 10);
+        assert!(actual.iter().any(|r| r.text.starts_with(" >   50")));
+    }
+
+    #[test]
+    fn does_not_scroll_past_end() {
+        let d = Debugger::new();
+
+        let actual = source_rows(&program(20), 1, &d, 20, 10);
+
+        assert_eq!(actual.len(), 10);
+        assert!(actual.last().is_some_and(|r| r.text.starts_with(" >   20")));
+    }
+}
+
+#[cfg(test)]
+mod register_rows {
+    use super::register_rows;
+    use crate::{register::Register, registers::RegisterOperations};
+
+    #[test]
+    fn highlights_changed_register() {
+        let previous = [0; Register::VARIANT_COUNT];
+        let mut registers = previous;
+        registers.set::<u64>(Register::B, 34);
+
+        let actual = register_rows(&registers, &previous);
+
+        assert_eq!(actual.len(), Register::VARIANT_COUNT);
+        assert!(actual.iter().filter(|r| r.highlight).count() == 1);
+        assert!(actual
+            .get(1)
+            .is_some_and(|r| r.highlight && r.text == "rb  0x0000000000000022  34"));
+    }
+}
+
+#[cfg(test)]
+mod flag_rows {
+    use super::{flag_rows, Row};
+    use crate::flags::Flags;
+
+    #[test]
+    fn highlights_changed_flag() {
+        let previous = Flags::new();
+        let mut flags = Flags::new();
+        flags.zero = true;
+        let expected = vec![
+            Row::new(String::from("of  0"), false),
+            Row::new(String::from("zf  1"), true),
+            Row::new(String::from("sf  0"), false),
+        ];
+
+        let actual = flag_rows(&flags, &previous);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod stack_rows {
+    use super::{stack_rows, Row};
+
+    #[test]
+    fn highlights_row_of_stack_pointer() {
+        let mut bytes = vec![0; 32];
+        if let Some(b) = bytes.get_mut(9) {
+            *b = 0xff;
+        }
+        let expected = vec![
+            Row::new(String::from("0x000000  00 00 00 00 00 00 00 00"), false),
+            Row::new(
+                String::from("0x000008  00 ff 00 00 00 00 00 00 <- sp"),
+                true,
+            ),
+            Row::new(String::from("0x000010  00 00 00 00 00 00 00 00"), false),
+            Row::new(String::from("0x000018  00 00 00 00 00 00 00 00"), false),
+        ];
+
+        let actual = stack_rows(&bytes, 10, 4);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn centers_on_stack_pointer() {
+        let bytes = vec![0; 1024];
+
+        let actual = stack_rows(&bytes, 512, 5);
+
+        assert_eq!(actual.len(), 5);
+        assert!(actual.get(2).is_some_and(|r| r.highlight));
+    }
+}
+
+#[cfg(test)]
+mod output_rows {
+    use super::{output_rows, Row};
+
+    #[test]
+    fn shows_last_lines() {
+        let buffer = b"ra: 1\nra: 2\nra: 3\n";
+        let expected = vec![
+            Row::plain(String::from("ra: 2")),
+            Row::plain(String::from("ra: 3")),
+        ];
+
+        let actual = output_rows(buffer, 2);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,155 @@
+use super::{try_get_first_parameter_str, ParameterDecoderHelper};
+use crate::{
+    constant::Parameters,
+    error::DecodeError,
+    flags::Flag,
+    instruction::{Assert, Instruction},
+};
+
+pub struct AssertParameterDecoder;
+
+impl AssertParameterDecoder {
+    pub fn byte(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let (operand1, operand2) = ParameterDecoderHelper::try_double_operand(parameters)?;
+        let instruction = Assert::Byte(operand1, operand2);
+
+        Ok(Instruction::Assert(instruction))
+    }
+
+    pub fn quarter(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let (operand1, operand2) = ParameterDecoderHelper::try_double_operand(parameters)?;
+        let instruction = Assert::Quarter(operand1, operand2);
+
+        Ok(Instruction::Assert(instruction))
+    }
+
+    pub fn half(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let (operand1, operand2) = ParameterDecoderHelper::try_double_operand(parameters)?;
+        let instruction = Assert::Half(operand1, operand2);
+
+        Ok(Instruction::Assert(instruction))
+    }
+
+    pub fn word(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let (operand1, operand2) = ParameterDecoderHelper::try_double_operand(parameters)?;
+        let instruction = Assert::Word(operand1, operand2);
+
+        Ok(Instruction::Assert(instruction))
+    }
+
+    pub fn flag_set(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let flag = Flag::try_from(try_get_first_parameter_str(parameters)?)?;
+
+        Ok(Instruction::Assert(Assert::Flag(flag, true)))
+    }
+
+    pub fn flag_clear(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let flag = Flag::try_from(try_get_first_parameter_str(parameters)?)?;
+
+        Ok(Instruction::Assert(Assert::Flag(flag, false)))
+    }
+
+    pub fn stack_depth(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let operand = ParameterDecoderHelper::try_operand(parameters)?;
+
+        Ok(Instruction::Assert(Assert::StackDepth(operand)))
+    }
+}
+
+#[cfg(test)]
+mod regression {
+    use crate::{
+        decode::decode,
+        error::DecodeError,
+        flags::Flag,
+        instruction::{Assert, Instruction},
+        operand::Operand,
+        register::Register,
+    };
+
+    #[test]
+    fn equal_byte() -> Result<(), DecodeError> {
+        let instruction = "aeqb ra 5";
+        let expected = Instruction::Assert(Assert::Byte(
+            Operand::Register(Register::A),
+            Operand::Immediate(5),
+        ));
+
+        let actual = decode(instruction)?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn equal_word() -> Result<(), DecodeError> {
+        let instruction = "aeqw ra rb";
+        let expected = Instruction::Assert(Assert::Word(
+            Operand::Register(Register::A),
+            Operand::Register(Register::B),
+        ));
+
+        let actual = decode(instruction)?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn equal_incomplete_instruction_error() {
+        let instruction = "aeqq ra";
+        let expected = Err(DecodeError::IncompleteInstruction);
+
+        let actual = decode(instruction);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn flag_set() -> Result<(), DecodeError> {
+        let instruction = "afs zf";
+        let expected = Instruction::Assert(Assert::Flag(Flag::Zero, true));
+
+        let actual = decode(instruction)?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn flag_clear() -> Result<(), DecodeError> {
+        let instruction = "afc of";
+        let expected = Instruction::Assert(Assert::Flag(Flag::Overflow, false));
+
+        let actual = decode(instruction)?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_flag_error() {
+        let instruction = "afs xf";
+        let expected = Err(DecodeError::InvalidFlag(String::from("xf")));
+
+        let actual = decode(instruction);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn stack_depth() -> Result<(), DecodeError> {
+        let instruction = "asd 16";
+        let expected = Instruction::Assert(Assert::StackDepth(Operand::Immediate(16)));
+
+        let actual = decode(instruction)?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+}
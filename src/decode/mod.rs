@@ -1,5 +1,5 @@
 use crate::{
-    constant::{DecodeTable, Parameters},
+    constant::{DecodeTable, Parameters, Word},
     error::DecodeError,
     instruction::Instruction,
     operand::Operand,
@@ -7,6 +7,7 @@ use crate::{
 };
 use addition::AdditionParameterDecoder;
 use and::AndParameterDecoder;
+use assert::AssertParameterDecoder;
 use compare::CompareParameterDecoder;
 use division::DivisionParameterDecoder;
 use jump::JumpParameterDecoder;
@@ -18,6 +19,7 @@ use pop::PopParameterDecoder;
 use print_register::PrintRegisterParameterDecoder;
 use print_stack::PrintStackParameterDecoder;
 use push::PushParameterDecoder;
+use read::ReadParameterDecoder;
 use remainder::RemainderParameterDecoder;
 use set::SetParameterDecoder;
 use std::str::FromStr;
@@ -27,6 +29,7 @@ use xor::XorParameterDecoder;
 
 mod addition;
 mod and;
+mod assert;
 mod compare;
 mod division;
 mod jump;
@@ -37,6 +40,7 @@ mod pop;
 mod print_register;
 mod print_stack;
 mod push;
+mod read;
 mod remainder;
 mod set;
 mod subtraction;
@@ -69,6 +73,13 @@ pub const DECODE_TABLE: DecodeTable = phf_map! {
     "andq" => AndParameterDecoder::quarter,
     "andh" => AndParameterDecoder::half,
     "andw" => AndParameterDecoder::word,
+    "aeqb" => AssertParameterDecoder::byte,
+    "aeqq" => AssertParameterDecoder::quarter,
+    "aeqh" => AssertParameterDecoder::half,
+    "aeqw" => AssertParameterDecoder::word,
+    "afc" => AssertParameterDecoder::flag_clear,
+    "afs" => AssertParameterDecoder::flag_set,
+    "asd" => AssertParameterDecoder::stack_depth,
     "call" => Instruction::call,
     "cmpb" => CompareParameterDecoder::byte,
     "cmpq" => CompareParameterDecoder::quarter,
@@ -78,6 +89,7 @@ pub const DECODE_TABLE: DecodeTable = phf_map! {
     "divq" => DivisionParameterDecoder::quarter,
     "divh" => DivisionParameterDecoder::half,
     "divw" => DivisionParameterDecoder::word,
+    "ext" => Instruction::external,
     "jmp" => JumpParameterDecoder::unconditional,
     "jiz" => JumpParameterDecoder::if_zero,
     "jnz" => JumpParameterDecoder::if_not_zero,
@@ -118,6 +130,16 @@ pub const DECODE_TABLE: DecodeTable = phf_map! {
     "pshq" => PushParameterDecoder::quarter,
     "pshh" => PushParameterDecoder::half,
     "pshw" => PushParameterDecoder::word,
+    "rdc" => ReadParameterDecoder::character,
+    "rdl" => ReadParameterDecoder::line,
+    "rdsb" => ReadParameterDecoder::signed_byte,
+    "rdsq" => ReadParameterDecoder::signed_quarter,
+    "rdsh" => ReadParameterDecoder::signed_half,
+    "rdsw" => ReadParameterDecoder::signed_word,
+    "rdub" => ReadParameterDecoder::unsigned_byte,
+    "rduq" => ReadParameterDecoder::unsigned_quarter,
+    "rduh" => ReadParameterDecoder::unsigned_half,
+    "rduw" => ReadParameterDecoder::unsigned_word,
     "remb" => RemainderParameterDecoder::byte,
     "remq" => RemainderParameterDecoder::quarter,
     "remh" => RemainderParameterDecoder::half,
@@ -146,7 +168,7 @@ pub const DECODE_TABLE: DecodeTable = phf_map! {
 ///
 /// # Errors
 /// Will return [`DecodeError::IncompleteInstruction`] if no parameter could be found.
-fn try_get_first_parameter_str(mut parameters: Parameters) -> Result<&str, DecodeError> {
+fn try_get_first_parameter_str(mut parameters: Parameters<'_>) -> Result<&str, DecodeError> {
     match parameters.next() {
         Some(s_operand) => Ok(s_operand),
         None => Err(DecodeError::IncompleteInstruction),
@@ -157,7 +179,9 @@ fn try_get_first_parameter_str(mut parameters: Parameters) -> Result<&str, Decod
 ///
 /// # Errors
 /// Will return [`DecodeError::IncompleteInstruction`] if either parameter could be found.
-fn try_get_both_parameters_str(mut parameters: Parameters) -> Result<(&str, &str), DecodeError> {
+fn try_get_both_parameters_str(
+    mut parameters: Parameters<'_>,
+) -> Result<(&str, &str), DecodeError> {
     let (Some(s_register), Some(s_operand)) = (parameters.next(), parameters.next()) else {
         return Err(DecodeError::IncompleteInstruction);
     };
@@ -224,6 +248,15 @@ impl Instruction {
         Ok(Instruction::Call(operand))
     }
 
+    pub fn external(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let s_index = try_get_first_parameter_str(parameters)?;
+        let index = s_index
+            .parse::<Word>()
+            .map_err(|_| DecodeError::InvalidImmediateValue(s_index.to_string()))?;
+
+        Ok(Instruction::External(index))
+    }
+
     pub fn ret(_parameters: Parameters) -> Result<Instruction, DecodeError> {
         Ok(Instruction::Return)
     }
@@ -0,0 +1,124 @@
+use super::ParameterDecoderHelper;
+use crate::{
+    constant::Parameters,
+    error::DecodeError,
+    instruction::{Instruction, Read},
+};
+
+pub struct ReadParameterDecoder;
+
+impl ReadParameterDecoder {
+    pub fn unsigned_byte(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::UnsignedByte(register)))
+    }
+
+    pub fn unsigned_quarter(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::UnsignedQuarter(register)))
+    }
+
+    pub fn unsigned_half(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::UnsignedHalf(register)))
+    }
+
+    pub fn unsigned_word(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::UnsignedWord(register)))
+    }
+
+    pub fn signed_byte(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::SignedByte(register)))
+    }
+
+    pub fn signed_quarter(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::SignedQuarter(register)))
+    }
+
+    pub fn signed_half(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::SignedHalf(register)))
+    }
+
+    pub fn signed_word(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::SignedWord(register)))
+    }
+
+    pub fn character(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::Character(register)))
+    }
+
+    pub fn line(parameters: Parameters) -> Result<Instruction, DecodeError> {
+        let register = ParameterDecoderHelper::try_register(parameters)?;
+
+        Ok(Instruction::Read(Read::Line(register)))
+    }
+}
+
+#[cfg(test)]
+mod regression {
+    use crate::{
+        decode::decode,
+        error::DecodeError,
+        instruction::{Instruction, Read},
+        register::Register,
+    };
+
+    #[test]
+    fn incomplete_instruction_error() {
+        let instruction = "rdub";
+        let expected = Err(DecodeError::IncompleteInstruction);
+
+        let actual = decode(instruction);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_register_error() {
+        let instruction = "rdl rx";
+        let expected = Err(DecodeError::InvalidRegister(String::from("rx")));
+
+        let actual = decode(instruction);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn variants() -> Result<(), DecodeError> {
+        let cases = [
+            ("rdub ra", Read::UnsignedByte(Register::A)),
+            ("rduq rb", Read::UnsignedQuarter(Register::B)),
+            ("rduh rc", Read::UnsignedHalf(Register::C)),
+            ("rduw rd", Read::UnsignedWord(Register::D)),
+            ("rdsb re", Read::SignedByte(Register::E)),
+            ("rdsq rf", Read::SignedQuarter(Register::F)),
+            ("rdsh rg", Read::SignedHalf(Register::G)),
+            ("rdsw rh", Read::SignedWord(Register::H)),
+            ("rdc ra", Read::Character(Register::A)),
+            ("rdl rb", Read::Line(Register::B)),
+        ];
+
+        for (instruction, expected) in cases {
+            let actual = decode(instruction)?;
+
+            assert_eq!(actual, Instruction::Read(expected));
+        }
+
+        Ok(())
+    }
+}
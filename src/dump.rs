@@ -0,0 +1,277 @@
+//! Writing the state of the machine to files when the interpretation fails, such that it can be diagnosed afterwards.
+
+use crate::{
+    constant::{Byte, Half, Quarter, Word},
+    error::InterpreterError,
+    register::Register,
+    registers::RegisterOperations,
+    snapshot::encode_hex,
+    utils::FromBytes,
+    Interpreter,
+};
+use serde_json::{json, Map, Value};
+use std::{fmt::Write, fs, path::Path};
+
+/// The amount of bytes on each line of the hex dump.
+const HEX_DUMP_WIDTH: usize = 16;
+
+/// A return address found on the stack, for a call that has not yet returned.
+#[derive(Debug, PartialEq)]
+struct ReturnAddress {
+    /// The position of the return address on the stack.
+    offset: usize,
+    /// The line the call returns to, as currently found on the stack.
+    address: Word,
+    /// The line of the call instruction.
+    call_site: usize,
+}
+
+/// Formats the bytes like `hexdump -C`, with the offset, the bytes in hex and the bytes as ASCII.
+fn hex_dump(bytes: &[Byte]) -> String {
+    let mut dump = String::new();
+
+    for (index, chunk) in bytes.chunks(HEX_DUMP_WIDTH).enumerate() {
+        let mut hex = String::new();
+        for (position, byte) in chunk.iter().enumerate() {
+            if position == HEX_DUMP_WIDTH / 2 {
+                hex.push(' ');
+            }
+            let _ = write!(hex, "{byte:02x} ");
+        }
+
+        let ascii: String = chunk
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    char::from(*byte)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        let _ = writeln!(dump, "{:08x}  {hex:<49} |{ascii}|", index * HEX_DUMP_WIDTH);
+    }
+
+    dump
+}
+
+impl Interpreter {
+    fn return_addresses(&self) -> Vec<ReturnAddress> {
+        let stack = self.stack();
+
+        self.call_stack
+            .frames()
+            .iter()
+            .filter_map(|frame| {
+                let offset = frame.stack_pointer.checked_sub(size_of::<Word>())?;
+                let bytes = stack.get(offset..frame.stack_pointer)?;
+
+                Some(ReturnAddress {
+                    offset,
+                    address: Word::from_bytes(bytes),
+                    call_site: frame.call_site,
+                })
+            })
+            .collect()
+    }
+
+    fn human_dump(&self, error: &InterpreterError) -> String {
+        let mut dump = format!("error: {error}\n\nregisters:\n");
+        let _ = writeln!(
+            dump,
+            "    {:>4} {:>7} {:>10} {:>20}",
+            "byte", "quarter", "half", "word"
+        );
+        for register in Register::ALL {
+            let _ = writeln!(
+                dump,
+                "{register}: {:>4} {:>7} {:>10} {:>20}",
+                self.registers.get::<Byte>(register),
+                self.registers.get::<Quarter>(register),
+                self.registers.get::<Half>(register),
+                self.registers.get::<Word>(register),
+            );
+        }
+
+        let _ = writeln!(
+            dump,
+            "\nflags: sf={} of={} zf={}",
+            u8::from(self.flags.sign),
+            u8::from(self.flags.overflow),
+            u8::from(self.flags.zero)
+        );
+        let _ = writeln!(dump, "pc: {}", self.pc());
+        let _ = writeln!(dump, "sp: {}", self.stack.sp());
+
+        let _ = writeln!(dump, "\nreturn addresses (most recent call first):");
+        for address in self.return_addresses().iter().rev() {
+            let _ = writeln!(
+                dump,
+                "  at offset {}: returns to line {}, called from line {}",
+                address.offset, address.address, address.call_site
+            );
+        }
+
+        let _ = writeln!(dump, "\n{}", self.backtrace());
+        let _ = writeln!(dump, "\nstack ({} bytes):", self.stack.sp());
+        dump.push_str(&hex_dump(self.stack()));
+
+        dump
+    }
+
+    fn json_dump(&self, error: &InterpreterError) -> Value {
+        let registers: Map<String, Value> = Register::ALL
+            .iter()
+            .map(|register| {
+                let views = json!({
+                    "byte": self.registers.get::<Byte>(*register),
+                    "quarter": self.registers.get::<Quarter>(*register),
+                    "half": self.registers.get::<Half>(*register),
+                    "word": self.registers.get::<Word>(*register),
+                });
+                (register.to_string(), views)
+            })
+            .collect();
+
+        let return_addresses: Vec<Value> = self
+            .return_addresses()
+            .iter()
+            .rev()
+            .map(|address| {
+                json!({
+                    "offset": address.offset,
+                    "address": address.address,
+                    "call_site": address.call_site,
+                })
+            })
+            .collect();
+
+        let stack = encode_hex(self.stack());
+
+        json!({
+            "error": error.to_string(),
+            "registers": registers,
+            "flags": {
+                "sf": self.flags.sign,
+                "of": self.flags.overflow,
+                "zf": self.flags.zero,
+            },
+            "pc": self.pc(),
+            "sp": self.stack.sp(),
+            "return_addresses": return_addresses,
+            "stack": stack,
+        })
+    }
+
+    /// Writes the state of the machine after the error to the path in a human-readable format,
+    /// and to the path with '.json' appended as JSON.
+    ///
+    /// # Errors
+    /// Returns [`InterpreterError::FailedDump`] if either file could not be written.
+    pub fn dump(&self, error: &InterpreterError, path: &Path) -> Result<(), InterpreterError> {
+        let mut json_path = path.as_os_str().to_owned();
+        json_path.push(".json");
+
+        fs::write(path, self.human_dump(error))
+            .and_then(|()| fs::write(json_path, format!("{:#}\n", self.json_dump(error))))
+            .map_err(|err| InterpreterError::FailedDump(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod hex_dump {
+    use super::hex_dump;
+
+    #[test]
+    fn partial_line() {
+        let expected = "00000000  48 69 00 ff                                       |Hi..|\n";
+
+        let actual = hex_dump(&[b'H', b'i', 0, 255]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn multiple_lines() {
+        let bytes: Vec<u8> = (0x41..0x41 + 18).collect();
+        let expected = [
+            "00000000  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|",
+            "00000010  51 52                                             |QR|",
+            "",
+        ]
+        .join("\n");
+
+        let actual = hex_dump(&bytes);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod machine_dump {
+    use super::ReturnAddress;
+    use crate::{error::InterpreterError, Interpreter};
+    use serde_json::json;
+
+    const PROGRAM: &str = "setw ra 300
+pshb 7
+call divide
+stop
+fn divide:
+  divb rb 0
+  ret";
+
+    fn failed() -> (Interpreter, InterpreterError) {
+        let mut i = Interpreter::new_test();
+        let error = i.run(PROGRAM).unwrap_err();
+        (i, error)
+    }
+
+    #[test]
+    fn return_addresses() {
+        let (i, _) = failed();
+        let expected = vec![ReturnAddress {
+            offset: 1,
+            address: 4,
+            call_site: 3,
+        }];
+
+        let actual = i.return_addresses();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn human_readable() {
+        let (i, error) = failed();
+
+        let actual = i.human_dump(&error);
+
+        assert!(actual.starts_with(
+            "error: failed to execute line 6: attempted to divide by zero\n\nregisters:\n"
+        ));
+        assert!(actual.contains("\nra:   44     300        300                  300\n"));
+        assert!(actual.contains("\npc: 6\nsp: 9\n"));
+        assert!(actual.contains("  at offset 1: returns to line 4, called from line 3\n"));
+        assert!(actual.ends_with(
+            "stack (9 bytes):\n00000000  07 04 00 00 00 00 00 00  00                       |.........|\n"
+        ));
+    }
+
+    #[test]
+    fn json() {
+        let (i, error) = failed();
+
+        let actual = i.json_dump(&error);
+
+        assert_eq!(actual.pointer("/registers/ra/byte"), Some(&json!(44)));
+        assert_eq!(actual.pointer("/registers/ra/word"), Some(&json!(300)));
+        assert_eq!(
+            actual.pointer("/return_addresses/0/call_site"),
+            Some(&json!(3))
+        );
+        assert_eq!(actual.pointer("/stack"), Some(&json!("070400000000000000")));
+        assert_eq!(actual.pointer("/pc"), Some(&json!(6)));
+    }
+}
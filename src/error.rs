@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use crate::constant::Word;
+use std::{path::PathBuf, time::Duration};
 use thiserror::Error;
 
 /// The application level error.
@@ -26,6 +27,15 @@ pub enum InterpreterError {
     #[error("line '{0}' is not part of the specified program")]
     InvalidProgramCounter(usize),
 
+    /// Used to indicate the program was stopped at a line after executing the maximum amount of instructions.
+    #[error("stopped at line {0} after reaching the limit of {1} executed instructions")]
+    InstructionLimitExceeded(usize, u64),
+
+    /// Used to indicate the program was stopped at a line after running for longer than the time limit,
+    /// containing the amount of instructions executed until then.
+    #[error("stopped at line {0} after executing {1} instructions, as the time limit of {2:?} was reached")]
+    TimeLimitExceeded(usize, u64, Duration),
+
     /// Used to indicate an error during the creation of an output file.
     ///
     /// This error can only occur if the output flag is used to specify
@@ -35,6 +45,129 @@ pub enum InterpreterError {
     /// in case it may be helpful to determine the underlying cause of the error.
     #[error("failed to create or open output file, underlying cause is: {0}")]
     FailedOutputFileCreation(String),
+
+    /// Used to indicate an error while opening the input file.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to open input file, underlying cause is: {0}")]
+    FailedInputFileOpen(String),
+
+    /// Used to indicate an error during the creation of a trace file.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to create or open trace file, underlying cause is: {0}")]
+    FailedTraceFileCreation(String),
+
+    /// Used to indicate an error during the creation of a flamegraph file.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to create or open flamegraph file, underlying cause is: {0}")]
+    FailedFlamegraphFileCreation(String),
+
+    /// Used to indicate an error while writing a report once the interpretation has ended,
+    /// such as flushing a trace file.
+    #[error("failed to write report, underlying cause is: {0}")]
+    FailedReport(String),
+
+    /// Used to indicate the output of the program differs from its expected output, containing a diff of the two.
+    #[error("the output does not match the expected output:\n{0}")]
+    OutputMismatch(String),
+
+    /// Used to indicate the output of the program was meant to be compared, but it has no expected output.
+    #[error(
+        "'{0}' has no expected output, add a '// expect:' comment block or a sidecar '.out' file"
+    )]
+    MissingExpectation(PathBuf),
+
+    /// Used to indicate an error while reading or updating the expected output.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to read or update the expected output, underlying cause is: {0}")]
+    FailedExpectationAccess(String),
+
+    /// Used to indicate an error while reading or writing a snapshot.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to read or write the snapshot, underlying cause is: {0}")]
+    FailedSnapshotAccess(String),
+
+    /// Used to indicate a snapshot could not be parsed.
+    #[error("the snapshot is invalid: {0}")]
+    InvalidSnapshot(String),
+
+    /// Used to indicate a snapshot was written in another version of the snapshot format.
+    #[error("the snapshot has format version {0}, but only version {v} is supported", v = crate::snapshot::SNAPSHOT_VERSION)]
+    SnapshotVersionMismatch(u64),
+
+    /// Used to indicate a snapshot is restored against another program than the one it was taken of.
+    #[error("the snapshot was taken of a different program, or the program was modified since")]
+    SnapshotProgramMismatch,
+
+    /// Used to indicate an error while writing the state of the machine after an error.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to write the dump, underlying cause is: {0}")]
+    FailedDump(String),
+
+    /// Used to indicate an error while writing an assembled file.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to create or write the assembled file, underlying cause is: {0}")]
+    FailedAssembledFileCreation(String),
+
+    /// Used to indicate a file expected to be assembled contains source code instead.
+    #[error("'{0}' is not an assembled file")]
+    NotAssembled(PathBuf),
+
+    /// Used to indicate an error while writing a disassembled program.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to write the disassembled program, underlying cause is: {0}")]
+    FailedDisassemblyWrite(String),
+
+    /// Used to indicate an assembled file could not be decoded.
+    #[error("the assembled file is corrupt: {0}")]
+    InvalidAssembledFile(String),
+
+    /// Used to indicate the checksum in the header of an assembled file does not match its contents,
+    /// containing the checksum of the header and the checksum of the contents.
+    #[error("the assembled file is corrupt, its checksum is {0:08x} but its contents have checksum {1:08x}")]
+    AssembledChecksumMismatch(u32, u32),
+
+    /// Used to indicate an assembled file was written in another version of the assembled format.
+    #[error("the assembled file has format version {0}, but only version {v} is supported, assemble the program again", v = crate::bytecode::BYTECODE_VERSION)]
+    AssembledVersionMismatch(u16),
+
+    /// Used to indicate an error while writing a formatted program back to its file.
+    ///
+    /// The underlying error is from the standard library and is only provided to the user
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to write the formatted program, underlying cause is: {0}")]
+    FailedFormatWrite(String),
+
+    /// Used to indicate a failure to write a graph of the program,
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("failed to write the graph, underlying cause is: {0}")]
+    FailedGraphWrite(String),
+
+    /// Used to indicate a failure to communicate with the client of the language server,
+    /// in case it may be helpful to determine the underlying cause of the error.
+    #[error("the language server failed, underlying cause is: {0}")]
+    LanguageServer(String),
+
+    /// Used to indicate an error while drawing to or reading events from the terminal.
+    ///
+    /// This error can only occur when running the terminal user interface.
+    #[error("failed to interact with the terminal, underlying cause is: {0}")]
+    Terminal(String),
 }
 
 /// Represents an error during the decoding of an instruction.
@@ -44,6 +177,10 @@ pub enum DecodeError {
     #[error("invalid register '{0}'")]
     InvalidRegister(String),
 
+    /// Indicates a parameter could not be parsed to a flag.
+    #[error("invalid flag '{0}'")]
+    InvalidFlag(String),
+
     /// Indicates a parameter could not be parsed to an immediate value.
     ///
     /// This includes when the immediate value does not fit within the specified size bounds,
@@ -97,6 +234,42 @@ pub enum ExecuteError {
     /// Indicates a program counter underflow during relative jump.
     #[error("the program counter underflowed")]
     ProgramCounterUnderflow,
+
+    /// Indicates a return without a matching call, only checked when running in checked mode.
+    #[error("returned without a matching call")]
+    ReturnOutsideFunction,
+
+    /// Indicates a function returned without popping everything it pushed onto the stack,
+    /// only checked when running in checked mode.
+    #[error("function '{0}' returned with {1} bytes left on the stack")]
+    BytesLeftOnStack(String, usize),
+
+    /// Indicates a function popped more bytes than it pushed onto the stack before returning,
+    /// only checked when running in checked mode.
+    #[error("function '{0}' returned after popping {1} bytes belonging to its caller")]
+    BytesTakenFromCaller(String, usize),
+
+    /// Indicates the return address on the stack was overwritten by a function,
+    /// only checked when running in checked mode.
+    #[error("function '{0}' would return to line {2} instead of line {1}")]
+    CorruptReturnAddress(String, Word, Word),
+
+    /// Indicates a host function called through an `ext` instruction failed, containing its name and message.
+    #[error("host function '{0}' failed: {1}")]
+    HostFunction(String, String),
+
+    /// Indicates the program reached a state it has been in before, so it loops forever between the lines.
+    #[error("the program loops forever between line {0} and line {1}")]
+    InfiniteLoop(usize, usize),
+
+    /// Indicates the input could not be parsed as the integer a read instruction expected.
+    #[error("the input '{0}' is not a valid {1}")]
+    InvalidInput(String, String),
+
+    /// Indicates an assert instruction did not hold, carrying the line of the assert instruction
+    /// along with the expected and actual values.
+    #[error("assertion on line {0} failed, expected {1} but was {2}")]
+    AssertionFailed(usize, String, String),
 }
 
 /// Represents an error during the parsing and substitution during preprocessing.
@@ -126,6 +299,10 @@ pub enum PreProcessError {
     #[error("called undefined function named '{0}'")]
     UndefinedFunctionCalled(String),
 
+    /// Indicates an `ext` instruction names a host function that has not been registered.
+    #[error("called undefined external function named '{0}'")]
+    UndefinedExternalFunction(String),
+
     #[error("function name '{0}' is not snake case")]
     InvalidFunctionNameFormat(String),
 
@@ -177,4 +354,12 @@ pub enum ArgumentError {
     /// Indicates the specified program file is not in fact a file.
     #[error("'{0}' is not a file")]
     NotAFile(PathBuf),
+
+    /// Indicates a duration is not a number followed by a known unit.
+    #[error("invalid duration '{0}', expected a number followed by 'ms', 's' or 'm'")]
+    InvalidDuration(String),
+
+    /// Indicates a line range is not of the format `start-end` or `line`.
+    #[error("invalid line range '{0}', expected the format 'start-end' or 'line'")]
+    InvalidLineRange(String),
 }
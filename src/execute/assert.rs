@@ -0,0 +1,197 @@
+use crate::{
+    constant::Word, error::ExecuteError, instruction::Assert, operand::Operand, utils::FromBytes,
+    Interpreter,
+};
+use std::fmt::Display;
+
+impl Interpreter {
+    pub(super) fn assert(&self, instruction: Assert) -> Result<(), ExecuteError> {
+        match instruction {
+            Assert::Byte(o1, o2) => self.assert_equal(o1, o2),
+            Assert::Quarter(o1, o2) => self.assert_equal(o1, o2),
+            Assert::Half(o1, o2) => self.assert_equal(o1, o2),
+            Assert::Word(o1, o2) => self.assert_equal(o1, o2),
+            Assert::Flag(flag, expected) => {
+                let actual = self.flags.get(flag);
+                self.assert_holds(
+                    expected == actual,
+                    format!("{flag} = {}", u8::from(expected)),
+                    format!("{flag} = {}", u8::from(actual)),
+                )
+            }
+            Assert::StackDepth(operand) => {
+                let expected = self.get_operand_value(operand);
+                let actual = self.stack.sp();
+                self.assert_holds(
+                    expected == actual as Word,
+                    format!("sp = {expected}"),
+                    format!("sp = {actual}"),
+                )
+            }
+        }
+    }
+
+    fn assert_equal<T>(
+        &self,
+        operand1: Operand<T>,
+        operand2: Operand<T>,
+    ) -> Result<(), ExecuteError>
+    where
+        T: FromBytes + PartialEq + Display,
+    {
+        let actual = self.get_operand_value(operand1);
+        let expected = self.get_operand_value(operand2);
+
+        self.assert_holds(actual == expected, expected.to_string(), actual.to_string())
+    }
+
+    fn assert_holds(
+        &self,
+        holds: bool,
+        expected: String,
+        actual: String,
+    ) -> Result<(), ExecuteError> {
+        if holds {
+            Ok(())
+        } else {
+            Err(ExecuteError::AssertionFailed(self.pc(), expected, actual))
+        }
+    }
+}
+
+#[cfg(test)]
+mod equal {
+    use crate::{
+        constant::Word,
+        error::ExecuteError,
+        instruction::{Assert, Instruction},
+        operand::Operand,
+        register::Register,
+        registers::RegisterOperations,
+        Interpreter,
+    };
+
+    #[test]
+    fn holds() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.registers.set(Register::A, 5 as Word);
+        let instruction = Instruction::Assert(Assert::Byte(
+            Operand::Register(Register::A),
+            Operand::Immediate(5),
+        ));
+
+        i.execute(instruction)?;
+
+        assert_eq!(i.program_counter, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_compares_size_of_instruction() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.registers.set(Register::A, 0x0105 as Word);
+        let instruction = Instruction::Assert(Assert::Byte(
+            Operand::Register(Register::A),
+            Operand::Immediate(5),
+        ));
+
+        i.execute(instruction)
+    }
+
+    #[test]
+    fn assertion_failed_error() {
+        let mut i = Interpreter::new_test();
+        i.program_counter = 7;
+        i.registers.set(Register::B, 3 as Word);
+        let instruction = Instruction::Assert(Assert::Word(
+            Operand::Register(Register::B),
+            Operand::Immediate(5),
+        ));
+        let expected = Err(ExecuteError::AssertionFailed(
+            7,
+            String::from("5"),
+            String::from("3"),
+        ));
+
+        let actual = i.execute(instruction);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod flag {
+    use crate::{
+        error::ExecuteError,
+        flags::Flag,
+        instruction::{Assert, Instruction},
+        Interpreter,
+    };
+
+    #[test]
+    fn set_holds() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.flags.zero = true;
+
+        i.execute(Instruction::Assert(Assert::Flag(Flag::Zero, true)))
+    }
+
+    #[test]
+    fn clear_holds() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+
+        i.execute(Instruction::Assert(Assert::Flag(Flag::Sign, false)))
+    }
+
+    #[test]
+    fn assertion_failed_error() {
+        let mut i = Interpreter::new_test();
+        let expected = Err(ExecuteError::AssertionFailed(
+            1,
+            String::from("of = 1"),
+            String::from("of = 0"),
+        ));
+
+        let actual = i.execute(Instruction::Assert(Assert::Flag(Flag::Overflow, true)));
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod stack_depth {
+    use crate::{
+        constant::Word,
+        error::ExecuteError,
+        instruction::{Assert, Instruction},
+        operand::Operand,
+        Interpreter,
+    };
+
+    #[test]
+    fn holds() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.stack.push::<Word>(1)?;
+
+        i.execute(Instruction::Assert(Assert::StackDepth(Operand::Immediate(
+            8,
+        ))))
+    }
+
+    #[test]
+    fn assertion_failed_error() {
+        let mut i = Interpreter::new_test();
+        let expected = Err(ExecuteError::AssertionFailed(
+            1,
+            String::from("sp = 4"),
+            String::from("sp = 0"),
+        ));
+
+        let actual = i.execute(Instruction::Assert(Assert::StackDepth(Operand::Immediate(
+            4,
+        ))));
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -14,19 +14,7 @@ impl Interpreter {
         operand: Operand<Word>,
         relative: Option<Relative>,
     ) -> Result<bool, ExecuteError> {
-        let jump_condition = match &instruction {
-            Jump::Unconditional => true,
-            Jump::IfZero => self.flags.zero,
-            Jump::IfNotZero => !self.flags.zero,
-            Jump::IfSign => self.flags.sign,
-            Jump::IfNotSign => !self.flags.sign,
-            Jump::IfOverflow => self.flags.overflow,
-            Jump::IfNotOverflow => !self.flags.overflow,
-            Jump::IfGreater => !self.flags.overflow && !self.flags.zero,
-            Jump::IfLesser => self.flags.overflow && !self.flags.zero,
-            Jump::IfGreaterOrEqual => !self.flags.overflow || self.flags.zero,
-            Jump::IfLesserOrEqual => self.flags.overflow ^ self.flags.zero,
-        };
+        let jump_condition = instruction.condition(&self.flags);
 
         if jump_condition {
             let destination = self.get_operand_value(operand);
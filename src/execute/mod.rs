@@ -1,10 +1,13 @@
 use crate::{
-    constant::Word, error::ExecuteError, instruction::Instruction, operand::Operand,
-    registers::RegisterOperations, utils::FromBytes, Interpreter,
+    call_stack::function_name, constant::Word, error::ExecuteError, host::Host,
+    instruction::Instruction, operand::Operand, registers::RegisterOperations, utils::FromBytes,
+    Interpreter,
 };
+use std::cmp::Ordering;
 
 mod addition;
 mod and;
+mod assert;
 mod compare;
 mod division;
 mod jump;
@@ -15,6 +18,7 @@ mod pop;
 mod print_register;
 mod print_stack;
 mod push;
+mod read;
 mod remainder;
 mod set;
 mod subtraction;
@@ -35,14 +39,21 @@ impl Interpreter {
     pub(super) fn execute(&mut self, instruction: Instruction) -> Result<(), ExecuteError> {
         self.config.instructions_executed += 1;
 
+        let hooked = !self.config.hooks.is_empty();
+        if hooked {
+            self.hooks_before(&instruction);
+        }
+
         let mut increment_pc = instruction.increment();
 
         match instruction {
             Instruction::Addition(add_ins) => self.add(add_ins),
             Instruction::And(and_ins) => self.and(and_ins),
+            Instruction::Assert(assert_ins) => self.assert(assert_ins)?,
             Instruction::Call(operand) => self.call(operand)?,
             Instruction::Compare(compare_ins) => self.compare(compare_ins),
             Instruction::Division(div_ins) => self.div(div_ins)?,
+            Instruction::External(index) => self.external(index)?,
             Instruction::Jump(jump_ins, operand, relative) => {
                 increment_pc = !self.jump(jump_ins, operand, relative)?;
             }
@@ -52,6 +63,7 @@ impl Interpreter {
             Instruction::Pop(pop_ins) => self.pop(pop_ins)?,
             Instruction::PrintRegister(print_reg_ins) => self.print_register(print_reg_ins)?,
             Instruction::PrintStack(print_stack_ins) => self.print_stack(print_stack_ins)?,
+            Instruction::Read(read_ins) => self.read(read_ins)?,
             Instruction::Push(push_ins) => self.push(push_ins)?,
             Instruction::Remainder(rem_ins) => self.rem(rem_ins)?,
             Instruction::Return => self.ret()?,
@@ -66,6 +78,10 @@ impl Interpreter {
             self.program_counter += 1;
         }
 
+        if hooked {
+            self.hooks_after()?;
+        }
+
         Ok(())
     }
 
@@ -81,25 +97,93 @@ impl Interpreter {
             .map_err(|err| ExecuteError::IO(err.to_string()))?;
         }
 
+        if self.config.checked {
+            writeln!(
+                self.config.report,
+                "Maximum Call Depth: {}",
+                self.call_stack.max_depth()
+            )
+            .map_err(|err| ExecuteError::IO(err.to_string()))?;
+        }
+
         Ok(())
     }
 
+    fn external(&mut self, index: Word) -> Result<(), ExecuteError> {
+        let (name, function) = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.config.host_functions.get_mut(index))
+            .ok_or_else(|| {
+                ExecuteError::HostFunction(
+                    index.to_string(),
+                    String::from("no host function is registered at this index"),
+                )
+            })?;
+
+        let mut host = Host::new(&mut self.registers, &mut self.stack);
+        function(&mut host).map_err(|message| ExecuteError::HostFunction(name.clone(), message))
+    }
+
     fn call(&mut self, operand: Operand<Word>) -> Result<(), ExecuteError> {
         let return_address = self.program_counter + 1;
         self.stack.push(return_address)?;
 
-        let destination = self.get_operand_value(operand);
-        self.program_counter = destination;
+        let line = self.pc();
+        self.program_counter = self.get_operand_value(operand);
+        self.call_stack.push(self.pc(), line, self.stack.sp());
 
         Ok(())
     }
 
     fn ret(&mut self) -> Result<(), ExecuteError> {
+        if self.config.checked {
+            self.check_return()?;
+        }
+
         let destination = self.stack.pop::<Word>()?;
+        self.call_stack.pop();
         self.program_counter = destination;
 
         Ok(())
     }
+
+    /// Ensures the stack is in the same state as right after the matching call.
+    fn check_return(&self) -> Result<(), ExecuteError> {
+        let frame = self
+            .call_stack
+            .last()
+            .ok_or(ExecuteError::ReturnOutsideFunction)?;
+        let function = function_name(frame.entry, &self.functions);
+        let sp = self.stack.sp();
+
+        match sp.cmp(&frame.stack_pointer) {
+            Ordering::Greater => Err(ExecuteError::BytesLeftOnStack(
+                function,
+                sp - frame.stack_pointer,
+            )),
+            Ordering::Less => Err(ExecuteError::BytesTakenFromCaller(
+                function,
+                frame.stack_pointer - sp,
+            )),
+            Ordering::Equal => {
+                let expected = frame.call_site as Word + 1;
+                let actual = self
+                    .stack
+                    .slice::<Word>(1)?
+                    .first()
+                    .copied()
+                    .ok_or(ExecuteError::StackUnderflow)?;
+
+                if actual == expected {
+                    Ok(())
+                } else {
+                    Err(ExecuteError::CorruptReturnAddress(
+                        function, expected, actual,
+                    ))
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +211,7 @@ mod stop {
 #[cfg(test)]
 mod call {
     use crate::{
+        call_stack::Frame,
         constant::{Byte, Word, TEST_STACK_SIZE},
         error::ExecuteError,
         instruction::Instruction,
@@ -191,11 +276,33 @@ mod call {
 
         Ok(())
     }
+
+    #[test]
+    fn tracks_call_in_call_stack() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.program_counter = 3;
+        let instruction = Instruction::Call(Operand::Immediate(500));
+        let expected = Some(Frame {
+            entry: 500,
+            call_site: 3,
+            stack_pointer: 8,
+        });
+
+        i.execute(instruction)?;
+
+        assert_eq!(i.call_stack.depth(), 1);
+        assert_eq!(i.call_stack.pop(), expected);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod ret {
-    use crate::{constant::Word, error::ExecuteError, instruction::Instruction, Interpreter};
+    use crate::{
+        constant::Word, error::ExecuteError, instruction::Instruction, operand::Operand,
+        Interpreter,
+    };
 
     #[test]
     fn stack_underflow() {
@@ -223,4 +330,142 @@ mod ret {
 
         Ok(())
     }
+
+    #[test]
+    fn removes_call_from_call_stack() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.execute(Instruction::Call(Operand::Immediate(500)))?;
+        let instruction = Instruction::Return;
+
+        i.execute(instruction)?;
+
+        assert_eq!(i.call_stack.depth(), 0);
+        assert_eq!(i.program_counter, 2);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod checked_ret {
+    use crate::{
+        constant::{Byte, Word},
+        error::ExecuteError,
+        instruction::Instruction,
+        operand::Operand,
+        Interpreter,
+    };
+
+    fn checked_interpreter_in_call() -> Interpreter {
+        let mut i = Interpreter::new_test();
+        i.config.checked = true;
+        i.functions.insert(5, String::from("inc_ra"));
+        i.execute(Instruction::Call(Operand::Immediate(5)))
+            .expect("should be able to call");
+
+        i
+    }
+
+    #[test]
+    fn return_outside_function() {
+        let mut i = Interpreter::new_test();
+        i.config.checked = true;
+        i.stack
+            .push::<Word>(2)
+            .expect("should be able to push value onto stack");
+        let expected = Err(ExecuteError::ReturnOutsideFunction);
+
+        let actual = i.execute(Instruction::Return);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bytes_left_on_stack() {
+        let mut i = checked_interpreter_in_call();
+        i.stack
+            .push::<Byte>(1)
+            .expect("should be able to push value onto stack");
+        let expected = Err(ExecuteError::BytesLeftOnStack(String::from("inc_ra"), 1));
+
+        let actual = i.execute(Instruction::Return);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bytes_taken_from_caller() {
+        let mut i = checked_interpreter_in_call();
+        i.stack.pop::<Word>().expect("should be able to pop value");
+        let expected = Err(ExecuteError::BytesTakenFromCaller(
+            String::from("inc_ra"),
+            8,
+        ));
+
+        let actual = i.execute(Instruction::Return);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn corrupt_return_address() {
+        let mut i = checked_interpreter_in_call();
+        i.stack.pop::<Word>().expect("should be able to pop value");
+        i.stack
+            .push::<Word>(40)
+            .expect("should be able to push value onto stack");
+        let expected = Err(ExecuteError::CorruptReturnAddress(
+            String::from("inc_ra"),
+            2,
+            40,
+        ));
+
+        let actual = i.execute(Instruction::Return);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn balanced_return() -> Result<(), ExecuteError> {
+        let mut i = checked_interpreter_in_call();
+
+        i.execute(Instruction::Return)?;
+
+        assert_eq!(i.program_counter, 2);
+        assert_eq!(i.call_stack.depth(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unchecked_return_to_garbage() -> Result<(), ExecuteError> {
+        let mut i = checked_interpreter_in_call();
+        i.config.checked = false;
+        i.stack
+            .push::<Byte>(1)
+            .expect("should be able to push value onto stack");
+
+        i.execute(Instruction::Return)?;
+
+        assert_ne!(i.program_counter, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn stop_prints_maximum_call_depth() -> Result<(), ExecuteError> {
+        let mut i = checked_interpreter_in_call();
+        i.execute(Instruction::Call(Operand::Immediate(5)))?;
+        i.execute(Instruction::Return)?;
+        let expected = "Maximum Call Depth: 2\n";
+
+        i.execute(Instruction::Stop)?;
+        let report = i.config.report.get_buffer().unwrap();
+        let output = i.config.output.get_buffer().unwrap();
+
+        assert_eq!(report, expected);
+        assert_eq!(output, "");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,235 @@
+use crate::{
+    constant::{Byte, Half, Quarter, Word},
+    error::ExecuteError,
+    instruction::Read,
+    register::Register,
+    registers::RegisterOperations,
+    utils::ToWord,
+    Interpreter,
+};
+
+impl Interpreter {
+    /// Reads from the input, setting the overflow flag if the end of the input has been reached.
+    pub(super) fn read(&mut self, instruction: Read) -> Result<(), ExecuteError> {
+        match instruction {
+            Read::UnsignedByte(r) => self.read_integer(r, "unsigned byte", str::parse::<Byte>),
+            Read::UnsignedQuarter(r) => {
+                self.read_integer(r, "unsigned quarter", str::parse::<Quarter>)
+            }
+            Read::UnsignedHalf(r) => self.read_integer(r, "unsigned half", str::parse::<Half>),
+            Read::UnsignedWord(r) => self.read_integer(r, "unsigned word", str::parse::<Word>),
+            Read::SignedByte(r) => {
+                self.read_integer(r, "signed byte", |s| s.parse::<i8>().map(i8::cast_unsigned))
+            }
+            Read::SignedQuarter(r) => self.read_integer(r, "signed quarter", |s| {
+                s.parse::<i16>().map(i16::cast_unsigned)
+            }),
+            Read::SignedHalf(r) => self.read_integer(r, "signed half", |s| {
+                s.parse::<i32>().map(i32::cast_unsigned)
+            }),
+            Read::SignedWord(r) => self.read_integer(r, "signed word", |s| {
+                s.parse::<i64>().map(i64::cast_unsigned)
+            }),
+            Read::Character(r) => {
+                let byte = self
+                    .config
+                    .input
+                    .read_byte()
+                    .map_err(|err| ExecuteError::IO(err.to_string()))?;
+
+                self.flags.overflow = byte.is_none();
+                self.registers.set(r, byte.unwrap_or_default());
+                Ok(())
+            }
+            Read::Line(r) => self.read_line(r),
+        }
+    }
+
+    fn read_integer<T, E>(
+        &mut self,
+        register: Register,
+        kind: &str,
+        parse: impl Fn(&str) -> Result<T, E>,
+    ) -> Result<(), ExecuteError>
+    where
+        T: ToWord,
+    {
+        let token = self
+            .config
+            .input
+            .read_token()
+            .map_err(|err| ExecuteError::IO(err.to_string()))?;
+
+        let value = match &token {
+            Some(token) => parse(token)
+                .map_err(|_| ExecuteError::InvalidInput(token.clone(), kind.to_string()))?
+                .to_word(),
+            None => 0,
+        };
+
+        self.flags.overflow = token.is_none();
+        self.registers.set(register, value);
+        Ok(())
+    }
+
+    fn read_line(&mut self, register: Register) -> Result<(), ExecuteError> {
+        let line = self
+            .config
+            .input
+            .read_line_bytes()
+            .map_err(|err| ExecuteError::IO(err.to_string()))?;
+
+        self.flags.overflow = line.is_none();
+        let line = line.unwrap_or_default();
+        for byte in &line {
+            self.stack.push::<Byte>(*byte)?;
+        }
+
+        self.registers.set(register, line.len() as Word);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod integer {
+    use crate::{
+        constant::{Byte, Quarter, Word},
+        error::ExecuteError,
+        instruction::{Instruction, Read},
+        register::Register,
+        registers::RegisterOperations,
+        Interpreter,
+    };
+    use std::io::Cursor;
+
+    fn interpreter(input: &str) -> Interpreter {
+        let mut i = Interpreter::new_test();
+        i.config.input = Box::new(Cursor::new(input.as_bytes().to_vec()));
+        i
+    }
+
+    #[test]
+    fn unsigned() -> Result<(), ExecuteError> {
+        let mut i = interpreter("200 60000\n");
+
+        i.execute(Instruction::Read(Read::UnsignedByte(Register::A)))?;
+        i.execute(Instruction::Read(Read::UnsignedQuarter(Register::B)))?;
+
+        assert_eq!(i.registers.get::<Byte>(Register::A), 200);
+        assert_eq!(i.registers.get::<Quarter>(Register::B), 60000);
+        assert!(!i.flags.overflow);
+
+        Ok(())
+    }
+
+    #[test]
+    fn signed() -> Result<(), ExecuteError> {
+        let mut i = interpreter("-1\n-2");
+
+        i.execute(Instruction::Read(Read::SignedByte(Register::A)))?;
+        i.execute(Instruction::Read(Read::SignedWord(Register::B)))?;
+
+        assert_eq!(i.registers.get::<Word>(Register::A), 0xFF);
+        assert_eq!(i.registers.get::<Word>(Register::B), Word::MAX - 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_error() {
+        let mut i = interpreter("256");
+        let expected = Err(ExecuteError::InvalidInput(
+            String::from("256"),
+            String::from("unsigned byte"),
+        ));
+
+        let actual = i.execute(Instruction::Read(Read::UnsignedByte(Register::A)));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn end_of_input_sets_overflow() -> Result<(), ExecuteError> {
+        let mut i = interpreter(" \n");
+        i.registers.set(Register::A, 5 as Word);
+
+        i.execute(Instruction::Read(Read::UnsignedWord(Register::A)))?;
+
+        assert_eq!(i.registers.get::<Word>(Register::A), 0);
+        assert!(i.flags.overflow);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod character {
+    use crate::{
+        constant::Word,
+        error::ExecuteError,
+        instruction::{Instruction, Read},
+        register::Register,
+        registers::RegisterOperations,
+        Interpreter,
+    };
+    use std::io::Cursor;
+
+    #[test]
+    fn reads_bytes_until_end_of_input() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.config.input = Box::new(Cursor::new(b"a\n".to_vec()));
+
+        i.execute(Instruction::Read(Read::Character(Register::A)))?;
+        assert_eq!(i.registers.get::<Word>(Register::A), Word::from(b'a'));
+
+        i.execute(Instruction::Read(Read::Character(Register::A)))?;
+        assert_eq!(i.registers.get::<Word>(Register::A), Word::from(b'\n'));
+        assert!(!i.flags.overflow);
+
+        i.execute(Instruction::Read(Read::Character(Register::A)))?;
+        assert_eq!(i.registers.get::<Word>(Register::A), 0);
+        assert!(i.flags.overflow);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod line {
+    use crate::{
+        constant::Word,
+        error::ExecuteError,
+        instruction::{Instruction, Read},
+        register::Register,
+        registers::RegisterOperations,
+        Interpreter,
+    };
+    use std::io::Cursor;
+
+    #[test]
+    fn pushes_line_onto_stack() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+        i.config.input = Box::new(Cursor::new(b"Hello\nWorld".to_vec()));
+
+        i.execute(Instruction::Read(Read::Line(Register::C)))?;
+
+        assert_eq!(i.registers.get::<Word>(Register::C), 5);
+        assert_eq!(i.stack.bytes().get(..i.stack.sp()), Some(&b"Hello"[..]));
+        assert!(!i.flags.overflow);
+
+        Ok(())
+    }
+
+    #[test]
+    fn end_of_input_sets_overflow() -> Result<(), ExecuteError> {
+        let mut i = Interpreter::new_test();
+
+        i.execute(Instruction::Read(Read::Line(Register::C)))?;
+
+        assert_eq!(i.registers.get::<Word>(Register::C), 0);
+        assert_eq!(i.stack.sp(), 0);
+        assert!(i.flags.overflow);
+
+        Ok(())
+    }
+}
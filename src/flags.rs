@@ -1,6 +1,7 @@
-use crate::utils::Setable;
+use crate::{error::DecodeError, utils::Setable};
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Flags {
     pub sign: bool,
     pub overflow: bool,
@@ -8,6 +9,7 @@ pub struct Flags {
 }
 
 impl Flags {
+    #[must_use]
     pub fn new() -> Self {
         Self {
             sign: false,
@@ -21,6 +23,60 @@ impl Flags {
         self.zero = result.is_zero();
         self.sign = result.is_signed();
     }
+
+    #[must_use]
+    pub fn get(&self, flag: Flag) -> bool {
+        match flag {
+            Flag::Sign => self.sign,
+            Flag::Overflow => self.overflow,
+            Flag::Zero => self.zero,
+        }
+    }
+}
+
+impl Default for Flags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single flag, as named in the source code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flag {
+    Sign,
+    Overflow,
+    Zero,
+}
+
+impl Flag {
+    const SIGN: &'static str = "sf";
+    const OVERFLOW: &'static str = "of";
+    const ZERO: &'static str = "zf";
+}
+
+impl Display for Flag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Flag::Sign => Flag::SIGN,
+            Flag::Overflow => Flag::OVERFLOW,
+            Flag::Zero => Flag::ZERO,
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+impl TryFrom<&str> for Flag {
+    type Error = DecodeError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            Flag::SIGN => Ok(Flag::Sign),
+            Flag::OVERFLOW => Ok(Flag::Overflow),
+            Flag::ZERO => Ok(Flag::Zero),
+            unknown => Err(DecodeError::InvalidFlag(unknown.to_string())),
+        }
+    }
 }
 
 // It does not make sense to test `overflow` flag and `zero` flag as they are trivially set.
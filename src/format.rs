@@ -0,0 +1,528 @@
+//! Formatting of source code into a canonical layout.
+//!
+//! Instructions and comments inside functions and the data section are indented,
+//! operands and trailing comments are aligned into columns within each function and the data section,
+//! and blank lines are normalised such that every function is preceded by exactly one blank line.
+//!
+//! As jumps and calls may target line numbers, blank lines are only normalised if every target
+//! can be moved along with the lines, otherwise every line is kept in place.
+
+use crate::{
+    cli::{read_program_file, FormatArguments},
+    constant::{Word, COMMENT},
+    decode::decode,
+    error::InterpreterError,
+    golden::diff,
+    instruction::{Instruction, Relative},
+    operand::Operand,
+    preprocess::{expand_data_section, DATA_SECTION},
+};
+use std::{collections::HashSet, fs};
+
+const INDENT: &str = "  ";
+const FUNCTION: &str = "fn";
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Blank,
+    /// A comment on a line of its own, including the comment marker.
+    Comment(String),
+    /// A function definition or the start of the data section, which are never indented.
+    Label(String, Option<String>),
+    /// An instruction or an entry of the data section, split into tokens.
+    Code(Vec<String>, Option<String>),
+}
+
+#[derive(Debug)]
+struct Line {
+    kind: Kind,
+    /// Every function and the data section start a new section, in which columns are aligned.
+    section: usize,
+    indented: bool,
+    /// The line number in the source code, or none for inserted blank lines.
+    number: Option<usize>,
+}
+
+/// Where a jump or call goes, given as an immediate value in the source code.
+#[derive(Debug, PartialEq)]
+enum Target {
+    Absolute(Word),
+    Relative(Relative, Word),
+}
+
+/// Splits a line into its code and its trailing comment.
+pub(crate) fn split_comment(line: &str) -> (&str, Option<&str>) {
+    match line.find(COMMENT) {
+        Some(index) => {
+            let (code, comment) = line.split_at(index);
+            (code, Some(comment.trim_end()))
+        }
+        None => (line, None),
+    }
+}
+
+fn parse(source_code: &str) -> Vec<Line> {
+    let data_start = source_code
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with(DATA_SECTION))
+        .map(|(index, _)| index)
+        .last();
+
+    let mut section = 0;
+    let mut lines: Vec<Line> = source_code
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let trimmed = line.trim();
+            let in_data = data_start.is_some_and(|start| index > start);
+            let kind = if trimmed.is_empty() {
+                Kind::Blank
+            } else if trimmed.starts_with(COMMENT) {
+                Kind::Comment(trimmed.to_string())
+            } else {
+                let (code, comment) = split_comment(trimmed);
+                let comment = comment.map(str::to_string);
+                let tokens: Vec<String> =
+                    code.split_ascii_whitespace().map(str::to_string).collect();
+
+                let is_function = !in_data && tokens.first().is_some_and(|t| t == FUNCTION);
+                if data_start == Some(index) || is_function {
+                    section += 1;
+                    Kind::Label(tokens.join(" "), comment)
+                } else {
+                    Kind::Code(tokens, comment)
+                }
+            };
+
+            Line {
+                indented: section > 0 && !matches!(kind, Kind::Label(_, _)),
+                kind,
+                section,
+                number: Some(index + 1),
+            }
+        })
+        .collect();
+
+    // a block of comments directly above a label documents it, so it is not indented
+    for index in 0..lines.len() {
+        if !matches!(
+            lines.get(index),
+            Some(Line {
+                kind: Kind::Label(_, _),
+                ..
+            })
+        ) {
+            continue;
+        }
+
+        for line in lines.iter_mut().take(index).rev() {
+            if !matches!(line.kind, Kind::Comment(_)) {
+                break;
+            }
+            line.indented = false;
+        }
+    }
+
+    lines
+}
+
+/// Finds the target of every jump and call given as an immediate value, by the index of its line.
+///
+/// Returns none if any target is computed at runtime or comes from the data section,
+/// in which case the lines cannot be moved.
+fn targets(source_code: &str, lines: &[Line]) -> Option<Vec<(usize, Target)>> {
+    let expanded = expand_data_section(source_code).ok()?;
+    let functions: HashSet<&str> = lines
+        .iter()
+        .filter_map(|line| match &line.kind {
+            Kind::Label(label, _) => label.strip_prefix("fn ")?.strip_suffix(':'),
+            _ => None,
+        })
+        .collect();
+
+    let mut targets = Vec::new();
+    for ((index, line), expanded) in lines.iter().enumerate().zip(expanded.lines()) {
+        let Kind::Code(tokens, _) = &line.kind else {
+            continue;
+        };
+        let (code, _) = split_comment(expanded);
+        let operand = tokens.get(1).map(String::as_str).unwrap_or_default();
+        let literal = operand
+            .trim_start_matches(['+', '-'])
+            .parse::<Word>()
+            .is_ok();
+
+        let target = match decode(code) {
+            Ok(
+                Instruction::Call(Operand::Immediate(line))
+                | Instruction::Jump(_, Operand::Immediate(line), None),
+            ) => Target::Absolute(line),
+            Ok(Instruction::Jump(_, Operand::Immediate(offset), Some(relative))) => {
+                Target::Relative(relative, offset)
+            }
+            Ok(Instruction::Call(Operand::Register(_)) | Instruction::Jump(_, _, _)) => {
+                return None;
+            }
+            _ => continue,
+        };
+
+        // the target of a call by function name is found during preprocessing
+        if functions.contains(operand) {
+            continue;
+        }
+        if !literal {
+            return None;
+        }
+        targets.push((index, target));
+    }
+
+    Some(targets)
+}
+
+/// Keeps at most one blank line in a row, ensures a blank line before every label
+/// and its comments, and removes blank lines at the start and end.
+fn normalise_blank_lines(lines: Vec<Line>) -> Vec<Line> {
+    let mut anchors = HashSet::new();
+    for (index, line) in lines.iter().enumerate() {
+        if let Kind::Label(_, _) = line.kind {
+            let comments = lines
+                .iter()
+                .take(index)
+                .rev()
+                .take_while(|line| matches!(line.kind, Kind::Comment(_)))
+                .count();
+            anchors.insert(index - comments);
+        }
+    }
+
+    let mut normalised: Vec<Line> = Vec::with_capacity(lines.len());
+    for (index, line) in lines.into_iter().enumerate() {
+        let after_blank = normalised
+            .last()
+            .is_none_or(|line| line.kind == Kind::Blank);
+
+        if line.kind == Kind::Blank {
+            if !after_blank {
+                normalised.push(line);
+            }
+            continue;
+        }
+
+        if anchors.contains(&index) && !after_blank {
+            normalised.push(Line {
+                kind: Kind::Blank,
+                section: line.section,
+                indented: false,
+                number: None,
+            });
+        }
+        normalised.push(line);
+    }
+
+    remove_trailing_blank_lines(&mut normalised);
+    normalised
+}
+
+fn remove_trailing_blank_lines(lines: &mut Vec<Line>) {
+    while lines.last().is_some_and(|line| line.kind == Kind::Blank) {
+        lines.pop();
+    }
+}
+
+/// Moves the targets of jumps and calls along with the lines they target.
+///
+/// Returns none if a target is no longer part of the program.
+fn retarget(lines: &mut [Line], targets: Vec<(usize, Target)>) -> Option<()> {
+    // the new line number of every line of the source code
+    let mut moved: Vec<Option<usize>> = Vec::new();
+    for (new, line) in (1..).zip(lines.iter()) {
+        if let Some(old) = line.number {
+            moved.resize(moved.len().max(old + 1), None);
+            *moved.get_mut(old)? = Some(new);
+        }
+    }
+    let moved = |old: usize| moved.get(old).copied().flatten();
+
+    let mut replacements = Vec::with_capacity(targets.len());
+    for (index, target) in targets {
+        let old = index + 1;
+        let operand = match target {
+            Target::Absolute(line) => moved(usize::try_from(line).ok()?)?.to_string(),
+            Target::Relative(relative, offset) => {
+                let offset = usize::try_from(offset).ok()?;
+                let target = match relative {
+                    Relative::Positive => old.checked_add(offset)?,
+                    Relative::Negative => old.checked_sub(offset)?,
+                };
+                let offset = moved(old)?.abs_diff(moved(target)?);
+                format!("{relative}{offset}")
+            }
+        };
+        replacements.push((moved(old)?, operand));
+    }
+
+    for (new, operand) in replacements {
+        if let Some(Line {
+            kind: Kind::Code(tokens, _),
+            ..
+        }) = lines.get_mut(new - 1)
+        {
+            if let Some(token) = tokens.get_mut(1) {
+                *token = operand;
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// Aligns the tokens and trailing comments of every section into columns.
+fn render(lines: &[Line]) -> String {
+    let sections = lines.last().map_or(0, |line| line.section + 1);
+    let mut widths: Vec<Vec<usize>> = vec![Vec::new(); sections];
+    for line in lines {
+        let (Kind::Code(tokens, _), Some(widths)) = (&line.kind, widths.get_mut(line.section))
+        else {
+            continue;
+        };
+
+        // the last token is never padded, so it does not widen its column
+        for (column, token) in tokens.iter().rev().skip(1).rev().enumerate() {
+            if widths.len() <= column {
+                widths.push(0);
+            }
+            if let Some(width) = widths.get_mut(column) {
+                *width = (*width).max(token.chars().count());
+            }
+        }
+    }
+
+    let code = |line: &Line| -> Option<String> {
+        let Kind::Code(tokens, _) = &line.kind else {
+            return None;
+        };
+        let widths = widths.get(line.section)?;
+        let last = tokens.len().saturating_sub(1);
+
+        Some(
+            tokens
+                .iter()
+                .enumerate()
+                .map(|(column, token)| match widths.get(column) {
+                    Some(width) if column < last => format!("{token:<width$} "),
+                    _ => token.clone(),
+                })
+                .collect(),
+        )
+    };
+
+    let mut comment_columns = vec![0; sections];
+    for line in lines {
+        if let (Kind::Code(_, Some(_)), Some(code)) = (&line.kind, code(line)) {
+            if let Some(column) = comment_columns.get_mut(line.section) {
+                *column = (*column).max(code.chars().count());
+            }
+        }
+    }
+
+    let mut formatted = String::new();
+    for line in lines {
+        let indent = if line.indented { INDENT } else { "" };
+        let text = match &line.kind {
+            Kind::Blank => String::new(),
+            Kind::Comment(comment) => format!("{indent}{comment}"),
+            Kind::Label(label, Some(comment)) => format!("{label} {comment}"),
+            Kind::Label(label, None) => label.clone(),
+            Kind::Code(_, comment) => {
+                let code = code(line).unwrap_or_default();
+                match comment {
+                    Some(comment) => {
+                        let column = comment_columns.get(line.section).copied().unwrap_or(0);
+                        format!("{indent}{code:<column$} {comment}")
+                    }
+                    None => format!("{indent}{code}"),
+                }
+            }
+        };
+
+        formatted.push_str(&text);
+        formatted.push('\n');
+    }
+
+    formatted
+}
+
+/// Formats the source code, keeping every comment.
+///
+/// Formatting already formatted source code leaves it unchanged.
+#[must_use]
+pub fn format(source_code: &str) -> String {
+    let mut lines = parse(source_code);
+    let Some(targets) = targets(source_code, &lines) else {
+        remove_trailing_blank_lines(&mut lines);
+        return render(&lines);
+    };
+
+    let mut normalised = normalise_blank_lines(parse(source_code));
+    if retarget(&mut normalised, targets).is_none() {
+        remove_trailing_blank_lines(&mut lines);
+        return render(&lines);
+    }
+
+    render(&normalised)
+}
+
+/// Formats every file in place, or only checks whether they are formatted,
+/// printing the difference for every file that is not.
+///
+/// Returns whether every file was already formatted when checking, and true otherwise.
+///
+/// # Errors
+/// Returns an error if a file cannot be read, or if a formatted file cannot be written back.
+pub fn format_files(args: &FormatArguments) -> Result<bool, InterpreterError> {
+    let mut formatted = true;
+
+    for file in &args.files {
+        let source_code = read_program_file(file).map_err(InterpreterError::Argument)?;
+        let result = format(&source_code);
+        if result == source_code {
+            continue;
+        }
+
+        if args.check {
+            let expected: Vec<&str> = source_code.lines().collect();
+            let actual: Vec<&str> = result.lines().collect();
+            println!(
+                "'{}' is not formatted:\n{}",
+                file.display(),
+                diff(&expected, &actual).unwrap_or_default()
+            );
+            formatted = false;
+        } else {
+            fs::write(file, result)
+                .map_err(|err| InterpreterError::FailedFormatWrite(err.to_string()))?;
+        }
+    }
+
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod indentation {
+    use super::format;
+
+    #[test]
+    fn functions_and_data_section() {
+        let source = "  call inc\n stop\n\nfn inc:\naddb ra ONE\n      ret\n\nDATA:\nONE 1\n";
+        let expected = "call inc\nstop\n\nfn inc:\n  addb ra ONE\n  ret\n\nDATA:\n  ONE 1\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn comments_follow_their_code() {
+        let source = "// top level\nstop\n\n// documents inc\nfn inc:\n// inside\naddb ra 1\nret\n";
+        let expected =
+            "// top level\nstop\n\n// documents inc\nfn inc:\n  // inside\n  addb ra 1\n  ret\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod alignment {
+    use super::format;
+
+    #[test]
+    fn operands_and_trailing_comments() {
+        let source = "setb ra 1 // first\njnz -2\naeqq 1000 rb    // second\nstop\n";
+        let expected = "setb ra   1  // first\njnz  -2\naeqq 1000 rb // second\nstop\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn per_section() {
+        let source = "call f\nstop\n\nfn f:\nsetb ra 1\nret\n\nDATA:\nA_LONG_KEY 1\nB 2\n";
+        let expected =
+            "call f\nstop\n\nfn f:\n  setb ra 1\n  ret\n\nDATA:\n  A_LONG_KEY 1\n  B          2\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod blank_lines {
+    use super::format;
+
+    #[test]
+    fn one_between_functions() {
+        let source = "\n\ncall f\nstop\nfn f:\nret\n\n\n\nfn g:\nret\n\n";
+        let expected = "call f\nstop\n\nfn f:\n  ret\n\nfn g:\n  ret\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn jump_targets_are_moved() {
+        let source = "\nsetb ra 3\nsubb ra 1\njnz -1\njmp 8\n\n\nstop\n";
+        let expected = "setb ra 3\nsubb ra 1\njnz  -1\njmp  6\n\nstop\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn kept_for_computed_jump_targets() {
+        let source = "\nsetb ra 5\njmp ra\n\n\nstop\n";
+        let expected = "\nsetb ra 5\njmp  ra\n\n\nstop\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn kept_for_jump_targets_in_data_section() {
+        let source = "jmp END\n\n\nstop\nDATA:\n  END 4\n";
+        let expected = "jmp END\n\n\nstop\nDATA:\n  END 4\n";
+
+        let actual = format(source);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod idempotence {
+    use super::format;
+
+    #[test]
+    fn formatting_twice() {
+        let sources = [
+            "   call calc   // entry\nstop\nfn calc:\n    setb ra 1 // one\n  addw rb   300\n  // doubled\n jnz -2\n   ret\n// doc\nfn helper:\n  prrb ra\n\n\n\n  jmp 4\nDATA:\nTARGET 10 // the target\n    FIB_1  0\n\n",
+            "\njmp ra\n\n\n  stop // end\n",
+            "",
+        ];
+
+        for source in sources {
+            let expected = format(source);
+
+            let actual = format(&expected);
+
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Comparison of the output of a program against its expected output.
+//!
+//! The expected output is either embedded at the end of the source code, as a block of comments
+//! starting with `// expect:`, or kept in a sidecar file with the same name and an `.out` extension.
+
+use crate::{constant::COMMENT, error::InterpreterError};
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const EXPECT_MARKER: &str = "// expect:";
+const SIDECAR_EXTENSION: &str = "out";
+
+/// Where the expected output of a program is kept.
+#[derive(Debug, PartialEq)]
+enum Location {
+    /// The index of the marker line and the amount of lines in the block, including the marker.
+    Embedded(usize, usize),
+    Sidecar(PathBuf),
+}
+
+#[derive(Debug, PartialEq)]
+struct Expectation {
+    location: Location,
+    lines: Vec<String>,
+}
+
+/// Finds the block of comments following the last `// expect:` marker.
+fn embedded(source: &str) -> Option<Expectation> {
+    let lines: Vec<&str> = source.lines().collect();
+    let start = lines
+        .iter()
+        .rposition(|line| line.trim() == EXPECT_MARKER)?;
+
+    let expected: Vec<String> = lines
+        .iter()
+        .skip(start + 1)
+        .map_while(|line| line.trim_start().strip_prefix(COMMENT))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).to_string())
+        .collect();
+
+    Some(Expectation {
+        location: Location::Embedded(start, expected.len() + 1),
+        lines: expected,
+    })
+}
+
+fn sidecar_path(file: &Path) -> PathBuf {
+    file.with_extension(SIDECAR_EXTENSION)
+}
+
+/// Finds the expected output, preferring an embedded block over a sidecar file.
+fn find(file: &Path, source: &str) -> Result<Option<Expectation>, InterpreterError> {
+    if let Some(expectation) = embedded(source) {
+        return Ok(Some(expectation));
+    }
+
+    let path = sidecar_path(file);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|err| InterpreterError::FailedExpectationAccess(err.to_string()))?;
+    Ok(Some(Expectation {
+        location: Location::Sidecar(path),
+        lines: content.lines().map(str::to_string).collect(),
+    }))
+}
+
+/// Creates a line based diff, where removed lines are prefixed with '-' and added lines with '+'.
+///
+/// Returns none if there is no difference.
+#[expect(
+    clippy::indexing_slicing,
+    reason = "the table has one more row and column than there are lines, so every index is in bounds"
+)]
+pub(crate) fn diff(expected: &[&str], actual: &[&str]) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    // the length of the longest common subsequence of the lines from each index onwards
+    let mut lcs = vec![vec![0_usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            let _ = writeln!(diff, "  {}", expected[i]);
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            let _ = writeln!(diff, "- {}", expected[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(diff, "+ {}", actual[j]);
+            j += 1;
+        }
+    }
+
+    Some(diff)
+}
+
+/// Replaces the expected output with the actual output.
+fn bless(
+    file: &Path,
+    source: &str,
+    location: Option<Location>,
+    actual: &str,
+) -> Result<(), InterpreterError> {
+    let (path, content) = match location {
+        Some(Location::Embedded(start, length)) => {
+            let block =
+                std::iter::once(EXPECT_MARKER.to_string()).chain(actual.lines().map(|line| {
+                    if line.is_empty() {
+                        COMMENT.to_string()
+                    } else {
+                        format!("{COMMENT} {line}")
+                    }
+                }));
+            let lines: Vec<String> = source
+                .lines()
+                .take(start)
+                .map(str::to_string)
+                .chain(block)
+                .chain(source.lines().skip(start + length).map(str::to_string))
+                .collect();
+
+            (file.to_path_buf(), lines.join("\n") + "\n")
+        }
+        Some(Location::Sidecar(path)) => (path, actual.to_string()),
+        None => (sidecar_path(file), actual.to_string()),
+    };
+
+    fs::write(path, content)
+        .map_err(|err| InterpreterError::FailedExpectationAccess(err.to_string()))
+}
+
+/// Compares the actual output of the program against its expected output,
+/// or replaces the expected output with the actual output when blessing.
+///
+/// # Errors
+/// Returns [`InterpreterError::OutputMismatch`] with a diff if the outputs differ,
+/// and [`InterpreterError::MissingExpectation`] if the program has no expected output.
+pub fn verify_output(
+    file: &Path,
+    source: &str,
+    actual: &str,
+    bless_output: bool,
+) -> Result<(), InterpreterError> {
+    let expectation = find(file, source)?;
+
+    if bless_output {
+        return bless(file, source, expectation.map(|e| e.location), actual);
+    }
+
+    let expectation =
+        expectation.ok_or_else(|| InterpreterError::MissingExpectation(file.to_path_buf()))?;
+    let expected: Vec<&str> = expectation.lines.iter().map(String::as_str).collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    match diff(&expected, &actual) {
+        Some(diff) => Err(InterpreterError::OutputMismatch(diff)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod embedded {
+    use super::{embedded, Expectation, Location};
+
+    #[test]
+    fn trailing_block() {
+        let source = "prrb ra\nstop\n\n// expect:\n// ra: 0\n//\n//   indented\n";
+        let expected = Some(Expectation {
+            location: Location::Embedded(3, 4),
+            lines: vec![
+                String::from("ra: 0"),
+                String::new(),
+                String::from("  indented"),
+            ],
+        });
+
+        let actual = embedded(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn block_ends_at_first_non_comment() {
+        let source = "// expect:\n// ra: 0\nprrb ra\n// not expected";
+        let expected = Some(Expectation {
+            location: Location::Embedded(0, 2),
+            lines: vec![String::from("ra: 0")],
+        });
+
+        let actual = embedded(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn missing_marker() {
+        let source = "prrb ra\nstop\n// ra: 0";
+
+        let actual = embedded(source);
+
+        assert_eq!(actual, None);
+    }
+}
+
+#[cfg(test)]
+mod diff {
+    use super::diff;
+
+    #[test]
+    fn equal() {
+        let actual = diff(&["ra: 1", "ra: 2"], &["ra: 1", "ra: 2"]);
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn changed_line() {
+        let expected = Some(String::from("  ra: 1\n- ra: 2\n+ ra: 3\n  ra: 4\n"));
+
+        let actual = diff(&["ra: 1", "ra: 2", "ra: 4"], &["ra: 1", "ra: 3", "ra: 4"]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn missing_and_extra_lines() {
+        let expected = Some(String::from("- ra: 1\n  ra: 2\n+ ra: 3\n"));
+
+        let actual = diff(&["ra: 1", "ra: 2"], &["ra: 2", "ra: 3"]);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod verify_output {
+    use super::verify_output;
+    use crate::error::InterpreterError;
+    use std::{fs, path::PathBuf};
+
+    fn file(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("kasm_golden_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        directory.join(name)
+    }
+
+    #[test]
+    fn embedded_match() {
+        let source = "prrb ra\nstop\n// expect:\n// ra: 0\n";
+
+        let actual = verify_output(&file("match.kasm"), source, "ra: 0\n", false);
+
+        assert_eq!(actual, Ok(()));
+    }
+
+    #[test]
+    fn embedded_mismatch_error() {
+        let source = "prrb ra\nstop\n// expect:\n// ra: 1\n";
+        let expected = Err(InterpreterError::OutputMismatch(String::from(
+            "- ra: 1\n+ ra: 0\n",
+        )));
+
+        let actual = verify_output(&file("mismatch.kasm"), source, "ra: 0\n", false);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn missing_expectation_error() {
+        let path = file("missing.kasm");
+        let _ = fs::remove_file(path.with_extension("out"));
+        let expected = Err(InterpreterError::MissingExpectation(path.clone()));
+
+        let actual = verify_output(&path, "stop", "", false);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn sidecar_blessed_then_matched() {
+        let path = file("sidecar.kasm");
+        let _ = fs::remove_file(path.with_extension("out"));
+
+        verify_output(&path, "stop", "ra: 5\n", true).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path.with_extension("out")).unwrap(),
+            "ra: 5\n"
+        );
+        assert_eq!(verify_output(&path, "stop", "ra: 5\n", false), Ok(()));
+    }
+
+    #[test]
+    fn embedded_blessed() {
+        let path = file("bless.kasm");
+        let source = "prrb ra\nstop\n\n// expect:\n// ra: 1\nDATA:\n  FIVE 5\n";
+        let expected = "prrb ra\nstop\n\n// expect:\n// ra: 0\n//\n// done\nDATA:\n  FIVE 5\n";
+
+        verify_output(&path, source, "ra: 0\n\ndone\n", true).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+    }
+}
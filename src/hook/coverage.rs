@@ -0,0 +1,409 @@
+use super::{Hook, Machine};
+use crate::{
+    instruction::{Instruction, Jump},
+    program::Program,
+    utils::Writer,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write as _},
+    fs,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+/// How often a conditional jump was and was not performed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Branch {
+    taken: u64,
+    not_taken: u64,
+}
+
+/// The coverage of a single source file.
+#[derive(Debug, Default, PartialEq)]
+pub struct FileCoverage {
+    /// The execution count of every line holding an instruction.
+    lines: BTreeMap<usize, u64>,
+    /// The branches of every line holding a conditional jump.
+    branches: BTreeMap<usize, Branch>,
+}
+
+impl FileCoverage {
+    fn merge(&mut self, other: &FileCoverage) {
+        for (line, count) in &other.lines {
+            *self.lines.entry(*line).or_default() += count;
+        }
+
+        for (line, branch) in &other.branches {
+            let b = self.branches.entry(*line).or_default();
+            b.taken += branch.taken;
+            b.not_taken += branch.not_taken;
+        }
+    }
+
+    /// Annotates every line of the source with how often it was executed.
+    ///
+    /// Lines without an instruction are marked with '-', and instructions that were never executed with '#####'.
+    fn listing(&self, source: &str) -> String {
+        let mut listing = String::new();
+
+        for (line, code) in (1..).zip(source.lines()) {
+            let count = match self.lines.get(&line) {
+                Some(0) => String::from("#####"),
+                Some(count) => count.to_string(),
+                None => String::from("-"),
+            };
+            let _ = writeln!(listing, "{count:>9}:{line:>5}:{code}");
+
+            if let Some(branch) = self.branches.get(&line) {
+                let _ = writeln!(
+                    listing,
+                    "{:>9}:{:>5}:  jump taken {}, not taken {}",
+                    "", "", branch.taken, branch.not_taken
+                );
+            }
+        }
+
+        listing
+    }
+}
+
+/// The coverage of any number of source files, in the lcov tracefile format.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report(BTreeMap<String, FileCoverage>);
+
+impl Report {
+    /// Parses the records of a tracefile, ignoring any information other than lines and branches.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut report = Report::default();
+        let mut current: Option<(String, FileCoverage)> = None;
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let invalid = || format!("invalid coverage record '{line}'");
+
+            if let Some(file) = line.strip_prefix("SF:") {
+                current = Some((file.to_string(), FileCoverage::default()));
+            } else if line == "end_of_record" {
+                let (file, coverage) = current.take().ok_or_else(invalid)?;
+                report.merge(&file, &coverage);
+            } else if let Some(record) = line.strip_prefix("DA:") {
+                let (_, coverage) = current.as_mut().ok_or_else(invalid)?;
+                let mut fields = record.split(',');
+                let line_number: usize = parse_field(fields.next()).ok_or_else(invalid)?;
+                let count: u64 = parse_field(fields.next()).ok_or_else(invalid)?;
+
+                *coverage.lines.entry(line_number).or_default() += count;
+            } else if let Some(record) = line.strip_prefix("BRDA:") {
+                let (_, coverage) = current.as_mut().ok_or_else(invalid)?;
+                let fields: Vec<&str> = record.split(',').collect();
+                let [line_number, _, branch, count] = fields.as_slice() else {
+                    return Err(invalid());
+                };
+                let line_number: usize = parse_field(Some(line_number)).ok_or_else(invalid)?;
+                // '-' marks a branch whose line was never executed
+                let count: u64 = match *count {
+                    "-" => 0,
+                    count => parse_field(Some(count)).ok_or_else(invalid)?,
+                };
+
+                let b = coverage.branches.entry(line_number).or_default();
+                match *branch {
+                    "0" => b.taken += count,
+                    "1" => b.not_taken += count,
+                    _ => return Err(invalid()),
+                }
+            }
+        }
+
+        match current {
+            Some((file, _)) => Err(format!("missing end of record for '{file}'")),
+            None => Ok(report),
+        }
+    }
+
+    pub fn merge(&mut self, file: &str, coverage: &FileCoverage) {
+        self.0.entry(file.to_string()).or_default().merge(coverage);
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(field: Option<&str>) -> Option<T> {
+    field?.parse().ok()
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (file, coverage) in &self.0 {
+            writeln!(f, "TN:")?;
+            writeln!(f, "SF:{file}")?;
+
+            for (line, branch) in &coverage.branches {
+                let executed = coverage.lines.get(line).is_some_and(|count| *count > 0);
+                for (index, count) in [branch.taken, branch.not_taken].into_iter().enumerate() {
+                    if executed {
+                        writeln!(f, "BRDA:{line},0,{index},{count}")?;
+                    } else {
+                        writeln!(f, "BRDA:{line},0,{index},-")?;
+                    }
+                }
+            }
+            let branches_hit = coverage
+                .branches
+                .values()
+                .flat_map(|branch| [branch.taken, branch.not_taken])
+                .filter(|count| *count > 0)
+                .count();
+            writeln!(f, "BRF:{}", coverage.branches.len() * 2)?;
+            writeln!(f, "BRH:{branches_hit}")?;
+
+            for (line, count) in &coverage.lines {
+                writeln!(f, "DA:{line},{count}")?;
+            }
+            let lines_hit = coverage.lines.values().filter(|count| **count > 0).count();
+            writeln!(f, "LF:{}", coverage.lines.len())?;
+            writeln!(f, "LH:{lines_hit}")?;
+
+            writeln!(f, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Records which lines were executed and which way every conditional jump went.
+///
+/// The tracefile is merged into if it already exists, such that coverage accumulates over multiple runs.
+pub struct Coverage {
+    /// The name of the source file as written to the tracefile.
+    file: String,
+    tracefile: PathBuf,
+    listing: Option<PathBuf>,
+    source: String,
+    counts: FileCoverage,
+}
+
+impl Coverage {
+    pub fn new(file: String, tracefile: PathBuf, listing: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            tracefile,
+            listing,
+            source: String::new(),
+            counts: FileCoverage::default(),
+        }
+    }
+}
+
+impl Hook for Coverage {
+    fn start(&mut self, source: &str, program: &Program) {
+        self.source = source.to_string();
+
+        for line in 1..=program.lines().len() {
+            let Ok(Some(instruction)) = program.instruction(line) else {
+                continue;
+            };
+
+            self.counts.lines.insert(line, 0);
+            if matches!(instruction, Instruction::Jump(ref jump, _, _) if *jump != Jump::Unconditional)
+            {
+                self.counts.branches.insert(line, Branch::default());
+            }
+        }
+    }
+
+    fn before(&mut self, machine: &Machine<'_>, instruction: &Instruction) {
+        *self.counts.lines.entry(machine.pc).or_default() += 1;
+
+        if let Instruction::Jump(jump, _, _) = instruction {
+            if *jump != Jump::Unconditional {
+                let branch = self.counts.branches.entry(machine.pc).or_default();
+                if jump.condition(machine.flags) {
+                    branch.taken += 1;
+                } else {
+                    branch.not_taken += 1;
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, _report: &mut dyn Writer) -> std::io::Result<()> {
+        let mut report = match fs::read_to_string(&self.tracefile) {
+            Ok(previous) => {
+                Report::parse(&previous).map_err(|err| Error::new(ErrorKind::InvalidData, err))?
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Report::default(),
+            Err(err) => return Err(err),
+        };
+        report.merge(&self.file, &self.counts);
+        fs::write(&self.tracefile, report.to_string())?;
+
+        if let Some(listing) = &self.listing {
+            let merged = report.0.get(&self.file).unwrap_or(&self.counts);
+            fs::write(listing, merged.listing(&self.source))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tracefile {
+    use super::{Branch, Coverage, FileCoverage, Report};
+    use crate::Interpreter;
+    use std::{collections::BTreeMap, fs};
+
+    const PROGRAM: &str = "setb ra 2
+call down
+stop
+
+fn down:
+  subb ra 1
+  jiz +2
+  jmp -2
+  ret";
+
+    fn expected() -> FileCoverage {
+        FileCoverage {
+            lines: BTreeMap::from([(1, 1), (2, 1), (3, 1), (6, 2), (7, 2), (8, 1), (9, 1)]),
+            branches: BTreeMap::from([(
+                7,
+                Branch {
+                    taken: 1,
+                    not_taken: 1,
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn records_lines_and_branches() {
+        let directory =
+            std::env::temp_dir().join(format!("kasm_coverage_records_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let tracefile = directory.join("lcov.info");
+        let listing = directory.join("listing.txt");
+        let _ = fs::remove_file(&tracefile);
+
+        let mut i = Interpreter::new_test();
+        i.config.hooks.push(Box::new(Coverage::new(
+            String::from("down.kasm"),
+            tracefile.clone(),
+            Some(listing.clone()),
+        )));
+        i.run(PROGRAM).unwrap();
+
+        let actual = Report::parse(&fs::read_to_string(&tracefile).unwrap()).unwrap();
+        let mut expected_report = Report::default();
+        expected_report.merge("down.kasm", &expected());
+        assert_eq!(actual, expected_report);
+
+        let expected_listing = [
+            "        1:    1:setb ra 2",
+            "        1:    2:call down",
+            "        1:    3:stop",
+            "        -:    4:",
+            "        -:    5:fn down:",
+            "        2:    6:  subb ra 1",
+            "        2:    7:  jiz +2",
+            "         :     :  jump taken 1, not taken 1",
+            "        1:    8:  jmp -2",
+            "        1:    9:  ret",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(fs::read_to_string(&listing).unwrap(), expected_listing);
+    }
+
+    #[test]
+    fn merges_with_previous_runs() {
+        let directory =
+            std::env::temp_dir().join(format!("kasm_coverage_merges_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let tracefile = directory.join("lcov.info");
+        let _ = fs::remove_file(&tracefile);
+
+        for _ in 0..2 {
+            let mut i = Interpreter::new_test();
+            i.config.hooks.push(Box::new(Coverage::new(
+                String::from("down.kasm"),
+                tracefile.clone(),
+                None,
+            )));
+            i.run(PROGRAM).unwrap();
+        }
+
+        let actual = Report::parse(&fs::read_to_string(&tracefile).unwrap()).unwrap();
+        let mut expected_report = Report::default();
+        expected_report.merge("down.kasm", &expected());
+        expected_report.merge("down.kasm", &expected());
+        assert_eq!(actual, expected_report);
+    }
+
+    #[test]
+    fn lcov_format() {
+        let mut report = Report::default();
+        report.merge("down.kasm", &expected());
+        report.merge(
+            "other.kasm",
+            &FileCoverage {
+                lines: BTreeMap::from([(1, 0), (2, 0)]),
+                branches: BTreeMap::from([(2, Branch::default())]),
+            },
+        );
+        let expected = [
+            "TN:",
+            "SF:down.kasm",
+            "BRDA:7,0,0,1",
+            "BRDA:7,0,1,1",
+            "BRF:2",
+            "BRH:2",
+            "DA:1,1",
+            "DA:2,1",
+            "DA:3,1",
+            "DA:6,2",
+            "DA:7,2",
+            "DA:8,1",
+            "DA:9,1",
+            "LF:7",
+            "LH:7",
+            "end_of_record",
+            "TN:",
+            "SF:other.kasm",
+            "BRDA:2,0,0,-",
+            "BRDA:2,0,1,-",
+            "BRF:2",
+            "BRH:0",
+            "DA:1,0",
+            "DA:2,0",
+            "LF:2",
+            "LH:0",
+            "end_of_record",
+            "",
+        ]
+        .join("\n");
+
+        let actual = report.to_string();
+
+        assert_eq!(actual, expected);
+        assert_eq!(Report::parse(&actual), Ok(report));
+    }
+
+    #[test]
+    fn parse_invalid_record_error() {
+        let input = "SF:down.kasm\nDA:one,1\nend_of_record\n";
+        let expected = Err(String::from("invalid coverage record 'DA:one,1'"));
+
+        let actual = Report::parse(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_missing_end_of_record_error() {
+        let input = "SF:down.kasm\nDA:1,1\n";
+        let expected = Err(String::from("missing end of record for 'down.kasm'"));
+
+        let actual = Report::parse(input);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,272 @@
+use super::{Hook, Machine};
+use crate::{
+    constant::LOOP_DETECTION_HISTORY, error::ExecuteError, instruction::Instruction, utils::Writer,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+/// Detects when the program revisits an identical state, meaning it will loop forever.
+///
+/// The state is only recorded after a backward transfer of control, such as a backward jump or a return,
+/// as every loop contains at least one. States are compared by their hash, so a collision could in theory
+/// report a loop that does not exist.
+///
+/// Hashing the stack is costly, so it is only hashed once the rest of the state has been seen before.
+/// A loop is then reported on its second repetition rather than its first.
+///
+/// Reading input or calling a host function makes the following states depend on more than the machine itself,
+/// so every recorded state is forgotten when one of those instructions is executed.
+///
+/// To bound the memory used by long running loops that do end, every recorded state is also forgotten
+/// after [`LOOP_DETECTION_HISTORY`] backward transfers. A loop is therefore only detected if it repeats
+/// twice within that many transfers, so loops taking more than half of them per repetition are not reported.
+pub struct LoopDetector {
+    /// The amount of backward transfers after which every recorded state is forgotten.
+    history: usize,
+    /// The line of the instruction being executed, and whether it depends on anything outside the machine.
+    current: Option<(usize, bool)>,
+    /// The source and destination line of every backward transfer since the states were last forgotten.
+    transfers: Vec<(usize, usize)>,
+    /// The hash of every recorded state excluding the stack contents.
+    seen: HashSet<u64>,
+    /// The hash of every recorded state including the stack contents, mapped to the amount of transfers before it was recorded.
+    states: HashMap<u64, usize>,
+}
+
+impl LoopDetector {
+    pub fn new() -> Self {
+        Self::with_history(LOOP_DETECTION_HISTORY)
+    }
+
+    fn with_history(history: usize) -> Self {
+        Self {
+            history,
+            current: None,
+            transfers: Vec::new(),
+            seen: HashSet::new(),
+            states: HashMap::new(),
+        }
+    }
+
+    fn forget(&mut self) {
+        self.transfers.clear();
+        self.seen.clear();
+        self.states.clear();
+    }
+
+    /// Hashes the state excluding the stack contents.
+    fn hash_machine(machine: &Machine<'_>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        machine.pc.hash(&mut hasher);
+        machine.registers.hash(&mut hasher);
+        machine.flags.sign.hash(&mut hasher);
+        machine.flags.overflow.hash(&mut hasher);
+        machine.flags.zero.hash(&mut hasher);
+        machine.stack.sp().hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    fn hash_state(machine_hash: u64, machine: &Machine<'_>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        machine_hash.hash(&mut hasher);
+        machine
+            .stack
+            .bytes()
+            .get(..machine.stack.sp())
+            .hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
+
+impl Hook for LoopDetector {
+    fn before(&mut self, machine: &Machine<'_>, instruction: &Instruction) {
+        let external = matches!(instruction, Instruction::Read(_) | Instruction::External(_));
+        self.current = Some((machine.pc, external));
+    }
+
+    fn after(&mut self, machine: &Machine<'_>) -> Result<(), ExecuteError> {
+        let Some((line, external)) = self.current.take() else {
+            return Ok(());
+        };
+
+        if external {
+            self.forget();
+            return Ok(());
+        }
+
+        if machine.pc > line {
+            return Ok(());
+        }
+
+        if self.transfers.len() >= self.history {
+            self.forget();
+        }
+        self.transfers.push((line, machine.pc));
+        let machine_hash = Self::hash_machine(machine);
+        if self.seen.insert(machine_hash) {
+            return Ok(());
+        }
+
+        let state = Self::hash_state(machine_hash, machine);
+        if let Some(&first) = self.states.get(&state) {
+            // every line of the cycle lies between the destination and source of its backward transfers
+            let cycle = self.transfers.get(first..).unwrap_or_default();
+            let start = cycle.iter().map(|(_, to)| *to).min().unwrap_or(machine.pc);
+            let end = cycle.iter().map(|(from, _)| *from).max().unwrap_or(line);
+
+            return Err(ExecuteError::InfiniteLoop(start, end));
+        }
+
+        self.states.insert(state, self.transfers.len());
+        Ok(())
+    }
+
+    fn finish(&mut self, _report: &mut dyn Writer) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod loop_detector {
+    use super::LoopDetector;
+    use crate::{
+        call_stack::{CallStack, Functions},
+        error::{ExecuteError, InterpreterError},
+        flags::Flags,
+        hook::{Hook, Machine},
+        instruction::Instruction,
+        register::Register,
+        registers::{RegisterOperations, Registers},
+        stack::Stack,
+        Interpreter,
+    };
+    use std::io::Cursor;
+
+    fn run_with(detector: LoopDetector, program: &[&str]) -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        i.config.hooks.push(Box::new(detector));
+        i.run(&program.join("\n"))
+    }
+
+    fn run(program: &[&str]) -> Result<(), InterpreterError> {
+        run_with(LoopDetector::new(), program)
+    }
+
+    #[test]
+    fn jump_to_self() {
+        let expected = Err(InterpreterError::Execute(
+            2,
+            ExecuteError::InfiniteLoop(2, 2),
+        ));
+
+        let actual = run(&["setb ra 1", "jmp 2"]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn loop_through_function() {
+        let expected = Err(InterpreterError::Execute(
+            3,
+            ExecuteError::InfiniteLoop(2, 8),
+        ));
+
+        let actual = run(&[
+            "setb ra 1",
+            "call reset_ra",
+            "addb ra 1",
+            "jmp 2",
+            "stop",
+            "fn reset_ra:",
+            "  setb ra 0",
+            "  ret",
+        ]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn failed_instruction_is_the_transfer() {
+        let mut i = Interpreter::new_test();
+        i.config.hooks.push(Box::new(LoopDetector::new()));
+
+        let _ = i.run("setb ra 1\ncall f\naddb ra 1\njmp 2\nstop\nfn f:\n  setb ra 0\n  ret");
+
+        assert_eq!(i.failed_instruction(), Some(String::from("ret")));
+    }
+
+    #[test]
+    fn terminating_loop() {
+        let actual = run(&["addb ra 1", "cmpb ra 200", "jnz 1", "stop"]);
+
+        assert_eq!(actual, Ok(()));
+    }
+
+    #[test]
+    fn terminating_loop_with_stack_growth() {
+        let actual = run(&["pshb 0", "jmp 1"]);
+
+        assert!(matches!(
+            actual,
+            Err(InterpreterError::Execute(1, ExecuteError::StackOverflow))
+        ));
+    }
+
+    #[test]
+    fn input_forgets_states() {
+        let mut i = Interpreter::new_test();
+        i.config.input = Box::new(Cursor::new(b"1 2 3 0".to_vec()));
+        i.config.hooks.push(Box::new(LoopDetector::new()));
+        let program = ["rdub ra", "cmpb ra 0", "jnz 1", "stop"].join("\n");
+
+        let actual = i.run(&program);
+
+        assert_eq!(actual, Ok(()));
+    }
+
+    #[test]
+    fn detected_within_history() {
+        let expected = Err(InterpreterError::Execute(
+            2,
+            ExecuteError::InfiniteLoop(2, 2),
+        ));
+
+        let actual = run_with(LoopDetector::with_history(3), &["setb ra 1", "jmp 2"]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn history_is_bounded() -> Result<(), ExecuteError> {
+        let mut detector = LoopDetector::with_history(8);
+        let mut registers: Registers = [0; Register::VARIANT_COUNT];
+        let (flags, stack) = (Flags::new(), Stack::new(8));
+        let (call_stack, functions) = (CallStack::new(), Functions::new());
+
+        // every repetition of the loop is in a new state, as a counter is increased
+        for step in 0..100 {
+            registers.set(Register::A, step);
+            let machine = |pc| Machine {
+                step,
+                pc,
+                registers: &registers,
+                flags: &flags,
+                stack: &stack,
+                call_stack: &call_stack,
+                functions: &functions,
+            };
+
+            detector.before(&machine(2), &Instruction::Stop);
+            detector.after(&machine(1))?;
+
+            assert!(detector.transfers.len() <= 8);
+            assert!(detector.seen.len() <= 8);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! Observers of the execution of instructions, such as tracing, profiling, coverage, loop detection
+//! and detection of reads of unwritten registers.
+//!
+//! Hooks are only invoked when at least one is registered, such that they cost nothing when disabled.
+
+use crate::{
+    call_stack::{function_name, CallStack, Functions},
+    error::ExecuteError,
+    flags::Flags,
+    instruction::Instruction,
+    program::Program,
+    registers::Registers,
+    stack::Stack,
+    utils::Writer,
+    Interpreter,
+};
+pub use coverage::Coverage;
+pub use loop_detection::LoopDetector;
+pub use profile::Profiler;
+pub use snapshot::Snapshotter;
+pub use trace::{TraceFilter, TraceFormat, Tracer};
+pub use uninitialised::UninitialisedDetector;
+
+mod coverage;
+mod loop_detection;
+mod profile;
+mod snapshot;
+mod trace;
+mod uninitialised;
+
+/// A read-only view of the machine state, given to hooks.
+pub struct Machine<'a> {
+    /// The number of the instruction being executed, starting at 1.
+    pub step: u64,
+    pub pc: usize,
+    pub registers: &'a Registers,
+    pub flags: &'a Flags,
+    pub stack: &'a Stack,
+    pub call_stack: &'a CallStack,
+    pub functions: &'a Functions,
+}
+
+impl Machine<'_> {
+    /// Gets the name of the function currently executing, if any.
+    pub fn function(&self) -> Option<String> {
+        self.call_stack
+            .last()
+            .map(|frame| function_name(frame.entry, self.functions))
+    }
+}
+
+pub trait Hook {
+    /// Called once the program has been preprocessed, before any instruction is executed.
+    ///
+    /// The source is the program as given, before preprocessing.
+    fn start(&mut self, _source: &str, _program: &Program) {}
+
+    /// Called before an instruction is executed.
+    fn before(&mut self, machine: &Machine<'_>, instruction: &Instruction);
+
+    /// Called after an instruction was executed without errors.
+    fn after(&mut self, _machine: &Machine<'_>) -> Result<(), ExecuteError> {
+        Ok(())
+    }
+
+    /// Called once the interpretation has ended, regardless of whether it ended in an error.
+    ///
+    /// Reports are written to the report, which is kept apart from the output of print instructions.
+    fn finish(&mut self, report: &mut dyn Writer) -> std::io::Result<()>;
+}
+
+impl Interpreter {
+    pub(crate) fn machine(&self) -> Machine<'_> {
+        Machine {
+            step: self.config.instructions_executed,
+            pc: self.pc(),
+            registers: &self.registers,
+            flags: &self.flags,
+            stack: &self.stack,
+            call_stack: &self.call_stack,
+            functions: &self.functions,
+        }
+    }
+
+    pub(crate) fn hooks_start(&mut self, source: &str, program: &Program) {
+        for hook in &mut self.config.hooks {
+            hook.start(source, program);
+        }
+    }
+
+    pub(crate) fn hooks_before(&mut self, instruction: &Instruction) {
+        let mut hooks = std::mem::take(&mut self.config.hooks);
+        let machine = self.machine();
+
+        for hook in &mut hooks {
+            hook.before(&machine, instruction);
+        }
+
+        self.config.hooks = hooks;
+    }
+
+    pub(crate) fn hooks_after(&mut self) -> Result<(), ExecuteError> {
+        let mut hooks = std::mem::take(&mut self.config.hooks);
+        let machine = self.machine();
+
+        let result = hooks.iter_mut().try_for_each(|hook| hook.after(&machine));
+
+        self.config.hooks = hooks;
+        result
+    }
+
+    pub(crate) fn hooks_finish(&mut self) -> std::io::Result<()> {
+        let mut hooks = std::mem::take(&mut self.config.hooks);
+
+        let result = hooks
+            .iter_mut()
+            .try_for_each(|hook| hook.finish(self.config.report.as_mut()));
+
+        self.config.hooks = hooks;
+        result
+    }
+}
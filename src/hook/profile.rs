@@ -0,0 +1,265 @@
+use super::{Hook, Machine};
+use crate::{
+    call_stack::{function_name, Functions, TOP_LEVEL},
+    constant::PROFILE_HOT_LINES,
+    instruction::Instruction,
+    program::Program,
+    utils::Writer,
+};
+use std::collections::BTreeMap;
+
+/// The amount of instructions executed within a function.
+#[derive(Debug, Default, PartialEq)]
+struct Counts {
+    /// Including instructions executed by the functions it called.
+    inclusive: u64,
+    /// Only instructions of the function itself.
+    exclusive: u64,
+}
+
+/// Counts executed instructions per line and per function.
+///
+/// Functions are identified by their first line while running, and only named once the report is written.
+pub struct Profiler {
+    /// Whether the hot spot table is printed once the interpretation has ended.
+    table: bool,
+    /// Where the folded stacks are written once the interpretation has ended, if anywhere.
+    folded: Option<Box<dyn Writer>>,
+    names: Functions,
+    total: u64,
+    /// The count of each line, alongside the function it was first executed in, or none for the top level.
+    lines: BTreeMap<usize, (Option<usize>, u64)>,
+    functions: BTreeMap<Option<usize>, Counts>,
+    /// The exclusive count of each chain of calls, given by the first lines of the called functions.
+    stacks: BTreeMap<Vec<usize>, u64>,
+    /// The functions already counted for the current instruction, and the chain of calls leading to it,
+    /// kept between instructions to reuse their allocations.
+    counted: Vec<Option<usize>>,
+    entries: Vec<usize>,
+}
+
+impl Profiler {
+    pub fn new(table: bool, folded: Option<Box<dyn Writer>>) -> Self {
+        Self {
+            table,
+            folded,
+            names: Functions::new(),
+            total: 0,
+            lines: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            stacks: BTreeMap::new(),
+            counted: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    fn percent(&self, count: u64) -> f64 {
+        count as f64 * 100.0 / self.total.max(1) as f64
+    }
+
+    fn name(&self, function: Option<usize>) -> String {
+        match function {
+            Some(entry) => function_name(entry, &self.names),
+            None => TOP_LEVEL.to_string(),
+        }
+    }
+
+    fn write_table(&self, output: &mut dyn Writer) -> std::io::Result<()> {
+        writeln!(output, "Profile of {} instructions", self.total)?;
+
+        let mut functions: Vec<(String, &Counts)> = self
+            .functions
+            .iter()
+            .map(|(function, counts)| (self.name(*function), counts))
+            .collect();
+        functions.sort_by(|(a_name, a), (b_name, b)| {
+            b.exclusive
+                .cmp(&a.exclusive)
+                .then(b.inclusive.cmp(&a.inclusive))
+                .then(a_name.cmp(b_name))
+        });
+
+        writeln!(
+            output,
+            "\n{:>10} {:>7} {:>10} {:>7}  function",
+            "inclusive", "%", "exclusive", "%"
+        )?;
+        for (name, counts) in functions {
+            writeln!(
+                output,
+                "{:>10} {:>6.1}% {:>10} {:>6.1}%  {name}",
+                counts.inclusive,
+                self.percent(counts.inclusive),
+                counts.exclusive,
+                self.percent(counts.exclusive),
+            )?;
+        }
+
+        let mut lines: Vec<(&usize, &(Option<usize>, u64))> = self.lines.iter().collect();
+        lines.sort_by(|(_, (_, a)), (_, (_, b))| b.cmp(a));
+
+        writeln!(
+            output,
+            "\n{:>10} {:>7} {:>7}  function",
+            "count", "%", "line"
+        )?;
+        for (line, (function, count)) in lines.into_iter().take(PROFILE_HOT_LINES) {
+            writeln!(
+                output,
+                "{count:>10} {:>6.1}% {line:>7}  {}",
+                self.percent(*count),
+                self.name(*function),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Hook for Profiler {
+    fn start(&mut self, _source: &str, program: &Program) {
+        self.names = program.functions();
+    }
+
+    fn before(&mut self, machine: &Machine<'_>, _instruction: &Instruction) {
+        let frames = machine.call_stack.frames();
+        let current = frames.last().map(|frame| frame.entry);
+
+        self.total += 1;
+        self.lines.entry(machine.pc).or_insert((current, 0)).1 += 1;
+
+        // recursive functions are only counted once per instruction
+        self.counted.clear();
+        self.counted.push(None);
+        self.counted
+            .extend(frames.iter().map(|frame| Some(frame.entry)));
+        self.counted.sort_unstable();
+        self.counted.dedup();
+        for function in &self.counted {
+            self.functions.entry(*function).or_default().inclusive += 1;
+        }
+        self.functions.entry(current).or_default().exclusive += 1;
+
+        self.entries.clear();
+        self.entries.extend(frames.iter().map(|frame| frame.entry));
+        match self.stacks.get_mut(self.entries.as_slice()) {
+            Some(count) => *count += 1,
+            None => {
+                self.stacks.insert(self.entries.clone(), 1);
+            }
+        }
+    }
+
+    fn finish(&mut self, report: &mut dyn Writer) -> std::io::Result<()> {
+        if self.table {
+            self.write_table(report)?;
+        }
+
+        if let Some(folded) = &mut self.folded {
+            for (stack, count) in &self.stacks {
+                let names: Vec<String> = std::iter::once(TOP_LEVEL.to_string())
+                    .chain(stack.iter().map(|entry| function_name(*entry, &self.names)))
+                    .collect();
+                writeln!(folded, "{} {count}", names.join(";"))?;
+            }
+            folded.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod profiler {
+    use super::Profiler;
+    use crate::{
+        utils::{Capture, SharedBuffer},
+        Interpreter, InterpreterError,
+    };
+
+    const PROGRAM: &str = "call inc
+call inc
+stop
+
+fn inc:
+  addb ra 1
+  ret";
+
+    fn profile(table: bool, program: &str) -> (Interpreter, String) {
+        let mut i = Interpreter::new_test();
+        let buffer = SharedBuffer::default();
+        let folded = Capture::new(buffer.clone(), None);
+        i.config
+            .hooks
+            .push(Box::new(Profiler::new(table, Some(Box::new(folded)))));
+
+        let result: Result<(), InterpreterError> = i.run(program);
+        assert_eq!(result, Ok(()));
+
+        let bytes = buffer.borrow().clone();
+        (i, String::from_utf8(bytes).unwrap())
+    }
+
+    #[test]
+    fn folded_stacks() {
+        let expected = "<top level> 3\n<top level>;inc 4\n";
+
+        let (_, actual) = profile(false, PROGRAM);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn recursion_counted_once_inclusively() {
+        let program = "setb ra 2
+call down
+stop
+
+fn down:
+  subb ra 1
+  jiz +2
+  call down
+  ret";
+        let expected_folded = [
+            "<top level> 3",
+            "<top level>;down 4",
+            "<top level>;down;down 3",
+            "",
+        ]
+        .join("\n");
+        let expected_row = "         7   70.0%          7   70.0%  down";
+
+        let (i, folded) = profile(true, program);
+        let table = i.config.report.get_buffer().unwrap();
+
+        assert_eq!(folded, expected_folded);
+        assert!(table.lines().any(|line| line == expected_row));
+    }
+
+    #[test]
+    fn hot_spot_table() {
+        let expected = [
+            "Profile of 7 instructions",
+            "",
+            " inclusive       %  exclusive       %  function",
+            "         4   57.1%          4   57.1%  inc",
+            "         7  100.0%          3   42.9%  <top level>",
+            "",
+            "     count       %    line  function",
+            "         2   28.6%       6  inc",
+            "         2   28.6%       7  inc",
+            "         1   14.3%       1  <top level>",
+            "         1   14.3%       2  <top level>",
+            "         1   14.3%       3  <top level>",
+            "",
+        ]
+        .join("\n");
+
+        let (i, _) = profile(true, PROGRAM);
+        let actual = i.config.report.get_buffer().unwrap();
+        let output = i.config.output.get_buffer().unwrap();
+
+        assert_eq!(actual, expected);
+        assert_eq!(output, "");
+    }
+}
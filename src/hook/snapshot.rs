@@ -0,0 +1,54 @@
+use super::{Hook, Machine};
+use crate::{
+    instruction::Instruction,
+    program::Program,
+    snapshot::{fingerprint, Snapshot},
+    utils::Writer,
+};
+use std::path::PathBuf;
+
+/// Writes a snapshot of the machine the first time it is about to execute the line.
+pub struct Snapshotter {
+    path: PathBuf,
+    line: usize,
+    program: u64,
+    written: bool,
+    /// The error of writing the snapshot, reported once the interpretation has ended.
+    error: Option<String>,
+}
+
+impl Snapshotter {
+    pub fn new(path: PathBuf, line: usize) -> Self {
+        Self {
+            path,
+            line,
+            program: 0,
+            written: false,
+            error: None,
+        }
+    }
+}
+
+impl Hook for Snapshotter {
+    fn start(&mut self, source: &str, _program: &Program) {
+        self.program = fingerprint(source);
+    }
+
+    fn before(&mut self, machine: &Machine<'_>, _instruction: &Instruction) {
+        if self.written || machine.pc != self.line {
+            return;
+        }
+
+        // the step already counts the instruction about to be executed
+        let snapshot = Snapshot::capture(self.program, machine, true, machine.step - 1);
+        self.error = snapshot.write(&self.path).err().map(|err| err.to_string());
+        self.written = true;
+    }
+
+    fn finish(&mut self, _report: &mut dyn Writer) -> std::io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(std::io::Error::other(error)),
+            None => Ok(()),
+        }
+    }
+}
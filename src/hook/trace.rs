@@ -0,0 +1,397 @@
+use super::{Hook, Machine};
+use crate::{
+    constant::Word,
+    error::ExecuteError,
+    flags::Flags,
+    instruction::Instruction,
+    register::Register,
+    registers::{RegisterOperations, Registers},
+    utils::Writer,
+};
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+use std::ops::RangeInclusive;
+
+/// The format of the records written by the tracer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum TraceFormat {
+    /// One human-readable line per instruction
+    #[default]
+    Human,
+    /// One JSON object per line
+    Json,
+}
+
+/// Restricts which instructions are traced.
+#[derive(Debug, Default, PartialEq)]
+pub struct TraceFilter {
+    /// Only trace instructions in functions with these names, unless empty.
+    pub functions: Vec<String>,
+    /// Only trace instructions on these lines, if specified.
+    pub lines: Option<RangeInclusive<usize>>,
+    /// Only trace every n-th instruction matching the filter.
+    pub sample: u64,
+    /// Stop tracing after this many records.
+    pub limit: Option<u64>,
+}
+
+impl TraceFilter {
+    fn matches(&self, machine: &Machine<'_>) -> bool {
+        let in_lines = self
+            .lines
+            .as_ref()
+            .is_none_or(|lines| lines.contains(&machine.pc));
+        let in_functions = self.functions.is_empty()
+            || machine
+                .function()
+                .is_some_and(|function| self.functions.contains(&function));
+
+        in_lines && in_functions
+    }
+}
+
+/// The state before an instruction that is going to be recorded.
+struct Pending {
+    step: u64,
+    line: usize,
+    instruction: String,
+    registers: Registers,
+    flags: Flags,
+}
+
+/// Writes a record for every executed instruction matching the filter.
+pub struct Tracer {
+    output: Box<dyn Writer>,
+    format: TraceFormat,
+    filter: TraceFilter,
+    matched: u64,
+    recorded: u64,
+    pending: Option<Pending>,
+}
+
+impl Tracer {
+    pub fn new(output: Box<dyn Writer>, format: TraceFormat, filter: TraceFilter) -> Self {
+        Self {
+            output,
+            format,
+            filter,
+            matched: 0,
+            recorded: 0,
+            pending: None,
+        }
+    }
+
+    fn write_record(&mut self, pending: &Pending, machine: &Machine<'_>) -> std::io::Result<()> {
+        let registers: Vec<(Register, Word)> = Register::ALL
+            .into_iter()
+            .map(|r| (r, machine.registers.get::<Word>(r)))
+            .filter(|(r, value)| *value != pending.registers.get::<Word>(*r))
+            .collect();
+        let flags: Vec<(&str, bool)> = [
+            ("of", pending.flags.overflow, machine.flags.overflow),
+            ("zf", pending.flags.zero, machine.flags.zero),
+            ("sf", pending.flags.sign, machine.flags.sign),
+        ]
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(name, _, after)| (name, after))
+        .collect();
+        let sp = machine.stack.sp();
+
+        match self.format {
+            TraceFormat::Human => {
+                let changes: Vec<String> = registers
+                    .iter()
+                    .map(|(r, value)| format!("{r} = {value}"))
+                    .chain(
+                        flags
+                            .iter()
+                            .map(|(name, value)| format!("{name} = {}", u8::from(*value))),
+                    )
+                    .collect();
+
+                write!(
+                    self.output,
+                    "step {}, line {}: {}",
+                    pending.step, pending.line, pending.instruction
+                )?;
+                if !changes.is_empty() {
+                    write!(self.output, " | {}", changes.join(", "))?;
+                }
+                writeln!(self.output, " | sp = {sp}")
+            }
+            TraceFormat::Json => {
+                let registers: Map<String, Value> = registers
+                    .iter()
+                    .map(|(r, value)| (r.to_string(), json!(value)))
+                    .collect();
+                let flags: Map<String, Value> = flags
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), json!(value)))
+                    .collect();
+                let record = json!({
+                    "step": pending.step,
+                    "line": pending.line,
+                    "instruction": pending.instruction,
+                    "registers": registers,
+                    "flags": flags,
+                    "sp": sp,
+                });
+
+                writeln!(self.output, "{record}")
+            }
+        }
+    }
+
+    /// Writes the record of an instruction which caused an error, so there is no state after it.
+    fn write_failed(&mut self, pending: &Pending) -> std::io::Result<()> {
+        match self.format {
+            TraceFormat::Human => writeln!(
+                self.output,
+                "step {}, line {}: {} | failed",
+                pending.step, pending.line, pending.instruction
+            ),
+            TraceFormat::Json => {
+                let record = json!({
+                    "step": pending.step,
+                    "line": pending.line,
+                    "instruction": pending.instruction,
+                    "failed": true,
+                });
+
+                writeln!(self.output, "{record}")
+            }
+        }
+    }
+}
+
+impl Hook for Tracer {
+    fn before(&mut self, machine: &Machine<'_>, instruction: &Instruction) {
+        if self
+            .filter
+            .limit
+            .is_some_and(|limit| self.recorded >= limit)
+            || !self.filter.matches(machine)
+        {
+            return;
+        }
+
+        self.matched += 1;
+        if !(self.matched - 1).is_multiple_of(self.filter.sample.max(1)) {
+            return;
+        }
+
+        self.pending = Some(Pending {
+            step: machine.step,
+            line: machine.pc,
+            instruction: instruction.to_string(),
+            registers: *machine.registers,
+            flags: machine.flags.clone(),
+        });
+    }
+
+    fn after(&mut self, machine: &Machine<'_>) -> Result<(), ExecuteError> {
+        if let Some(pending) = self.pending.take() {
+            self.write_record(&pending, machine)
+                .map_err(|err| ExecuteError::IO(err.to_string()))?;
+            self.recorded += 1;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, _report: &mut dyn Writer) -> std::io::Result<()> {
+        // the instruction is still pending if it failed, as only successful instructions are followed by `after`
+        if let Some(pending) = self.pending.take() {
+            self.write_failed(&pending)?;
+            self.recorded += 1;
+        }
+
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tracer {
+    use super::{TraceFilter, TraceFormat, Tracer};
+    use crate::{
+        utils::{Capture, SharedBuffer},
+        Interpreter, InterpreterError,
+    };
+
+    fn run(
+        format: TraceFormat,
+        filter: TraceFilter,
+        program: &[&str],
+    ) -> (Result<(), InterpreterError>, String) {
+        let mut i = Interpreter::new_test();
+        let buffer = SharedBuffer::default();
+        let output = Capture::new(buffer.clone(), None);
+        i.config
+            .hooks
+            .push(Box::new(Tracer::new(Box::new(output), format, filter)));
+
+        let result = i.run(&program.join("\n"));
+
+        let bytes = buffer.borrow().clone();
+        (result, String::from_utf8(bytes).unwrap())
+    }
+
+    fn trace(format: TraceFormat, filter: TraceFilter, program: &[&str]) -> String {
+        let (result, output) = run(format, filter, program);
+        assert_eq!(result, Ok(()));
+
+        output
+    }
+
+    fn filter() -> TraceFilter {
+        TraceFilter {
+            sample: 1,
+            ..TraceFilter::default()
+        }
+    }
+
+    #[test]
+    fn human_records_changes() {
+        let expected = [
+            "step 1, line 1: setb ra 5 | ra = 5 | sp = 0",
+            "step 2, line 2: pshb ra | sp = 1",
+            "step 3, line 3: subb ra 5 | ra = 0, zf = 1 | sp = 1",
+            "step 4, line 4: stop | sp = 1",
+            "",
+        ]
+        .join("\n");
+
+        let actual = trace(
+            TraceFormat::Human,
+            filter(),
+            &["setb ra 5", "pshb ra", "subb ra 5", "stop"],
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn json_records_changes() {
+        let expected = [
+            r#"{"flags":{},"instruction":"setb ra 5","line":1,"registers":{"ra":5},"sp":0,"step":1}"#,
+            r#"{"flags":{"zf":true},"instruction":"subb ra 5","line":2,"registers":{"ra":0},"sp":0,"step":2}"#,
+            r#"{"flags":{},"instruction":"stop","line":3,"registers":{},"sp":0,"step":3}"#,
+            "",
+        ]
+        .join("\n");
+
+        let actual = trace(
+            TraceFormat::Json,
+            filter(),
+            &["setb ra 5", "subb ra 5", "stop"],
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn filter_by_lines() {
+        let f = TraceFilter {
+            lines: Some(2..=2),
+            ..filter()
+        };
+        let expected = "step 2, line 2: setb rb 2 | rb = 2 | sp = 0\n";
+
+        let actual = trace(TraceFormat::Human, f, &["setb ra 1", "setb rb 2", "stop"]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn filter_by_function() {
+        let f = TraceFilter {
+            functions: vec![String::from("inc_ra")],
+            ..filter()
+        };
+        let expected = [
+            "step 2, line 5: addb ra 1 | ra = 1 | sp = 8",
+            "step 3, line 6: ret | sp = 0",
+            "",
+        ]
+        .join("\n");
+
+        let actual = trace(
+            TraceFormat::Human,
+            f,
+            &[
+                "call inc_ra",
+                "stop",
+                "",
+                "fn inc_ra:",
+                "  addb ra 1",
+                "  ret",
+            ],
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn sample_and_limit() {
+        let f = TraceFilter {
+            sample: 2,
+            limit: Some(2),
+            ..filter()
+        };
+        let expected = [
+            "step 1, line 1: setb ra 1 | ra = 1 | sp = 0",
+            "step 3, line 3: setb ra 3 | ra = 3 | sp = 0",
+            "",
+        ]
+        .join("\n");
+
+        let actual = trace(
+            TraceFormat::Human,
+            f,
+            &[
+                "setb ra 1",
+                "setb ra 2",
+                "setb ra 3",
+                "setb ra 4",
+                "setb ra 5",
+                "stop",
+            ],
+        );
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn failed_instruction_recorded() {
+        let expected = [
+            "step 1, line 1: setb ra 1 | ra = 1 | sp = 0",
+            "step 2, line 2: divb ra 0 | failed",
+            "",
+        ]
+        .join("\n");
+
+        let (result, actual) = run(
+            TraceFormat::Human,
+            filter(),
+            &["setb ra 1", "divb ra 0", "stop"],
+        );
+
+        assert!(result.is_err());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn failed_instruction_recorded_as_json() {
+        let expected = [
+            r#"{"failed":true,"instruction":"popb ra","line":1,"step":1}"#,
+            "",
+        ]
+        .join("\n");
+
+        let (result, actual) = run(TraceFormat::Json, filter(), &["popb ra", "stop"]);
+
+        assert!(result.is_err());
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,133 @@
+use super::{Hook, Machine};
+use crate::{
+    constant::Word, instruction::Instruction, register::Register, registers::RegisterOperations,
+    utils::Writer,
+};
+
+/// Reports every read of a register that has not been written since the program started.
+///
+/// Every register starts at zero, so reading one before writing it works, but is usually a mistake.
+/// Registers which are not zero when the first instruction is executed, such as when restored from a snapshot,
+/// are considered written, as are all registers once a host function has been called.
+#[derive(Default)]
+pub struct UninitialisedDetector {
+    /// Whether each register has been written, in the order of `Register::ALL`,
+    /// or none before the first instruction is executed.
+    written: Option<[bool; Register::VARIANT_COUNT]>,
+    /// The line, instruction and register of every read of an unwritten register, in the order they happened.
+    reads: Vec<(usize, String, Register)>,
+}
+
+impl UninitialisedDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Hook for UninitialisedDetector {
+    fn before(&mut self, machine: &Machine<'_>, instruction: &Instruction) {
+        let written = self.written.get_or_insert_with(|| {
+            Register::ALL.map(|register| machine.registers.get::<Word>(register) != 0)
+        });
+
+        for (register, _) in instruction.reads() {
+            let unwritten = !written.get(register as usize).copied().unwrap_or(true);
+            let reported = self
+                .reads
+                .iter()
+                .any(|(line, _, r)| *line == machine.pc && *r == register);
+            if unwritten && !reported {
+                self.reads
+                    .push((machine.pc, instruction.to_string(), register));
+            }
+        }
+
+        if let Instruction::External(_) = instruction {
+            *written = [true; Register::VARIANT_COUNT];
+        }
+        if let Some((register, _)) = instruction.writes() {
+            if let Some(written) = written.get_mut(register as usize) {
+                *written = true;
+            }
+        }
+    }
+
+    fn finish(&mut self, report: &mut dyn Writer) -> std::io::Result<()> {
+        if self.reads.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(report, "Reads of registers that were never written:")?;
+        for (line, instruction, register) in &self.reads {
+            writeln!(report, "  line {line}: '{instruction}' reads {register}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod uninitialised_detector {
+    use super::UninitialisedDetector;
+    use crate::{
+        utils::{Capture, SharedBuffer},
+        Interpreter,
+    };
+
+    fn run(program: &[&str]) -> String {
+        let buffer = SharedBuffer::default();
+        let mut i = Interpreter::new_test();
+        i.config.report = Box::new(Capture::new(buffer.clone(), None));
+        i.config.hooks.push(Box::new(UninitialisedDetector::new()));
+        i.run(&program.join("\n")).unwrap();
+
+        let output = String::from_utf8_lossy(&buffer.borrow()).into_owned();
+
+        output
+    }
+
+    #[test]
+    fn written_before_read() {
+        let program = [
+            "pshb 1",
+            "setb ra 1",
+            "addb ra 1",
+            "popb rb",
+            "addb ra rb",
+            "stop",
+        ];
+        let expected = "";
+
+        let actual = run(&program);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn read_before_written() {
+        let program = ["setb ra rb", "addb ra 1", "cmpb rc rb", "stop"];
+        let expected = "\
+Reads of registers that were never written:
+  line 1: 'setb ra rb' reads rb
+  line 3: 'cmpb rc rb' reads rc
+  line 3: 'cmpb rc rb' reads rb
+";
+
+        let actual = run(&program);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn reported_once_per_line() {
+        let program = ["setb ra 3", "cmpb rb 0", "subb ra 1", "jnz -2", "stop"];
+        let expected = "\
+Reads of registers that were never written:
+  line 2: 'cmpb rb 0' reads rb
+";
+
+        let actual = run(&program);
+
+        assert_eq!(actual, expected);
+    }
+}
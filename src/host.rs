@@ -0,0 +1,119 @@
+//! Functions defined by the embedding Rust program, which kasm programs invoke with the `ext` instruction.
+
+use crate::{
+    constant::{Byte, Word},
+    register::Register,
+    registers::{RegisterOperations, Registers},
+    stack::Stack,
+};
+
+/// A function of the embedding program, returning a message describing the failure on error.
+pub type HostFunction = Box<dyn FnMut(&mut Host) -> Result<(), String>>;
+
+/// The registered host functions, where the index of a function is the operand of its `ext` instructions.
+#[derive(Default)]
+pub struct HostFunctions(Vec<(String, HostFunction)>);
+
+impl HostFunctions {
+    /// Registers the function, replacing any earlier function with the same name.
+    pub fn register(&mut self, name: &str, function: HostFunction) {
+        match self.0.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = function,
+            None => self.0.push((name.to_string(), function)),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.0.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut (String, HostFunction)> {
+        self.0.get_mut(index)
+    }
+}
+
+/// The state of the interpreter a host function has access to.
+pub struct Host<'a> {
+    registers: &'a mut Registers,
+    stack: &'a mut Stack,
+}
+
+impl<'a> Host<'a> {
+    pub(crate) fn new(registers: &'a mut Registers, stack: &'a mut Stack) -> Self {
+        Self { registers, stack }
+    }
+
+    #[must_use]
+    pub fn register(&self, register: Register) -> Word {
+        self.registers.get::<Word>(register)
+    }
+
+    pub fn set_register(&mut self, register: Register, value: Word) {
+        self.registers.set(register, value);
+    }
+
+    /// Gets the bytes on the stack, from the bottom to the top.
+    #[must_use]
+    pub fn stack(&self) -> &[Byte] {
+        self.stack
+            .bytes()
+            .get(..self.stack.sp())
+            .unwrap_or_default()
+    }
+
+    /// Pushes a byte onto the stack.
+    ///
+    /// # Errors
+    /// Returns the message of the error if the stack overflows.
+    pub fn push_byte(&mut self, value: Byte) -> Result<(), String> {
+        self.stack.push(value).map_err(|err| err.to_string())
+    }
+
+    /// Pushes a word onto the stack.
+    ///
+    /// # Errors
+    /// Returns the message of the error if the stack overflows.
+    pub fn push_word(&mut self, value: Word) -> Result<(), String> {
+        self.stack.push(value).map_err(|err| err.to_string())
+    }
+
+    /// Pops a byte off the stack.
+    ///
+    /// # Errors
+    /// Returns the message of the error if the stack is empty.
+    pub fn pop_byte(&mut self) -> Result<Byte, String> {
+        self.stack.pop().map_err(|err| err.to_string())
+    }
+
+    /// Pops a word off the stack.
+    ///
+    /// # Errors
+    /// Returns the message of the error if the stack holds fewer than eight bytes.
+    pub fn pop_word(&mut self) -> Result<Word, String> {
+        self.stack.pop().map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod host_functions {
+    use super::{Host, HostFunctions};
+    use crate::{register::Register, stack::Stack};
+
+    #[test]
+    fn reregistering_keeps_index() {
+        let mut functions = HostFunctions::default();
+        functions.register("first", Box::new(|_| Ok(())));
+        functions.register("second", Box::new(|_| Ok(())));
+        functions.register("first", Box::new(|_| Err(String::from("replaced"))));
+        let mut registers = [0; Register::VARIANT_COUNT];
+        let mut stack = Stack::new(0);
+        let mut host = Host::new(&mut registers, &mut stack);
+
+        let actual = functions
+            .get_mut(0)
+            .map(|(_, function)| function(&mut host));
+
+        assert_eq!(functions.names(), ["first", "second"]);
+        assert_eq!(actual, Some(Err(String::from("replaced"))));
+    }
+}
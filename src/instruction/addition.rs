@@ -3,11 +3,23 @@ use crate::{
     operand::Operand,
     register::Register,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Addition {
     Byte(Register, Operand<Byte>),
     Quarter(Register, Operand<Quarter>),
     Half(Register, Operand<Half>),
     Word(Register, Operand<Word>),
 }
+
+impl Display for Addition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Addition::Byte(register, operand) => write!(f, "addb {register} {operand}"),
+            Addition::Quarter(register, operand) => write!(f, "addq {register} {operand}"),
+            Addition::Half(register, operand) => write!(f, "addh {register} {operand}"),
+            Addition::Word(register, operand) => write!(f, "addw {register} {operand}"),
+        }
+    }
+}
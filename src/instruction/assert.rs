@@ -0,0 +1,32 @@
+use crate::{
+    constant::{Byte, Half, Quarter, Word},
+    flags::Flag,
+    operand::Operand,
+};
+use std::fmt::Display;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Assert {
+    Byte(Operand<Byte>, Operand<Byte>),
+    Quarter(Operand<Quarter>, Operand<Quarter>),
+    Half(Operand<Half>, Operand<Half>),
+    Word(Operand<Word>, Operand<Word>),
+    /// The flag is expected to be set if true, and cleared if false.
+    Flag(Flag, bool),
+    /// The stack is expected to contain the given amount of bytes.
+    StackDepth(Operand<Word>),
+}
+
+impl Display for Assert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assert::Byte(expected, actual) => write!(f, "aeqb {expected} {actual}"),
+            Assert::Quarter(expected, actual) => write!(f, "aeqq {expected} {actual}"),
+            Assert::Half(expected, actual) => write!(f, "aeqh {expected} {actual}"),
+            Assert::Word(expected, actual) => write!(f, "aeqw {expected} {actual}"),
+            Assert::Flag(flag, true) => write!(f, "afs {flag}"),
+            Assert::Flag(flag, false) => write!(f, "afc {flag}"),
+            Assert::StackDepth(depth) => write!(f, "asd {depth}"),
+        }
+    }
+}
@@ -2,11 +2,23 @@ use crate::{
     constant::{Byte, Half, Quarter, Word},
     operand::Operand,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Compare {
     Byte(Operand<Byte>, Operand<Byte>),
     Quarter(Operand<Quarter>, Operand<Quarter>),
     Half(Operand<Half>, Operand<Half>),
     Word(Operand<Word>, Operand<Word>),
 }
+
+impl Display for Compare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compare::Byte(first, second) => write!(f, "cmpb {first} {second}"),
+            Compare::Quarter(first, second) => write!(f, "cmpq {first} {second}"),
+            Compare::Half(first, second) => write!(f, "cmph {first} {second}"),
+            Compare::Word(first, second) => write!(f, "cmpw {first} {second}"),
+        }
+    }
+}
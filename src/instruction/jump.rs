@@ -1,4 +1,7 @@
-#[derive(Debug, PartialEq)]
+use crate::flags::Flags;
+use std::fmt::Display;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Jump {
     Unconditional,
     IfZero,
@@ -13,8 +16,56 @@ pub enum Jump {
     IfLesserOrEqual,
 }
 
-#[derive(Debug, PartialEq)]
+impl Jump {
+    /// Checks whether the jump is performed given the current flags.
+    pub fn condition(&self, flags: &Flags) -> bool {
+        match self {
+            Jump::Unconditional => true,
+            Jump::IfZero => flags.zero,
+            Jump::IfNotZero => !flags.zero,
+            Jump::IfSign => flags.sign,
+            Jump::IfNotSign => !flags.sign,
+            Jump::IfOverflow => flags.overflow,
+            Jump::IfNotOverflow => !flags.overflow,
+            Jump::IfGreater => !flags.overflow && !flags.zero,
+            Jump::IfLesser => flags.overflow && !flags.zero,
+            Jump::IfGreaterOrEqual => !flags.overflow || flags.zero,
+            Jump::IfLesserOrEqual => flags.overflow ^ flags.zero,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Relative {
     Positive,
     Negative,
 }
+
+impl Display for Jump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Jump::Unconditional => "jmp",
+            Jump::IfZero => "jiz",
+            Jump::IfNotZero => "jnz",
+            Jump::IfSign => "jis",
+            Jump::IfNotSign => "jns",
+            Jump::IfOverflow => "jio",
+            Jump::IfNotOverflow => "jno",
+            Jump::IfGreater => "jig",
+            Jump::IfLesser => "jil",
+            Jump::IfGreaterOrEqual => "jge",
+            Jump::IfLesserOrEqual => "jle",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+impl Display for Relative {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relative::Positive => write!(f, "+"),
+            Relative::Negative => write!(f, "-"),
+        }
+    }
+}
@@ -1,8 +1,14 @@
-use crate::{constant::Word, operand::Operand};
+use crate::{
+    constant::{Byte, Half, Quarter, Word},
+    operand::Operand,
+    register::Register,
+};
+use std::fmt::Display;
 use variant_count::VariantCount;
 
 pub use addition::Addition;
 pub use and::And;
+pub use assert::Assert;
 pub use compare::Compare;
 pub use division::Division;
 pub use jump::{Jump, Relative};
@@ -13,6 +19,7 @@ pub use pop::Pop;
 pub use print_register::PrintRegister;
 pub use print_stack::PrintStack;
 pub use push::Push;
+pub use read::Read;
 pub use remainder::Remainder;
 pub use set::Set;
 pub use subtraction::Subtraction;
@@ -21,6 +28,7 @@ pub use xor::Xor;
 
 mod addition;
 mod and;
+mod assert;
 mod compare;
 mod division;
 mod jump;
@@ -31,19 +39,80 @@ mod pop;
 mod print_register;
 mod print_stack;
 mod push;
+mod read;
 mod remainder;
 mod set;
 mod subtraction;
 mod test;
 mod xor;
 
-#[derive(Debug, PartialEq, VariantCount)]
+/// Gets the register, the register of the operand if it is one, and the size in bytes
+/// of an instruction of a register and an operand.
+macro_rules! sized {
+    ($family:ident, $instruction:expr) => {
+        match $instruction {
+            $family::Byte(register, operand) => (*register, operand.register(), size_of::<Byte>()),
+            $family::Quarter(register, operand) => {
+                (*register, operand.register(), size_of::<Quarter>())
+            }
+            $family::Half(register, operand) => (*register, operand.register(), size_of::<Half>()),
+            $family::Word(register, operand) => (*register, operand.register(), size_of::<Word>()),
+        }
+    };
+}
+
+/// Gets the registers read by an instruction which modifies its register by its operand.
+macro_rules! modifying {
+    ($family:ident, $instruction:expr) => {{
+        let (register, operand, size) = sized!($family, $instruction);
+        (vec![Some(register), operand], Some(size))
+    }};
+}
+
+/// Gets the register written by an instruction of a register and an operand.
+macro_rules! written {
+    ($family:ident, $instruction:expr) => {{
+        let (register, _, size) = sized!($family, $instruction);
+        (register, size)
+    }};
+}
+
+/// Gets the registers read by an instruction of two operands.
+macro_rules! comparing {
+    ($family:ident, $instruction:expr) => {
+        match $instruction {
+            $family::Byte(a, b) => (vec![a.register(), b.register()], Some(size_of::<Byte>())),
+            $family::Quarter(a, b) => {
+                (vec![a.register(), b.register()], Some(size_of::<Quarter>()))
+            }
+            $family::Half(a, b) => (vec![a.register(), b.register()], Some(size_of::<Half>())),
+            $family::Word(a, b) => (vec![a.register(), b.register()], Some(size_of::<Word>())),
+        }
+    };
+}
+
+/// Gets the register and the size in bytes of an instruction of a single register.
+macro_rules! unary {
+    ($family:ident, $instruction:expr) => {
+        match $instruction {
+            $family::Byte(register) => (*register, size_of::<Byte>()),
+            $family::Quarter(register) => (*register, size_of::<Quarter>()),
+            $family::Half(register) => (*register, size_of::<Half>()),
+            $family::Word(register) => (*register, size_of::<Word>()),
+        }
+    };
+}
+
+#[derive(Clone, Debug, PartialEq, VariantCount)]
 pub enum Instruction {
     Addition(Addition),
     And(And),
+    Assert(Assert),
     Call(Operand<Word>),
     Compare(Compare),
     Division(Division),
+    /// Calls the host function at the index, as substituted for its name during preprocessing.
+    External(Word),
     Jump(Jump, Operand<Word>, Option<Relative>),
     Multiplication(Multiplication),
     Not(Not),
@@ -52,6 +121,7 @@ pub enum Instruction {
     PrintRegister(PrintRegister),
     PrintStack(PrintStack),
     Push(Push),
+    Read(Read),
     Remainder(Remainder),
     Return,
     Set(Set),
@@ -67,11 +137,348 @@ impl Instruction {
         use Instruction::*;
 
         match self {
-            Addition(_) | And(_) | Compare(_) | Division(_) | Multiplication(_) | Not(_)
-            | Or(_) | Pop(_) | PrintRegister(_) | PrintStack(_) | Push(_) | Remainder(_)
-            | Set(_) | Subtraction(_) | Test(_) | Xor(_) => true,
+            Addition(_) | And(_) | Assert(_) | Compare(_) | Division(_) | External(_)
+            | Multiplication(_) | Not(_) | Or(_) | Pop(_) | PrintRegister(_) | PrintStack(_)
+            | Push(_) | Read(_) | Remainder(_) | Set(_) | Subtraction(_) | Test(_) | Xor(_) => true,
 
             Call(_) | Jump(_, _, _) | Return | Stop => false,
         }
     }
+
+    /// Gets the registers read by the instruction, along with the size in bytes they are read at,
+    /// which is none for instructions without a size component.
+    ///
+    /// Host functions called by `ext` may read any register, which is not included.
+    pub fn reads(&self) -> Vec<(Register, Option<usize>)> {
+        let (registers, size) = match self {
+            Instruction::Addition(i) => modifying!(Addition, i),
+            Instruction::And(i) => modifying!(And, i),
+            Instruction::Division(i) => modifying!(Division, i),
+            Instruction::Multiplication(i) => modifying!(Multiplication, i),
+            Instruction::Or(i) => modifying!(Or, i),
+            Instruction::Remainder(i) => modifying!(Remainder, i),
+            Instruction::Subtraction(i) => modifying!(Subtraction, i),
+            Instruction::Xor(i) => modifying!(Xor, i),
+            Instruction::Set(i) => {
+                let (_, operand, size) = sized!(Set, i);
+                (vec![operand], Some(size))
+            }
+            Instruction::Compare(i) => comparing!(Compare, i),
+            Instruction::Test(i) => comparing!(Test, i),
+            Instruction::Assert(i) => match i {
+                Assert::Byte(a, b) => (vec![a.register(), b.register()], Some(size_of::<Byte>())),
+                Assert::Quarter(a, b) => {
+                    (vec![a.register(), b.register()], Some(size_of::<Quarter>()))
+                }
+                Assert::Half(a, b) => (vec![a.register(), b.register()], Some(size_of::<Half>())),
+                Assert::Word(a, b) => (vec![a.register(), b.register()], Some(size_of::<Word>())),
+                Assert::Flag(_, _) => (vec![], None),
+                Assert::StackDepth(depth) => (vec![depth.register()], None),
+            },
+            Instruction::Not(i) => {
+                let (register, size) = unary!(Not, i);
+                (vec![Some(register)], Some(size))
+            }
+            Instruction::PrintRegister(i) => {
+                let (register, size) = unary!(PrintRegister, i);
+                (vec![Some(register)], Some(size))
+            }
+            Instruction::Push(i) => match i {
+                Push::Byte(operand) => (vec![operand.register()], Some(size_of::<Byte>())),
+                Push::Quarter(operand) => (vec![operand.register()], Some(size_of::<Quarter>())),
+                Push::Half(operand) => (vec![operand.register()], Some(size_of::<Half>())),
+                Push::Word(operand) => (vec![operand.register()], Some(size_of::<Word>())),
+            },
+            Instruction::PrintStack(
+                PrintStack::Byte(amount)
+                | PrintStack::Quarter(amount)
+                | PrintStack::Half(amount)
+                | PrintStack::Word(amount)
+                | PrintStack::Str(amount),
+            ) => (vec![amount.register()], None),
+            Instruction::Call(destination) | Instruction::Jump(_, destination, _) => {
+                (vec![destination.register()], None)
+            }
+            Instruction::External(_)
+            | Instruction::Pop(_)
+            | Instruction::Read(_)
+            | Instruction::Return
+            | Instruction::Stop => (vec![], None),
+        };
+
+        registers
+            .into_iter()
+            .flatten()
+            .map(|register| (register, size))
+            .collect()
+    }
+
+    /// Gets the register written by the instruction, along with the size in bytes it is written at,
+    /// where writing at a size clears the bytes above it.
+    ///
+    /// Host functions called by `ext` may write any register, which is not included.
+    pub fn writes(&self) -> Option<(Register, usize)> {
+        let (register, size) = match self {
+            Instruction::Addition(i) => written!(Addition, i),
+            Instruction::And(i) => written!(And, i),
+            Instruction::Division(i) => written!(Division, i),
+            Instruction::Multiplication(i) => written!(Multiplication, i),
+            Instruction::Or(i) => written!(Or, i),
+            Instruction::Remainder(i) => written!(Remainder, i),
+            Instruction::Subtraction(i) => written!(Subtraction, i),
+            Instruction::Xor(i) => written!(Xor, i),
+            Instruction::Set(i) => written!(Set, i),
+            Instruction::Not(i) => unary!(Not, i),
+            Instruction::Pop(i) => unary!(Pop, i),
+            Instruction::Read(i) => match i {
+                Read::UnsignedByte(r) | Read::SignedByte(r) | Read::Character(r) => {
+                    (*r, size_of::<Byte>())
+                }
+                Read::UnsignedQuarter(r) | Read::SignedQuarter(r) => (*r, size_of::<Quarter>()),
+                Read::UnsignedHalf(r) | Read::SignedHalf(r) => (*r, size_of::<Half>()),
+                Read::UnsignedWord(r) | Read::SignedWord(r) | Read::Line(r) => {
+                    (*r, size_of::<Word>())
+                }
+            },
+            _ => return None,
+        };
+
+        Some((register, size))
+    }
+}
+
+/// A program using every instruction of the instruction set, except for `ext` as no host functions are available.
+#[cfg(test)]
+pub const EVERY_INSTRUCTION: &str = "\
+// every instruction
+addb ra 1
+addq rb rc
+addh rc 3
+addw rd 18446744073709551615
+andb ra 1
+andq ra rb
+andh ra 3
+andw ra 4
+aeqb ra 1
+aeqq 2 rb
+aeqh ra 3
+aeqw 4 4
+afc of
+afs zf
+asd 8
+call set_ra
+cmpb ra 1
+cmpq rb 2
+cmph rc 3
+cmpw rd 4
+divb ra 1
+divq ra 2
+divh ra 3
+divw ra 4
+jmp 30
+jiz +2
+jnz -1
+jis ra
+jns 5
+jio +rb
+jno -rc
+jig 6
+jil 7
+jge 8
+jle 9
+mulb ra 1
+mulq ra 2
+mulh ra 3
+mulw ra 4
+notb ra
+notq rb
+noth rc
+notw rd
+orb ra 1
+orq ra 2
+orh ra 3
+orw ra 4
+popb ra
+popq rb
+poph rc
+popw rd
+prrb ra
+prrq rb
+prrh rc
+prrw rd
+prsb 1
+prsq rb
+prsh 3
+prsw 4
+prss re
+pshb 255
+pshq rf
+pshh 65536
+pshw rg
+rdc ra
+rdl rb
+rdsb rc
+rdsq rd
+rdsh re
+rdsw rf
+rdub rg
+rduq rh
+rduh ra
+rduw rb
+remb ra 1
+remq ra 2
+remh ra 3
+remw ra 4
+setb ra 1
+setq ra 2
+seth ra 3
+setw ra 4
+subb ra 1
+subq ra 2
+subh ra 3
+subw ra 4
+tstb ra 1
+tstq ra 2
+tsth ra 3
+tstw ra 4
+xorb ra 1
+xorq ra 2
+xorh ra 3
+xorw ra 4
+stop
+
+fn set_ra:
+  setb ra 1
+  ret";
+
+/// Prints the instruction as canonical source code, which decodes to the same instruction.
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Instruction::*;
+
+        match self {
+            Addition(i) => write!(f, "{i}"),
+            And(i) => write!(f, "{i}"),
+            Assert(i) => write!(f, "{i}"),
+            Call(destination) => write!(f, "call {destination}"),
+            Compare(i) => write!(f, "{i}"),
+            Division(i) => write!(f, "{i}"),
+            External(index) => write!(f, "ext {index}"),
+            Jump(jump, destination, relative) => match relative {
+                Some(relative) => write!(f, "{jump} {relative}{destination}"),
+                None => write!(f, "{jump} {destination}"),
+            },
+            Multiplication(i) => write!(f, "{i}"),
+            Not(i) => write!(f, "{i}"),
+            Or(i) => write!(f, "{i}"),
+            Pop(i) => write!(f, "{i}"),
+            PrintRegister(i) => write!(f, "{i}"),
+            PrintStack(i) => write!(f, "{i}"),
+            Push(i) => write!(f, "{i}"),
+            Read(i) => write!(f, "{i}"),
+            Remainder(i) => write!(f, "{i}"),
+            Return => write!(f, "ret"),
+            Set(i) => write!(f, "{i}"),
+            Stop => write!(f, "stop"),
+            Subtraction(i) => write!(f, "{i}"),
+            Test(i) => write!(f, "{i}"),
+            Xor(i) => write!(f, "{i}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod display {
+    use super::EVERY_INSTRUCTION;
+    use crate::{
+        constant::COMMENT,
+        decode::{decode, DECODE_TABLE},
+    };
+
+    #[test]
+    fn program_uses_every_instruction() {
+        for mnemonic in DECODE_TABLE.keys().filter(|mnemonic| **mnemonic != "ext") {
+            assert!(
+                EVERY_INSTRUCTION
+                    .lines()
+                    .any(|line| line.split_ascii_whitespace().next() == Some(mnemonic)),
+                "'{mnemonic}' is not used"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_every_instruction() {
+        let lines = EVERY_INSTRUCTION
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(COMMENT));
+
+        for line in lines {
+            let Ok(expected) = decode(line) else {
+                // function definitions and calls by name only decode after preprocessing
+                continue;
+            };
+
+            let printed = expected.to_string();
+            let actual = decode(&printed);
+
+            assert_eq!(printed, line);
+            assert_eq!(actual, Ok(expected));
+        }
+    }
+
+    #[test]
+    fn external() {
+        let printed = decode("ext 3").unwrap().to_string();
+
+        assert_eq!(printed, "ext 3");
+    }
+}
+
+#[cfg(test)]
+mod accesses {
+    use crate::{decode::decode, register::Register};
+
+    #[test]
+    fn modifying_reads_and_writes_register() {
+        let instruction = decode("addq ra rb").unwrap();
+        let expected_reads = vec![(Register::A, Some(2)), (Register::B, Some(2))];
+        let expected_writes = Some((Register::A, 2));
+
+        let actual_reads = instruction.reads();
+        let actual_writes = instruction.writes();
+
+        assert_eq!(actual_reads, expected_reads);
+        assert_eq!(actual_writes, expected_writes);
+    }
+
+    #[test]
+    fn set_only_reads_operand() {
+        let instruction = decode("seth ra 3").unwrap();
+
+        let actual_reads = instruction.reads();
+        let actual_writes = instruction.writes();
+
+        assert_eq!(actual_reads, vec![]);
+        assert_eq!(actual_writes, Some((Register::A, 4)));
+    }
+
+    #[test]
+    fn unsized_reads() {
+        let expected = vec![(Register::C, None)];
+
+        let actual = decode("jio +rc").unwrap().reads();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn read_line_writes_word() {
+        let expected = Some((Register::D, 8));
+
+        let actual = decode("rdl rd").unwrap().writes();
+
+        assert_eq!(actual, expected);
+    }
 }
@@ -1,9 +1,21 @@
 use crate::register::Register;
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Not {
     Byte(Register),
     Quarter(Register),
     Half(Register),
     Word(Register),
 }
+
+impl Display for Not {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Not::Byte(register) => write!(f, "notb {register}"),
+            Not::Quarter(register) => write!(f, "notq {register}"),
+            Not::Half(register) => write!(f, "noth {register}"),
+            Not::Word(register) => write!(f, "notw {register}"),
+        }
+    }
+}
@@ -3,11 +3,23 @@ use crate::{
     operand::Operand,
     register::Register,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Or {
     Byte(Register, Operand<Byte>),
     Quarter(Register, Operand<Quarter>),
     Half(Register, Operand<Half>),
     Word(Register, Operand<Word>),
 }
+
+impl Display for Or {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Or::Byte(register, operand) => write!(f, "orb {register} {operand}"),
+            Or::Quarter(register, operand) => write!(f, "orq {register} {operand}"),
+            Or::Half(register, operand) => write!(f, "orh {register} {operand}"),
+            Or::Word(register, operand) => write!(f, "orw {register} {operand}"),
+        }
+    }
+}
@@ -1,9 +1,36 @@
-use crate::register::Register;
+use crate::{
+    constant::{Byte, Half, Quarter, Word},
+    register::Register,
+};
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Pop {
     Byte(Register),
     Quarter(Register),
     Half(Register),
     Word(Register),
 }
+
+impl Pop {
+    /// Gets the size in bytes of the value.
+    pub fn size(&self) -> usize {
+        match self {
+            Pop::Byte(_) => size_of::<Byte>(),
+            Pop::Quarter(_) => size_of::<Quarter>(),
+            Pop::Half(_) => size_of::<Half>(),
+            Pop::Word(_) => size_of::<Word>(),
+        }
+    }
+}
+
+impl Display for Pop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pop::Byte(register) => write!(f, "popb {register}"),
+            Pop::Quarter(register) => write!(f, "popq {register}"),
+            Pop::Half(register) => write!(f, "poph {register}"),
+            Pop::Word(register) => write!(f, "popw {register}"),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{constant::Word, operand::Operand};
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PrintStack {
     Byte(Operand<Word>),
     Quarter(Operand<Word>),
@@ -8,3 +9,15 @@ pub enum PrintStack {
     Word(Operand<Word>),
     Str(Operand<Word>),
 }
+
+impl Display for PrintStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrintStack::Byte(operand) => write!(f, "prsb {operand}"),
+            PrintStack::Quarter(operand) => write!(f, "prsq {operand}"),
+            PrintStack::Half(operand) => write!(f, "prsh {operand}"),
+            PrintStack::Word(operand) => write!(f, "prsw {operand}"),
+            PrintStack::Str(operand) => write!(f, "prss {operand}"),
+        }
+    }
+}
@@ -2,11 +2,35 @@ use crate::{
     constant::{Byte, Half, Quarter, Word},
     operand::Operand,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Push {
     Byte(Operand<Byte>),
     Quarter(Operand<Quarter>),
     Half(Operand<Half>),
     Word(Operand<Word>),
 }
+
+impl Push {
+    /// Gets the size in bytes of the value.
+    pub fn size(&self) -> usize {
+        match self {
+            Push::Byte(_) => size_of::<Byte>(),
+            Push::Quarter(_) => size_of::<Quarter>(),
+            Push::Half(_) => size_of::<Half>(),
+            Push::Word(_) => size_of::<Word>(),
+        }
+    }
+}
+
+impl Display for Push {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Push::Byte(operand) => write!(f, "pshb {operand}"),
+            Push::Quarter(operand) => write!(f, "pshq {operand}"),
+            Push::Half(operand) => write!(f, "pshh {operand}"),
+            Push::Word(operand) => write!(f, "pshw {operand}"),
+        }
+    }
+}
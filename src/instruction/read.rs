@@ -0,0 +1,35 @@
+use crate::register::Register;
+use std::fmt::Display;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Read {
+    UnsignedByte(Register),
+    UnsignedQuarter(Register),
+    UnsignedHalf(Register),
+    UnsignedWord(Register),
+    SignedByte(Register),
+    SignedQuarter(Register),
+    SignedHalf(Register),
+    SignedWord(Register),
+    /// Reads a single byte of input.
+    Character(Register),
+    /// Reads a line of input onto the stack, and its length in bytes into the register.
+    Line(Register),
+}
+
+impl Display for Read {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Read::UnsignedByte(register) => write!(f, "rdub {register}"),
+            Read::UnsignedQuarter(register) => write!(f, "rduq {register}"),
+            Read::UnsignedHalf(register) => write!(f, "rduh {register}"),
+            Read::UnsignedWord(register) => write!(f, "rduw {register}"),
+            Read::SignedByte(register) => write!(f, "rdsb {register}"),
+            Read::SignedQuarter(register) => write!(f, "rdsq {register}"),
+            Read::SignedHalf(register) => write!(f, "rdsh {register}"),
+            Read::SignedWord(register) => write!(f, "rdsw {register}"),
+            Read::Character(register) => write!(f, "rdc {register}"),
+            Read::Line(register) => write!(f, "rdl {register}"),
+        }
+    }
+}
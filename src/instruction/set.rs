@@ -3,11 +3,23 @@ use crate::{
     operand::Operand,
     register::Register,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Set {
     Byte(Register, Operand<Byte>),
     Quarter(Register, Operand<Quarter>),
     Half(Register, Operand<Half>),
     Word(Register, Operand<Word>),
 }
+
+impl Display for Set {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Set::Byte(register, operand) => write!(f, "setb {register} {operand}"),
+            Set::Quarter(register, operand) => write!(f, "setq {register} {operand}"),
+            Set::Half(register, operand) => write!(f, "seth {register} {operand}"),
+            Set::Word(register, operand) => write!(f, "setw {register} {operand}"),
+        }
+    }
+}
@@ -2,11 +2,23 @@ use crate::{
     constant::{Byte, Half, Quarter, Word},
     operand::Operand,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Test {
     Byte(Operand<Byte>, Operand<Byte>),
     Quarter(Operand<Quarter>, Operand<Quarter>),
     Half(Operand<Half>, Operand<Half>),
     Word(Operand<Word>, Operand<Word>),
 }
+
+impl Display for Test {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Test::Byte(first, second) => write!(f, "tstb {first} {second}"),
+            Test::Quarter(first, second) => write!(f, "tstq {first} {second}"),
+            Test::Half(first, second) => write!(f, "tsth {first} {second}"),
+            Test::Word(first, second) => write!(f, "tstw {first} {second}"),
+        }
+    }
+}
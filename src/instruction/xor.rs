@@ -3,11 +3,23 @@ use crate::{
     operand::Operand,
     register::Register,
 };
+use std::fmt::Display;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Xor {
     Byte(Register, Operand<Byte>),
     Quarter(Register, Operand<Quarter>),
     Half(Register, Operand<Half>),
     Word(Register, Operand<Word>),
 }
+
+impl Display for Xor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Xor::Byte(register, operand) => write!(f, "xorb {register} {operand}"),
+            Xor::Quarter(register, operand) => write!(f, "xorq {register} {operand}"),
+            Xor::Half(register, operand) => write!(f, "xorh {register} {operand}"),
+            Xor::Word(register, operand) => write!(f, "xorw {register} {operand}"),
+        }
+    }
+}
@@ -1,40 +1,91 @@
+pub use builder::InterpreterBuilder;
+use bytecode::Assembled;
+pub use bytecode::{assemble, disassemble, is_assembled};
+pub use call_stack::Backtrace;
+use call_stack::{CallStack, Functions};
+pub use cfg::{export_graphs, Block, Call, ControlFlowGraph, Edge, EdgeKind};
 use cli::parse_stack_size;
-pub use cli::Arguments;
 use cli::Configuration;
-use constant::{Word, COMMENT, DEBUG_HELP, DEBUG_INITIAL};
+pub use cli::{
+    read_program, read_program_file, Arguments, AssembleArguments, Cli, Command,
+    DisassembleArguments, FormatArguments, GraphArguments, LimitArguments, LintArguments,
+    ProgramFile, TestArguments,
+};
+use constant::{Byte, Word, TIME_LIMIT_CHECK_INTERVAL};
 pub use error::ArgumentError;
 pub use error::InterpreterError;
-use flags::Flags;
+pub use flags::{Flag, Flags};
+pub use format::{format, format_files};
+pub use golden::verify_output;
+pub use host::{Host, HostFunction};
 use instruction::Instruction;
+pub use lint::{lint, lint_files, Lint, Warning};
+pub use lsp::language_server;
 use preprocess::expand_data_section;
 use preprocess::expand_function_calls;
 use program::Program;
-use register::Register;
-use registers::Registers;
+pub use register::Register;
+use registers::{RegisterOperations, Registers};
+pub use snapshot::Snapshot;
 use stack::Stack;
-use std::io::stdin;
-
+pub use stack_analysis::{analyse_stack, stack_usage, FunctionStack, StackAnalysis};
+use std::time::Instant;
+pub use test_runner::run_tests;
+pub use utils::{Reader, Writer};
+
+mod builder;
+mod bytecode;
+mod call_stack;
+mod cfg;
 mod cli;
 mod constant;
+mod debugger;
 mod decode;
+mod dump;
 mod error;
 mod execute;
 mod flags;
+mod format;
+mod golden;
+mod hook;
+mod host;
 mod instruction;
+mod lint;
+mod lsp;
 mod operand;
 mod preprocess;
 mod program;
 mod register;
 mod registers;
+mod size_analysis;
+mod snapshot;
 mod stack;
+mod stack_analysis;
+mod test_runner;
+mod uninitialised_analysis;
 mod utils;
 
+/// Expands the data section and function calls of the source code,
+/// where the externals are the names of the host functions available to the program.
+fn preprocess(source_code: &str, externals: &[&str]) -> Result<Program, InterpreterError> {
+    let data_expanded_source_code =
+        expand_data_section(source_code).map_err(InterpreterError::PreProcess)?;
+    let final_source_code = expand_function_calls(data_expanded_source_code, externals)
+        .map_err(InterpreterError::PreProcess)?;
+
+    Ok(Program::new(final_source_code))
+}
+
 pub struct Interpreter {
     registers: Registers,
     program_counter: Word,
     flags: Flags,
     running: bool,
     stack: Stack,
+    call_stack: CallStack,
+    functions: Functions,
+    /// The instruction which failed to execute, kept to describe the error.
+    failed_instruction: Option<Instruction>,
     config: Configuration,
 }
 
@@ -43,29 +94,34 @@ impl Interpreter {
         let stack_size = parse_stack_size(&args.stack_size).map_err(InterpreterError::Argument)?;
         let config = Configuration::try_from(args)?;
 
-        let p = Self {
+        Ok(Self::with_config(stack_size, config))
+    }
+
+    /// Creates a builder for configuring an interpreter without command line arguments.
+    #[must_use]
+    pub fn builder() -> InterpreterBuilder {
+        InterpreterBuilder::new()
+    }
+
+    fn with_config(stack_size: usize, config: Configuration) -> Self {
+        Self {
             registers: [0; Register::VARIANT_COUNT],
             program_counter: 1,
             flags: Flags::new(),
             running: true,
             stack: Stack::new(stack_size),
+            call_stack: CallStack::new(),
+            functions: Functions::new(),
+            failed_instruction: None,
             config,
-        };
-        Ok(p)
+        }
     }
 
     #[cfg(test)]
     pub fn new_test() -> Self {
         use constant::TEST_STACK_SIZE;
 
-        Self {
-            registers: [0; Register::VARIANT_COUNT],
-            program_counter: 1,
-            flags: Flags::new(),
-            running: true,
-            stack: Stack::new(TEST_STACK_SIZE),
-            config: Configuration::new_test(),
-        }
+        Self::with_config(TEST_STACK_SIZE, Configuration::new_test())
     }
 
     /// Gets the program counter as a usize.
@@ -74,69 +130,163 @@ impl Interpreter {
     }
 
     pub fn run(&mut self, source_code: &str) -> Result<(), InterpreterError> {
-        let data_expanded_source_code =
-            expand_data_section(source_code).map_err(InterpreterError::PreProcess)?;
-        let final_source_code = expand_function_calls(data_expanded_source_code)
-            .map_err(InterpreterError::PreProcess)?;
-        let program = Program::new(final_source_code);
-
-        if self.config.debug {
-            self.debug(program)?;
-        } else {
-            self.full(program)?;
-        }
+        let program = preprocess(source_code, &self.config.host_functions.names())?;
 
-        Ok(())
+        self.run_program(source_code, &program)
     }
 
-    fn advance(&mut self, program: &Program) -> Result<(), InterpreterError> {
-        let line = program.get(self.pc())?;
+    /// Runs a program assembled with `kasm assemble`, without preprocessing or decoding its source code.
+    ///
+    /// # Errors
+    /// Besides the errors of [`Interpreter::run`], an error is returned if the assembled file is corrupt,
+    /// or if it was assembled for another version of the format.
+    pub fn run_assembled(&mut self, bytes: &[Byte]) -> Result<(), InterpreterError> {
+        let assembled = Assembled::from_bytes(bytes)?;
+        let source_code = assembled.source();
 
-        if line.starts_with(COMMENT) {
-            self.program_counter += 1;
-            return Ok(());
+        self.run_program(&source_code, &assembled.into_program())
+    }
+
+    /// Runs the program, where the source code is only used by hooks and snapshots.
+    fn run_program(
+        &mut self,
+        source_code: &str,
+        program: &Program,
+    ) -> Result<(), InterpreterError> {
+        if let Some(snapshot) = self.config.restore.take() {
+            self.restore(&snapshot, source_code)?;
         }
 
-        let instruction =
-            decode::decode(line).map_err(|e| InterpreterError::Decode(self.pc(), e))?;
+        self.functions = program.functions();
+        self.hooks_start(source_code, program);
 
-        self.execute(instruction)
-            .map_err(|e| InterpreterError::Execute(self.pc(), e))?;
+        let result = if self.config.debug {
+            self.debug(program)
+        } else if self.config.tui {
+            self.tui(program)
+        } else {
+            self.full(program)
+        };
 
-        Ok(())
+        // hooks are finished even after an error, as their reports are most useful then,
+        // but the error of the program takes precedence over failing to write a report
+        let finished = self.hooks_finish();
+        result?;
+
+        finished.map_err(|err| InterpreterError::FailedReport(err.to_string()))
     }
 
-    fn full(&mut self, program: Program) -> Result<(), InterpreterError> {
-        while self.running {
-            self.advance(&program)?;
+    /// Gets everything written to the output, if it is being captured.
+    #[must_use]
+    pub fn captured_output(&self) -> Option<String> {
+        self.config
+            .captured_output
+            .as_ref()
+            .map(|buffer| String::from_utf8_lossy(&buffer.borrow()).into_owned())
+    }
+
+    /// Gets the value of the register.
+    #[must_use]
+    pub fn register(&self, register: Register) -> Word {
+        self.registers.get::<Word>(register)
+    }
+
+    /// Gets the values of every register, in the order they are declared in [`Register`].
+    #[must_use]
+    pub fn registers(&self) -> &[Word] {
+        &self.registers
+    }
+
+    #[must_use]
+    pub fn flags(&self) -> &Flags {
+        &self.flags
+    }
+
+    /// Gets the bytes on the stack, from the bottom to the top.
+    #[must_use]
+    pub fn stack(&self) -> &[Byte] {
+        self.stack
+            .bytes()
+            .get(..self.stack.sp())
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn instructions_executed(&self) -> u64 {
+        self.config.instructions_executed
+    }
+
+    /// Gets the line of the next instruction, or of the last one if the program has stopped.
+    #[must_use]
+    pub fn program_counter(&self) -> usize {
+        self.pc()
+    }
+
+    /// Gets the chain of function calls leading to the current line.
+    ///
+    /// This is most useful after [`Interpreter::run`] returned an execute error,
+    /// as it then describes how the failing line was reached.
+    #[must_use]
+    pub fn backtrace(&self) -> Backtrace {
+        self.call_stack.backtrace(self.pc(), &self.functions)
+    }
+
+    /// Gets the instruction which failed to execute, printed as source code.
+    ///
+    /// This is only present after [`Interpreter::run`] returned an execute error.
+    #[must_use]
+    pub fn failed_instruction(&self) -> Option<String> {
+        self.failed_instruction.as_ref().map(Instruction::to_string)
+    }
+
+    fn advance(&mut self, program: &Program) -> Result<(), InterpreterError> {
+        let Some(instruction) = program.instruction(self.pc())? else {
+            self.program_counter += 1;
+            return Ok(());
+        };
+
+        // kept for reporting, as a failing hook may have already moved the program counter past the instruction
+        let failed_instruction = instruction.clone();
+        if let Err(e) = self.execute(instruction) {
+            self.failed_instruction = Some(failed_instruction);
+            return Err(InterpreterError::Execute(self.pc(), e));
         }
 
         Ok(())
     }
 
-    fn debug(&mut self, program: Program) -> Result<(), InterpreterError> {
-        println!("{DEBUG_INITIAL}");
+    /// Returns an error if the instruction limit is reached, or if the time limit is exceeded since the start.
+    fn check_limits(&self, start: Instant) -> Result<(), InterpreterError> {
+        let executed = self.config.instructions_executed;
+        if self
+            .config
+            .instruction_limit
+            .is_some_and(|limit| executed >= limit)
+        {
+            return Err(InterpreterError::InstructionLimitExceeded(
+                self.pc(),
+                executed,
+            ));
+        }
 
-        while self.running {
-            let mut action = String::new();
-            stdin().read_line(&mut action).unwrap();
-
-            match action.trim() {
-                "help" | "h" => {
-                    println!("{DEBUG_HELP}");
-                }
-                "next" | "n" => {
-                    self.advance(&program)?;
-                }
-                "stop" | "s" => {
-                    self.execute(Instruction::Stop)
-                        .map_err(|err| InterpreterError::Execute(self.pc(), err))?;
-                    break;
-                }
-                unknown => println!("unknown action: '{unknown}'"),
+        if let Some(limit) = self.config.time_limit {
+            if executed.is_multiple_of(TIME_LIMIT_CHECK_INTERVAL) && start.elapsed() >= limit {
+                return Err(InterpreterError::TimeLimitExceeded(
+                    self.pc(),
+                    executed,
+                    limit,
+                ));
             }
+        }
 
-            println!("pc: {}, sp: {}", self.pc(), self.stack.sp());
+        Ok(())
+    }
+
+    fn full(&mut self, program: &Program) -> Result<(), InterpreterError> {
+        let start = Instant::now();
+        while self.running {
+            self.check_limits(start)?;
+            self.advance(program)?;
         }
 
         Ok(())
@@ -146,10 +296,14 @@ impl Interpreter {
 #[cfg(test)]
 mod integration {
     use crate::{
+        bytecode::Assembled,
         constant::{Byte, Word, COMMENT},
         error::{DecodeError, ExecuteError, InterpreterError},
+        hook::{Hook, Machine},
+        instruction::Instruction,
         register::Register,
         registers::RegisterOperations,
+        utils::Writer,
         Interpreter,
     };
 
@@ -280,6 +434,28 @@ mod integration {
         Ok(())
     }
 
+    #[test]
+    fn indented_comment_has_no_effect() -> Result<(), InterpreterError> {
+        let mut i = Interpreter::new_test();
+        let program = [
+            "call set_rb",
+            "stop",
+            "fn set_rb:",
+            &format!("  {COMMENT} sets rb to 5"),
+            "  setb rb 5",
+            "  ret",
+        ]
+        .join("\n");
+        let expected = 5;
+
+        i.run(&program)?;
+        let actual = i.registers.get::<Byte>(Register::B);
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
     #[test]
     fn decode_error_on_expected_line() {
         let mut i = Interpreter::new_test();
@@ -305,5 +481,168 @@ mod integration {
         assert_eq!(actual, expected);
     }
 
-    // something to do with calling functions
+    #[test]
+    fn backtrace_of_execute_error_in_nested_function() {
+        let mut i = Interpreter::new_test();
+        let program = [
+            "call outer",
+            "stop",
+            "fn outer:",
+            "  call inner",
+            "  ret",
+            "fn inner:",
+            "  divb ra 0",
+            "  ret",
+        ]
+        .join("\n");
+        let expected_error = Err(InterpreterError::Execute(7, ExecuteError::DivideByZero));
+        let expected_backtrace = [
+            "backtrace (most recent call first):",
+            "  0: inner at line 7",
+            "  1: outer at line 4",
+            "  2: <top level> at line 1",
+        ]
+        .join("\n");
+
+        let actual_error = i.run(&program);
+        let actual_backtrace = i.backtrace().to_string();
+
+        assert_eq!(actual_error, expected_error);
+        assert_eq!(actual_backtrace, expected_backtrace);
+    }
+
+    #[test]
+    fn checked_function_leaving_bytes_on_stack() {
+        let mut i = Interpreter::new_test();
+        i.config.checked = true;
+        let program = ["call push_ra", "stop", "fn push_ra:", "  pshb ra", "  ret"].join("\n");
+        let expected = Err(InterpreterError::Execute(
+            5,
+            ExecuteError::BytesLeftOnStack(String::from("push_ra"), 1),
+        ));
+
+        let actual = i.run(&program);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn backtrace_after_return() {
+        let mut i = Interpreter::new_test();
+        let program = [
+            "call inc_ra",
+            "popb rb",
+            "stop",
+            "fn inc_ra:",
+            "  addb ra 1",
+            "  ret",
+        ]
+        .join("\n");
+        let expected_error = Err(InterpreterError::Execute(2, ExecuteError::StackUnderflow));
+        let expected_backtrace = [
+            "backtrace (most recent call first):",
+            "  0: <top level> at line 2",
+        ]
+        .join("\n");
+
+        let actual_error = i.run(&program);
+        let actual_backtrace = i.backtrace().to_string();
+
+        assert_eq!(actual_error, expected_error);
+        assert_eq!(actual_backtrace, expected_backtrace);
+    }
+
+    #[test]
+    fn assembled_matches_source() -> Result<(), InterpreterError> {
+        let program = [
+            "setb ra 5",
+            "call double_ra",
+            "subb ra 1",
+            "jnz -1",
+            "stop",
+            "fn double_ra:",
+            "  mulb ra 2",
+            "  ret",
+        ]
+        .join("\n");
+        let bytes = Assembled::assemble(&program, false)?.to_bytes();
+        let mut expected = Interpreter::new_test();
+        let mut actual = Interpreter::new_test();
+
+        expected.run(&program)?;
+        actual.run_assembled(&bytes)?;
+
+        assert_eq!(actual.registers(), expected.registers());
+        assert_eq!(
+            actual.instructions_executed(),
+            expected.instructions_executed()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn assembled_decode_error_on_expected_line() -> Result<(), InterpreterError> {
+        let bytes = Assembled::assemble("setb ra 5\n\nstop", false)?.to_bytes();
+        let mut i = Interpreter::new_test();
+        let expected = Err(InterpreterError::Decode(2, DecodeError::EmptyLine));
+
+        let actual = i.run_assembled(&bytes);
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    /// A hook that fails to write its report.
+    struct FailingReport;
+
+    impl Hook for FailingReport {
+        fn before(&mut self, _machine: &Machine<'_>, _instruction: &Instruction) {}
+
+        fn finish(&mut self, _report: &mut dyn Writer) -> std::io::Result<()> {
+            Err(std::io::Error::other("broken report"))
+        }
+    }
+
+    #[test]
+    fn execute_error_before_failed_report() {
+        let mut i = Interpreter::new_test();
+        i.config.hooks.push(Box::new(FailingReport));
+
+        let actual = i.run("setb rb 0\ndivb ra rb\nstop");
+
+        assert_eq!(
+            actual,
+            Err(InterpreterError::Execute(2, ExecuteError::DivideByZero))
+        );
+    }
+
+    #[test]
+    fn failed_report_after_success() {
+        let mut i = Interpreter::new_test();
+        i.config.hooks.push(Box::new(FailingReport));
+
+        let actual = i.run("stop");
+
+        assert_eq!(
+            actual,
+            Err(InterpreterError::FailedReport(String::from(
+                "broken report"
+            )))
+        );
+    }
+
+    #[test]
+    fn failed_instruction_printed_as_source() {
+        let mut i = Interpreter::new_test();
+        let program = "setb rb 0\ndivb   ra  rb // by zero\nstop";
+
+        let actual_error = i.run(program);
+        let actual_instruction = i.failed_instruction();
+
+        assert_eq!(
+            actual_error,
+            Err(InterpreterError::Execute(2, ExecuteError::DivideByZero))
+        );
+        assert_eq!(actual_instruction, Some(String::from("divb ra rb")));
+    }
 }
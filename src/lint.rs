@@ -0,0 +1,469 @@
+//! Warnings for common mistakes in programs, found without running them.
+//!
+//! Every lint has an identifier, and a warning is suppressed by a comment of the form
+//! `// lint: allow(<identifier>, ...)`, either at the end of the line or on the line above it.
+
+use crate::{
+    cfg::{is_terminator, jump_target, ControlFlowGraph},
+    cli::{read_program_file, LintArguments},
+    constant::COMMENT,
+    error::InterpreterError,
+    format::split_comment,
+    instruction::Instruction,
+    operand::Operand,
+    preprocess,
+    preprocess::DATA_SECTION,
+    size_analysis::analyse_sizes,
+    stack_analysis::analyse_stack,
+    test_runner::TEST_PREFIX,
+    uninitialised_analysis::analyse_uninitialised,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+};
+
+const ALLOW_PREFIX: &str = "lint: allow(";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lint {
+    UnusedDataKey,
+    UnusedFunction,
+    UnreachableCode,
+    MissingStop,
+    FallThroughIntoFunction,
+    JumpIntoFunction,
+    ReturnOutsideFunction,
+    StackUnderflow,
+    UnbalancedReturn,
+    RegisterSizeMismatch,
+    StackSizeMismatch,
+    UninitialisedRegister,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Lint::UnusedDataKey => "unused-data-key",
+            Lint::UnusedFunction => "unused-function",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::MissingStop => "missing-stop",
+            Lint::FallThroughIntoFunction => "fall-through-into-function",
+            Lint::JumpIntoFunction => "jump-into-function",
+            Lint::ReturnOutsideFunction => "ret-outside-function",
+            Lint::StackUnderflow => "stack-underflow",
+            Lint::UnbalancedReturn => "unbalanced-return",
+            Lint::RegisterSizeMismatch => "register-size-mismatch",
+            Lint::StackSizeMismatch => "stack-size-mismatch",
+            Lint::UninitialisedRegister => "uninitialised-register",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+/// A lint found on a line of the program.
+#[derive(Debug, PartialEq)]
+pub struct Warning {
+    pub lint: Lint,
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "warning[{}]: line {}: {}",
+            self.lint, self.line, self.message
+        )
+    }
+}
+
+/// Gets the lines targeted by jumps, or none if any jump is computed at runtime.
+fn jump_targets(cfg: &ControlFlowGraph) -> Option<HashSet<usize>> {
+    let mut targets = HashSet::new();
+    for (line, instruction) in cfg.instructions() {
+        if let Instruction::Jump(_, destination, relative) = instruction {
+            targets.insert(jump_target(line, destination, relative.as_ref())?);
+        }
+    }
+
+    Some(targets)
+}
+
+/// Gets the lines that are reachable from the line before them.
+///
+/// Every line is considered reachable if any jump is computed at runtime.
+fn reachable(cfg: &ControlFlowGraph) -> Vec<bool> {
+    let Some(targets) = jump_targets(cfg) else {
+        return vec![true; cfg.line_count()];
+    };
+
+    let mut reachable = true;
+    (1..=cfg.line_count())
+        .map(|line| {
+            if cfg.functions().contains_key(&line) || targets.contains(&line) {
+                reachable = true;
+            }
+            let current = reachable;
+            if cfg.instruction(line).is_some_and(is_terminator) {
+                reachable = false;
+            }
+            current
+        })
+        .collect()
+}
+
+fn unused_data_keys(source_code: &str, warnings: &mut Vec<Warning>) {
+    let lines: Vec<&str> = source_code.lines().collect();
+    let Some(start) = lines
+        .iter()
+        .rposition(|line| line.trim_start().starts_with(DATA_SECTION))
+    else {
+        return;
+    };
+
+    // the sign of a relative jump precedes a key used as its offset
+    let used: HashSet<&str> = lines
+        .iter()
+        .take(start)
+        .flat_map(|line| split_comment(line).0.split_ascii_whitespace())
+        .map(|token| token.strip_prefix(['+', '-']).unwrap_or(token))
+        .collect();
+
+    for (line, text) in (1..).zip(&lines).skip(start + 1) {
+        let key = text.split_ascii_whitespace().next();
+        if let Some(key) = key.filter(|key| !key.starts_with(COMMENT) && !used.contains(key)) {
+            warnings.push(Warning {
+                lint: Lint::UnusedDataKey,
+                line,
+                message: format!("the key '{key}' is never used"),
+            });
+        }
+    }
+}
+
+fn unused_functions(cfg: &ControlFlowGraph, warnings: &mut Vec<Warning>) {
+    let called: HashSet<usize> = cfg
+        .instructions()
+        .filter_map(|(_, instruction)| match instruction {
+            Instruction::Call(Operand::Immediate(line)) => usize::try_from(*line).ok(),
+            _ => None,
+        })
+        .collect();
+    let computed_calls = cfg
+        .instructions()
+        .any(|(_, instruction)| matches!(instruction, Instruction::Call(Operand::Register(_))));
+
+    for (entry, name) in cfg.functions() {
+        if computed_calls || called.contains(entry) || name.starts_with(TEST_PREFIX) {
+            continue;
+        }
+
+        warnings.push(Warning {
+            lint: Lint::UnusedFunction,
+            line: entry - 1,
+            message: format!("the function '{name}' is never called"),
+        });
+    }
+}
+
+fn unreachable_code(cfg: &ControlFlowGraph, reachable: &[bool], warnings: &mut Vec<Warning>) {
+    let mut previous_reachable = true;
+    for (line, _) in cfg.instructions() {
+        let current = reachable.get(line - 1).copied().unwrap_or(true);
+        // only the first line of unreachable code is reported
+        if !current && previous_reachable {
+            warnings.push(Warning {
+                lint: Lint::UnreachableCode,
+                line,
+                message: String::from("this line can never be reached"),
+            });
+        }
+        previous_reachable = current;
+    }
+}
+
+fn control_flow(cfg: &ControlFlowGraph, reachable: &[bool], warnings: &mut Vec<Warning>) {
+    let mut previous: Option<(usize, &Instruction)> = None;
+    for (line, instruction) in cfg.instructions() {
+        if let (Some(name), Some((previous_line, previous_instruction))) =
+            (cfg.functions().get(&line), previous)
+        {
+            let falls_through = reachable.get(previous_line - 1).copied().unwrap_or(true)
+                && !is_terminator(previous_instruction);
+            if falls_through {
+                warnings.push(Warning {
+                    lint: Lint::FallThroughIntoFunction,
+                    line: line - 1,
+                    message: format!(
+                        "the function '{name}' is entered by continuing past line {previous_line}, rather than by a call"
+                    ),
+                });
+            }
+        }
+
+        match instruction {
+            Instruction::Return if cfg.function(line).is_none() => {
+                warnings.push(Warning {
+                    lint: Lint::ReturnOutsideFunction,
+                    line,
+                    message: String::from("returns outside of a function"),
+                });
+            }
+            Instruction::Jump(_, destination, relative) => {
+                let function = jump_target(line, destination, relative.as_ref())
+                    .and_then(|target| Some((target, cfg.function(target)?)));
+
+                if let Some((target, function)) = function {
+                    if cfg.function(line) != Some(function) && target != function {
+                        let name = cfg.function_name(Some(function));
+                        warnings.push(Warning {
+                            lint: Lint::JumpIntoFunction,
+                            line,
+                            message: format!("jumps into the middle of the function '{name}'"),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        previous = Some((line, instruction));
+    }
+
+    if let Some((line, instruction)) = previous {
+        if !is_terminator(instruction) && reachable.get(line - 1).copied().unwrap_or(true) {
+            warnings.push(Warning {
+                lint: Lint::MissingStop,
+                line,
+                message: String::from(
+                    "the program continues past its last line, rather than reaching a stop instruction",
+                ),
+            });
+        }
+    }
+}
+
+/// Finds the lints allowed on every line, by the line they are allowed on.
+fn allowed(source_code: &str) -> BTreeMap<usize, Vec<String>> {
+    let mut allowed: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+
+    for (line, text) in (1..).zip(source_code.lines()) {
+        let (code, comment) = split_comment(text.trim());
+        let Some(lints) = comment
+            .and_then(|comment| comment.split_once(ALLOW_PREFIX))
+            .and_then(|(_, rest)| rest.split_once(')'))
+            .map(|(lints, _)| lints)
+        else {
+            continue;
+        };
+
+        // a comment on a line of its own applies to the line below it
+        let target = if code.trim().is_empty() {
+            line + 1
+        } else {
+            line
+        };
+        allowed
+            .entry(target)
+            .or_default()
+            .extend(lints.split(',').map(|lint| lint.trim().to_string()));
+    }
+
+    allowed
+}
+
+/// Finds the lints of the program, ordered by line.
+///
+/// # Errors
+/// Returns an error if the program cannot be preprocessed.
+pub fn lint(source_code: &str) -> Result<Vec<Warning>, InterpreterError> {
+    let program = preprocess(source_code, &[])?;
+    let cfg = ControlFlowGraph::new(&program);
+    let reachable = reachable(&cfg);
+
+    let mut warnings = Vec::new();
+    unused_data_keys(source_code, &mut warnings);
+    unused_functions(&cfg, &mut warnings);
+    unreachable_code(&cfg, &reachable, &mut warnings);
+    control_flow(&cfg, &reachable, &mut warnings);
+    warnings.extend(analyse_stack(&cfg).warnings);
+    warnings.extend(analyse_sizes(&cfg));
+    warnings.extend(analyse_uninitialised(&cfg));
+
+    let allowed = allowed(source_code);
+    warnings.retain(|warning| {
+        allowed
+            .get(&warning.line)
+            .is_none_or(|lints| !lints.contains(&warning.lint.to_string()))
+    });
+    warnings.sort_by_key(|warning| warning.line);
+
+    Ok(warnings)
+}
+
+/// Lints every file, printing the warnings.
+///
+/// Returns whether none of the files have any warnings.
+///
+/// # Errors
+/// Returns an error if a file cannot be read or preprocessed.
+pub fn lint_files(args: &LintArguments) -> Result<bool, InterpreterError> {
+    let mut clean = true;
+
+    for file in &args.files {
+        let source_code = read_program_file(file).map_err(InterpreterError::Argument)?;
+        for warning in lint(&source_code)? {
+            println!("{}: {warning}", file.display());
+            clean = false;
+        }
+    }
+
+    Ok(clean)
+}
+
+#[cfg(test)]
+mod lints {
+    use super::{lint, Lint};
+
+    /// Gets the lint and line of every warning of the program.
+    fn warnings(source: &str) -> Vec<(Lint, usize)> {
+        lint(source)
+            .unwrap()
+            .into_iter()
+            .map(|warning| (warning.lint, warning.line))
+            .collect()
+    }
+
+    #[test]
+    fn clean_program() {
+        let source =
+            "setb ra 0\ncall inc\nstop\n\nfn inc:\n  addb ra ONE\n  ret\n\nDATA:\n  ONE 1\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn unused_data_key() {
+        let source = "setb ra ONE\nstop\nDATA:\n  ONE 1\n  // a comment\n  TWO 2\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::UnusedDataKey, 6)]);
+    }
+
+    #[test]
+    fn data_key_as_relative_offset() {
+        let source = "setb ra 3\nsubb ra 1\njnz -BACK\nstop\nDATA:\n  BACK 1\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn unused_function() {
+        let source = "stop\nfn unused:\n  ret\nfn test_called_by_runner:\n  stop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::UnusedFunction, 2)]);
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let source = "jmp 4\nsetb ra 1\nsetb rb 1\nstop\nprrb ra\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(
+            actual,
+            vec![(Lint::UnreachableCode, 2), (Lint::UnreachableCode, 5)]
+        );
+    }
+
+    #[test]
+    fn jump_target_is_reachable() {
+        let source = "jmp 3\nstop\nsetb ra 1\njmp 2\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn missing_stop() {
+        let source = "setb ra 1\nprrb ra\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::MissingStop, 2)]);
+    }
+
+    #[test]
+    fn fall_through_into_function() {
+        let source = "call f\nfn f:\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::FallThroughIntoFunction, 2)]);
+    }
+
+    #[test]
+    fn jump_into_function() {
+        let source = "call f\njmp 5\nfn f:\n  setb ra 1\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::JumpIntoFunction, 2)]);
+    }
+
+    #[test]
+    fn ret_outside_function() {
+        let source = "setb ra 1\nret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::ReturnOutsideFunction, 2)]);
+    }
+}
+
+#[cfg(test)]
+mod suppression {
+    use super::{lint, Lint};
+
+    #[test]
+    fn trailing_comment() {
+        let source = "setb ra 1\nret // lint: allow(ret-outside-function)\n";
+
+        let actual = lint(source).unwrap();
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn comment_above() {
+        let source = "stop\n// lint: allow(unused-function, missing-stop)\nfn unused:\n  ret\n";
+
+        let actual = lint(source).unwrap();
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn other_lint_still_reported() {
+        let source = "setb ra 1\nret // lint: allow(missing-stop)\n";
+
+        let actual: Vec<Lint> = lint(source)
+            .unwrap()
+            .into_iter()
+            .map(|warning| warning.lint)
+            .collect();
+
+        assert_eq!(actual, vec![Lint::ReturnOutsideFunction]);
+    }
+}
@@ -0,0 +1,336 @@
+//! Hover documentation and completions, taken from the instruction documentation and the decode table.
+
+use super::source::{Source, Symbol};
+use crate::{decode::DECODE_TABLE, register::Register};
+use std::fmt::Write;
+
+const DOCUMENTATION: &str = include_str!("../../doc/instructions.md");
+
+/// The sections of the documentation describing the instructions starting with each prefix,
+/// where longer prefixes come before the shorter ones they start with.
+const SECTIONS: [(&str, &str); 24] = [
+    ("add", "Addition"),
+    ("sub", "Subtraction"),
+    ("mul", "Multiplication"),
+    ("div", "Division"),
+    ("rem", "Remainder"),
+    ("set", "Set"),
+    ("and", "And"),
+    ("xor", "Xor"),
+    ("or", "Or"),
+    ("not", "Not"),
+    ("psh", "Push"),
+    ("pop", "Pop"),
+    ("prr", "Print Register"),
+    ("prs", "Print Stack"),
+    ("rd", "Read"),
+    ("a", "Assert"),
+    ("cmp", "Compare"),
+    ("tst", "Test"),
+    ("call", "Call"),
+    ("ret", "Return"),
+    ("stop", "Stop"),
+    ("ext", "External"),
+    ("j", "Jump"),
+    ("fn", "Functions"),
+];
+
+/// The name, number of bits and suffix of every size variant.
+const SIZES: [(&str, u32, char); 4] = [
+    ("byte", 8, 'b'),
+    ("quarter", 16, 'q'),
+    ("half", 32, 'h'),
+    ("word", 64, 'w'),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompletionKind {
+    Instruction,
+    Keyword,
+    Register,
+    Function,
+    DataKey,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+}
+
+/// Gets the body of the section of the documentation with the heading.
+fn section(heading: &str) -> Option<&'static str> {
+    let start = DOCUMENTATION.find(&format!("\n## {heading}\n"))? + heading.len() + 5;
+    let body = DOCUMENTATION.get(start..)?;
+    let end = body
+        .find("\n# ")
+        .into_iter()
+        .chain(body.find("\n## "))
+        .min();
+
+    Some(body.get(..end.unwrap_or(body.len()))?.trim())
+}
+
+fn heading(mnemonic: &str) -> Option<&'static str> {
+    SECTIONS
+        .iter()
+        .find(|(prefix, _)| mnemonic.starts_with(prefix))
+        .map(|(_, heading)| *heading)
+}
+
+/// Gets the name and number of bits of the operands of a sized instruction,
+/// which is one of several instructions differing only by their size suffix.
+fn size(mnemonic: &str) -> Option<(&'static str, u32)> {
+    let stem = mnemonic.get(..mnemonic.len().checked_sub(1)?)?;
+    let (name, bits, _) = SIZES
+        .iter()
+        .find(|(_, _, suffix)| mnemonic.ends_with(*suffix))?;
+    let variants = SIZES
+        .iter()
+        .filter(|(_, _, suffix)| DECODE_TABLE.contains_key(&format!("{stem}{suffix}")))
+        .count();
+
+    (variants > 1).then_some((name, *bits))
+}
+
+fn describe_size(mnemonic: &str) -> String {
+    match size(mnemonic) {
+        Some((name, bits)) => format!("{name} ({bits}-bit) operands"),
+        None => "unsized".to_string(),
+    }
+}
+
+fn mnemonic_hover(mnemonic: &str) -> Option<String> {
+    if !DECODE_TABLE.contains_key(mnemonic) {
+        return None;
+    }
+
+    let mut hover = format!("**{mnemonic}**: {}", describe_size(mnemonic));
+    if let Some(body) = heading(mnemonic).and_then(section) {
+        hover.push_str("\n\n");
+        hover.push_str(body);
+    }
+
+    Some(hover)
+}
+
+fn register_hover(register: Register, mnemonic: Option<&str>) -> String {
+    let mut hover = format!("**{register}**: 64-bit general purpose register");
+    if let Some((name, bits)) = mnemonic.and_then(size) {
+        let _ = write!(
+            hover,
+            "\n\nUsed as a {name} ({bits}-bit) value by `{}`",
+            mnemonic.unwrap_or_default()
+        );
+    }
+
+    hover
+}
+
+/// Gets the documentation of the instruction, register, function or data key at the column, in markdown.
+pub fn hover(source: &Source<'_>, line: usize, column: usize) -> Option<String> {
+    if let Some((symbol, _)) = source.symbol_at(line, column) {
+        return match symbol {
+            Symbol::Function(name) => source
+                .definition(&Symbol::Function(name.clone()))
+                .map(|span| format!("**fn {name}**: defined on line {}", span.line + 1)),
+            Symbol::DataKey(key) => source
+                .data_value(&key)
+                .map(|value| format!("**{key}**: `{value}`")),
+        };
+    }
+
+    if source.is_data(line) {
+        return None;
+    }
+
+    let token = source.token_at(line, column)?;
+    let mnemonic = source.tokens(line).first().map(|token| token.text);
+    match token.index {
+        0 if token.text == "fn" => section("Functions").map(str::to_string),
+        0 => mnemonic_hover(token.text),
+        _ => Register::try_from(token.text)
+            .ok()
+            .map(|register| register_hover(register, mnemonic)),
+    }
+}
+
+/// Gets the completions of the token at the column, which are the instructions for the first token of a line,
+/// the functions after a call, and the registers and data keys elsewhere.
+pub fn completions(source: &Source<'_>, line: usize, column: usize) -> Vec<Completion> {
+    if source.is_data(line) {
+        return Vec::new();
+    }
+
+    let tokens = source.tokens(line);
+    let index = tokens
+        .iter()
+        .position(|token| column <= token.span.end)
+        .unwrap_or(tokens.len());
+
+    match (index, tokens.first().map(|token| token.text)) {
+        (0, _) => {
+            let mut completions: Vec<Completion> = DECODE_TABLE
+                .keys()
+                .map(|mnemonic| Completion {
+                    label: mnemonic.to_string(),
+                    kind: CompletionKind::Instruction,
+                    detail: describe_size(mnemonic),
+                })
+                .collect();
+            completions.sort_by(|a, b| a.label.cmp(&b.label));
+            completions.push(Completion {
+                label: "fn".to_string(),
+                kind: CompletionKind::Keyword,
+                detail: "function definition".to_string(),
+            });
+            completions
+        }
+        (1, Some("call")) => source
+            .functions()
+            .into_iter()
+            .map(|(name, span)| Completion {
+                label: name.to_string(),
+                kind: CompletionKind::Function,
+                detail: format!("defined on line {}", span.line + 1),
+            })
+            .collect(),
+        (_, Some("fn")) => Vec::new(),
+        _ => Register::ALL
+            .iter()
+            .map(|register| Completion {
+                label: register.to_string(),
+                kind: CompletionKind::Register,
+                detail: "register".to_string(),
+            })
+            .chain(source.data_keys().into_iter().map(|(key, _)| Completion {
+                label: key.to_string(),
+                kind: CompletionKind::DataKey,
+                detail: source.data_value(key).unwrap_or_default().to_string(),
+            }))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod hovers {
+    use super::{hover, size};
+    use crate::lsp::source::Source;
+
+    const PROGRAM: &str = "setq ra ONE\ncall f\nstop\n\nfn f:\n  ret\n\nDATA:\n  ONE 1\n";
+
+    #[test]
+    fn sizes() {
+        let expected = [
+            Some(("byte", 8)),
+            Some(("quarter", 16)),
+            Some(("word", 64)),
+            None,
+            None,
+        ];
+
+        let actual = ["addb", "rduq", "pshw", "jmp", "prss"].map(size);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn mnemonic() {
+        let actual = hover(&Source::new(PROGRAM), 0, 2).unwrap();
+
+        assert!(actual.starts_with("**setq**: quarter (16-bit) operands\n\nSets a register"));
+        assert!(!actual.contains("## Addition"));
+    }
+
+    #[test]
+    fn unsized_mnemonic() {
+        let actual = hover(&Source::new(PROGRAM), 1, 2).unwrap();
+
+        assert!(actual.starts_with("**call**: unsized\n\nPushes a return address"));
+    }
+
+    #[test]
+    fn register() {
+        let expected = Some(
+            "**ra**: 64-bit general purpose register\n\nUsed as a quarter (16-bit) value by `setq`"
+                .to_string(),
+        );
+
+        let actual = hover(&Source::new(PROGRAM), 0, 6);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn function() {
+        let expected = Some("**fn f**: defined on line 5".to_string());
+
+        let actual = hover(&Source::new(PROGRAM), 1, 5);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn data_key() {
+        let expected = Some("**ONE**: `1`".to_string());
+
+        let actual = hover(&Source::new(PROGRAM), 0, 9);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod completion {
+    use super::{completions, CompletionKind};
+    use crate::lsp::source::Source;
+
+    const PROGRAM: &str = "ad\ncall \nsetb ra \nstop\n\nfn first:\n  ret\n\nDATA:\n  ONE 1\n";
+
+    fn labels(line: usize, column: usize) -> Vec<(String, CompletionKind)> {
+        completions(&Source::new(PROGRAM), line, column)
+            .into_iter()
+            .map(|completion| (completion.label, completion.kind))
+            .collect()
+    }
+
+    #[test]
+    fn mnemonics() {
+        let actual = labels(0, 2);
+
+        assert!(actual.contains(&("addb".to_string(), CompletionKind::Instruction)));
+        assert!(actual.contains(&("fn".to_string(), CompletionKind::Keyword)));
+    }
+
+    #[test]
+    fn functions() {
+        let expected = vec![("first".to_string(), CompletionKind::Function)];
+
+        let actual = labels(1, 5);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn registers_and_data_keys() {
+        let actual = labels(2, 8);
+
+        assert_eq!(actual.len(), 9);
+        assert_eq!(
+            actual.first(),
+            Some(&("ra".to_string(), CompletionKind::Register))
+        );
+        assert_eq!(
+            actual.last(),
+            Some(&("ONE".to_string(), CompletionKind::DataKey))
+        );
+    }
+
+    #[test]
+    fn nothing_in_data_section() {
+        let actual = labels(9, 2);
+
+        assert_eq!(actual, vec![]);
+    }
+}
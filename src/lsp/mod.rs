@@ -0,0 +1,431 @@
+//! A language server for programs, speaking the Language Server Protocol over stdio.
+//!
+//! Every open document is analysed on its own, as programs are a single file.
+//! Diagnostics come from preprocessing, decoding and linting the program,
+//! and functions and data keys can be navigated to, found and renamed.
+
+use crate::error::InterpreterError;
+use docs::{completions, hover, CompletionKind};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{
+        Completion, GotoDefinition, HoverRequest, References, Rename, Request as LspRequest,
+    },
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, GotoDefinitionParams, GotoDefinitionResponse, Hover,
+    HoverContents, HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind,
+    NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range, ReferenceParams,
+    RenameParams, ServerCapabilities, TextDocumentPositionParams, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit,
+};
+use source::{Severity, Source, Span};
+use std::collections::HashMap;
+
+mod docs;
+mod source;
+
+/// Runs the language server over stdio until the client shuts it down.
+///
+/// # Errors
+/// Returns an error if the client cannot be initialised or communicated with.
+pub fn language_server() -> Result<(), InterpreterError> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection)?;
+    // the writer thread only finishes once the connection, and so its sender, has been dropped
+    drop(connection);
+    io_threads
+        .join()
+        .map_err(|e| InterpreterError::LanguageServer(e.to_string()))
+}
+
+fn serve(connection: &Connection) -> Result<(), InterpreterError> {
+    let failed = |e: &dyn std::fmt::Display| InterpreterError::LanguageServer(e.to_string());
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        rename_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    };
+    let capabilities = serde_json::to_value(capabilities).map_err(|e| failed(&e))?;
+    connection
+        .initialize(capabilities)
+        .map_err(|e| failed(&e))?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        let replies = match message {
+            Message::Request(request) => {
+                if connection
+                    .handle_shutdown(&request)
+                    .map_err(|e| failed(&e))?
+                {
+                    return Ok(());
+                }
+                vec![Message::Response(server.request(request))]
+            }
+            Message::Notification(notification) => server
+                .notification(notification)
+                .into_iter()
+                .map(Message::Notification)
+                .collect(),
+            Message::Response(_) => Vec::new(),
+        };
+
+        for reply in replies {
+            connection.sender.send(reply).map_err(|e| failed(&e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a byte offset into a line to the number of UTF-16 code units before it, as positions are given in.
+fn position(text: &str, span_line: usize, byte: usize) -> Position {
+    let line = text.lines().nth(span_line).unwrap_or_default();
+    let character = line
+        .get(..byte)
+        .unwrap_or(line)
+        .chars()
+        .map(char::len_utf16)
+        .sum::<usize>();
+
+    Position::new(
+        u32::try_from(span_line).unwrap_or(u32::MAX),
+        u32::try_from(character).unwrap_or(u32::MAX),
+    )
+}
+
+fn range(text: &str, span: Span) -> Range {
+    Range::new(
+        position(text, span.line, span.start),
+        position(text, span.line, span.end),
+    )
+}
+
+/// Converts a position to a line and a byte offset into the line.
+fn offset(text: &str, position: Position) -> (usize, usize) {
+    let line = position.line as usize;
+    let text = text.lines().nth(line).unwrap_or_default();
+    let mut units = 0;
+    let byte = text
+        .char_indices()
+        .find(|(_, c)| {
+            units += c.len_utf16();
+            units > position.character as usize
+        })
+        .map_or(text.len(), |(byte, _)| byte);
+
+    (line, byte)
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let response = match request.method.as_str() {
+            GotoDefinition::METHOD => self
+                .handle::<GotoDefinition>(request, |server, params| Ok(server.definition(params))),
+            References::METHOD => {
+                self.handle::<References>(request, |server, params| Ok(server.references(params)))
+            }
+            HoverRequest::METHOD => {
+                self.handle::<HoverRequest>(request, |server, params| Ok(server.hover(&params)))
+            }
+            Completion::METHOD => {
+                self.handle::<Completion>(request, |server, params| Ok(server.completion(&params)))
+            }
+            Rename::METHOD => self.handle::<Rename>(request, Self::rename),
+            method => Err((
+                ErrorCode::MethodNotFound,
+                format!("unsupported request '{method}'"),
+            )),
+        };
+
+        match response {
+            Ok(result) => Response {
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err((code, message)) => Response::new_err(id, code as i32, message),
+        }
+    }
+
+    fn handle<R>(
+        &self,
+        request: Request,
+        handler: impl FnOnce(&Self, R::Params) -> Result<R::Result, String>,
+    ) -> Result<serde_json::Value, (ErrorCode, String)>
+    where
+        R: LspRequest,
+    {
+        let (_, params) = request
+            .extract::<R::Params>(R::METHOD)
+            .map_err(|e| (ErrorCode::InvalidParams, e.to_string()))?;
+        let result =
+            handler(self, params).map_err(|message| (ErrorCode::InvalidParams, message))?;
+
+        serde_json::to_value(result).map_err(|e| (ErrorCode::InternalError, e.to_string()))
+    }
+
+    /// Gets the text of the document and the line and byte offset of the position in it.
+    fn document(&self, params: &TextDocumentPositionParams) -> Option<(&str, usize, usize)> {
+        let text = self.documents.get(&params.text_document.uri)?;
+        let (line, column) = offset(text, params.position);
+
+        Some((text, line, column))
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let params = params.text_document_position_params;
+        let (text, line, column) = self.document(&params)?;
+
+        let source = Source::new(text);
+        source
+            .symbol_at(line, column)
+            .and_then(|(symbol, _)| source.definition(&symbol))
+            .map(|span| {
+                GotoDefinitionResponse::Scalar(Location::new(
+                    params.text_document.uri.clone(),
+                    range(text, span),
+                ))
+            })
+    }
+
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let include_declaration = params.context.include_declaration;
+        let params = params.text_document_position;
+        let (text, line, column) = self.document(&params)?;
+
+        let source = Source::new(text);
+        source.symbol_at(line, column).map(|(symbol, _)| {
+            source
+                .references(&symbol, include_declaration)
+                .into_iter()
+                .map(|span| Location::new(params.text_document.uri.clone(), range(text, span)))
+                .collect()
+        })
+    }
+
+    fn hover(&self, params: &HoverParams) -> Option<Hover> {
+        let (text, line, column) = self.document(&params.text_document_position_params)?;
+
+        let source = Source::new(text);
+        hover(&source, line, column).map(|value| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: source
+                .token_at(line, column)
+                .map(|token| range(text, token.span)),
+        })
+    }
+
+    fn completion(&self, params: &CompletionParams) -> Option<CompletionResponse> {
+        let (text, line, column) = self.document(&params.text_document_position)?;
+
+        let items = completions(&Source::new(text), line, column)
+            .into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.label,
+                kind: Some(match completion.kind {
+                    CompletionKind::Instruction | CompletionKind::Keyword => {
+                        CompletionItemKind::KEYWORD
+                    }
+                    CompletionKind::Register => CompletionItemKind::VARIABLE,
+                    CompletionKind::Function => CompletionItemKind::FUNCTION,
+                    CompletionKind::DataKey => CompletionItemKind::CONSTANT,
+                }),
+                detail: Some(completion.detail),
+                ..CompletionItem::default()
+            })
+            .collect();
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>, String> {
+        let position = params.text_document_position;
+        let Some((text, line, column)) = self.document(&position) else {
+            return Ok(None);
+        };
+
+        let edits = Source::new(text)
+            .rename(line, column, &params.new_name)?
+            .into_iter()
+            .map(|span| TextEdit::new(range(text, span), params.new_name.clone()))
+            .collect();
+
+        Ok(Some(WorkspaceEdit::new(HashMap::from([(
+            position.text_document.uri,
+            edits,
+        )]))))
+    }
+
+    /// Handles a notification, returning the notifications to send in reply.
+    fn notification(&mut self, notification: Notification) -> Vec<Notification> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<<DidOpenTextDocument as LspNotification>::Params>(
+                        DidOpenTextDocument::METHOD,
+                    )
+                else {
+                    return Vec::new();
+                };
+                let uri = params.text_document.uri;
+                self.documents
+                    .insert(uri.clone(), params.text_document.text);
+                uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<<DidChangeTextDocument as LspNotification>::Params>(
+                        DidChangeTextDocument::METHOD,
+                    )
+                else {
+                    return Vec::new();
+                };
+                // the whole document is synchronised, so the last change holds all of its text
+                let uri = params.text_document.uri;
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(uri.clone(), change.text);
+                }
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<<DidCloseTextDocument as LspNotification>::Params>(
+                        DidCloseTextDocument::METHOD,
+                    )
+                else {
+                    return Vec::new();
+                };
+                self.documents.remove(&params.text_document.uri);
+                params.text_document.uri
+            }
+            _ => return Vec::new(),
+        };
+
+        vec![self.diagnostics(uri)]
+    }
+
+    /// Publishes the problems of the document, or clears them once it has been closed.
+    fn diagnostics(&self, uri: Uri) -> Notification {
+        let diagnostics = self
+            .documents
+            .get(&uri)
+            .map(|text| {
+                Source::new(text)
+                    .problems()
+                    .into_iter()
+                    .map(|problem| Diagnostic {
+                        range: range(text, problem.span),
+                        severity: Some(match problem.severity {
+                            Severity::Error => DiagnosticSeverity::ERROR,
+                            Severity::Warning => DiagnosticSeverity::WARNING,
+                        }),
+                        code: problem.code.map(NumberOrString::String),
+                        source: Some("kasm".to_string()),
+                        message: problem.message,
+                        ..Diagnostic::default()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams::new(uri, diagnostics, None),
+        )
+    }
+}
+
+#[cfg(test)]
+mod server {
+    use super::serve;
+    use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+    use serde_json::{json, Value};
+    use std::thread;
+
+    /// Sends the request and gets the result of its response.
+    fn request(client: &Connection, id: i32, method: &str, params: Value) -> Value {
+        let request = Request::new(RequestId::from(id), method.to_string(), params);
+        client.sender.send(Message::Request(request)).unwrap();
+
+        match client.receiver.recv().unwrap() {
+            Message::Response(Response { result, error, .. }) => {
+                assert!(error.is_none(), "unexpected error {error:?}");
+                result.unwrap_or_default()
+            }
+            message => panic!("expected a response, got {message:?}"),
+        }
+    }
+
+    fn notify(client: &Connection, method: &str, params: Value) {
+        let notification = Notification::new(method.to_string(), params);
+        client
+            .sender
+            .send(Message::Notification(notification))
+            .unwrap();
+    }
+
+    #[test]
+    fn session() {
+        let (server, client) = Connection::memory();
+        let handle = thread::spawn(move || serve(&server));
+        let uri = "file:///program.kasm";
+        let position = |line: u32, character: u32| json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } });
+
+        let initialized = request(&client, 1, "initialize", json!({ "capabilities": {} }));
+        assert_eq!(
+            initialized.pointer("/capabilities/definitionProvider"),
+            Some(&json!(true))
+        );
+        notify(&client, "initialized", json!({}));
+
+        notify(
+            &client,
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": uri, "languageId": "kasm", "version": 1, "text": "call f\nstop\n\nfn f:\n  setb ra 300\n  ret\n" } }),
+        );
+        let Message::Notification(diagnostics) = client.receiver.recv().unwrap() else {
+            panic!("expected diagnostics to be published");
+        };
+        assert_eq!(diagnostics.method, "textDocument/publishDiagnostics");
+        assert_eq!(
+            diagnostics.params.pointer("/diagnostics/0/range"),
+            Some(
+                &json!({ "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 13 } })
+            )
+        );
+
+        let definition = request(&client, 2, "textDocument/definition", position(0, 5));
+        assert_eq!(
+            definition,
+            json!({ "uri": uri, "range": { "start": { "line": 3, "character": 3 }, "end": { "line": 3, "character": 4 } } })
+        );
+
+        let rename = json!({ "textDocument": { "uri": uri }, "position": { "line": 3, "character": 3 }, "newName": "g" });
+        let renamed = request(&client, 3, "textDocument/rename", rename);
+        let edits = renamed.get("changes").and_then(|changes| changes.get(uri));
+        assert_eq!(edits.and_then(Value::as_array).map(Vec::len), Some(2));
+
+        request(&client, 4, "shutdown", Value::Null);
+        notify(&client, "exit", Value::Null);
+        handle.join().unwrap().unwrap();
+    }
+}
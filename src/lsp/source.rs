@@ -0,0 +1,626 @@
+//! The symbols of a program's source code and the problems found in it, as the language server sees them.
+//!
+//! Every position is a zero-based line and a byte offset into that line,
+//! conversion to the positions of the protocol is left to the server.
+
+use crate::{
+    decode::DECODE_TABLE,
+    error::{DecodeError, InterpreterError, PreProcessError},
+    format::split_comment,
+    lint::lint,
+    preprocess,
+    preprocess::{is_screaming_snake_case, is_snake_case, DATA_SECTION},
+};
+
+const FUNCTION: &str = "fn";
+const CALL: &str = "call";
+const EXTERNAL: &str = "ext";
+
+/// A part of a line, from the start byte up to, but not including, the end byte.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A whitespace separated part of the code on a line, excluding any comment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Token<'a> {
+    pub span: Span,
+    pub text: &'a str,
+    /// The position of the token among the tokens of its line.
+    pub index: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Symbol {
+    Function(String),
+    DataKey(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// An error or lint found in the source code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub span: Span,
+    pub severity: Severity,
+    /// The name of the lint, if the problem is a lint.
+    pub code: Option<String>,
+    pub message: String,
+}
+
+pub struct Source<'a> {
+    text: &'a str,
+    lines: Vec<&'a str>,
+    /// The line starting the data section, which is the last line containing the data section marker,
+    /// the same as the preprocessor uses.
+    data_section: Option<usize>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let data_section = lines.iter().rposition(|line| line.contains(DATA_SECTION));
+
+        Self {
+            text,
+            lines,
+            data_section,
+        }
+    }
+
+    pub fn line(&self, line: usize) -> Option<&'a str> {
+        self.lines.get(line).copied()
+    }
+
+    /// Whether the line is an entry of the data section.
+    pub fn is_data(&self, line: usize) -> bool {
+        self.data_section.is_some_and(|start| line > start)
+    }
+
+    pub fn tokens(&self, line: usize) -> Vec<Token<'a>> {
+        let Some(text) = self.line(line) else {
+            return Vec::new();
+        };
+        let (code, _) = split_comment(text);
+
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (offset, c) in code.char_indices().chain([(code.len(), ' ')]) {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some(offset),
+                (Some(from), true) => {
+                    tokens.push(Token {
+                        span: Span {
+                            line,
+                            start: from,
+                            end: offset,
+                        },
+                        text: code.get(from..offset).unwrap_or_default(),
+                        index: tokens.len(),
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        tokens
+    }
+
+    /// Gets the token the column is in or directly after.
+    pub fn token_at(&self, line: usize, column: usize) -> Option<Token<'a>> {
+        self.tokens(line)
+            .into_iter()
+            .find(|token| token.span.start <= column && column <= token.span.end)
+    }
+
+    /// Gets the name and span of the name of every function definition.
+    pub fn functions(&self) -> Vec<(&'a str, Span)> {
+        (0..self.lines.len())
+            .filter(|line| !self.is_data(*line))
+            .filter_map(|line| match self.tokens(line).as_slice() {
+                [keyword, name, ..] if keyword.text == FUNCTION => {
+                    let text = name.text.strip_suffix(':').unwrap_or(name.text);
+                    let span = Span {
+                        end: name.span.start + text.len(),
+                        ..name.span
+                    };
+                    Some((text, span))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the key and span of the key of every entry of the data section.
+    pub fn data_keys(&self) -> Vec<(&'a str, Span)> {
+        (0..self.lines.len())
+            .filter(|line| self.is_data(*line))
+            .filter_map(|line| {
+                self.tokens(line)
+                    .first()
+                    .map(|token| (token.text, token.span))
+            })
+            .collect()
+    }
+
+    /// Gets the value of an entry of the data section.
+    pub fn data_value(&self, key: &str) -> Option<&'a str> {
+        (0..self.lines.len())
+            .filter(|line| self.is_data(*line))
+            .find_map(|line| match self.tokens(line).as_slice() {
+                [k, value, ..] if k.text == key => Some(value.text),
+                _ => None,
+            })
+    }
+
+    /// Gets the names of the host functions called by the program.
+    pub fn externals(&self) -> Vec<&'a str> {
+        let mut externals: Vec<&str> = (0..self.lines.len())
+            .filter(|line| !self.is_data(*line))
+            .filter_map(|line| match self.tokens(line).as_slice() {
+                [keyword, name, ..] if keyword.text == EXTERNAL => Some(name.text),
+                _ => None,
+            })
+            .collect();
+        externals.sort_unstable();
+        externals.dedup();
+
+        externals
+    }
+
+    /// Gets the symbol at the column, along with the span of its name.
+    pub fn symbol_at(&self, line: usize, column: usize) -> Option<(Symbol, Span)> {
+        let token = self.token_at(line, column)?;
+        let first = self.tokens(line).first().map(|token| token.text)?;
+
+        if self.is_data(line) {
+            return (token.index == 0)
+                .then(|| (Symbol::DataKey(token.text.to_string()), token.span));
+        }
+
+        match (first, token.index) {
+            (FUNCTION, 1) => {
+                let name = token.text.strip_suffix(':').unwrap_or(token.text);
+                let span = Span {
+                    end: token.span.start + name.len(),
+                    ..token.span
+                };
+                Some((Symbol::Function(name.to_string()), span))
+            }
+            (CALL, 1) if self.functions().iter().any(|(f, _)| *f == token.text) => {
+                Some((Symbol::Function(token.text.to_string()), token.span))
+            }
+            (_, 0) => None,
+            _ => {
+                let (key, span) = strip_sign(token);
+                self.data_keys()
+                    .iter()
+                    .any(|(k, _)| *k == key)
+                    .then(|| (Symbol::DataKey(key.to_string()), span))
+            }
+        }
+    }
+
+    pub fn definition(&self, symbol: &Symbol) -> Option<Span> {
+        match symbol {
+            Symbol::Function(name) => self.functions().into_iter().find(|(f, _)| f == name),
+            Symbol::DataKey(name) => self.data_keys().into_iter().find(|(k, _)| k == name),
+        }
+        .map(|(_, span)| span)
+    }
+
+    /// Gets the span of every use of the symbol, along with its definition if asked for.
+    pub fn references(&self, symbol: &Symbol, include_definition: bool) -> Vec<Span> {
+        let mut spans = Vec::new();
+        if include_definition {
+            spans.extend(self.definition(symbol));
+        }
+
+        for line in (0..self.lines.len()).filter(|line| !self.is_data(*line)) {
+            let tokens = self.tokens(line);
+            match symbol {
+                Symbol::Function(name) => {
+                    if let [keyword, called, ..] = tokens.as_slice() {
+                        if keyword.text == CALL && called.text == name {
+                            spans.push(called.span);
+                        }
+                    }
+                }
+                Symbol::DataKey(name) => {
+                    if tokens.first().is_some_and(|token| token.text == FUNCTION) {
+                        continue;
+                    }
+                    spans.extend(
+                        tokens
+                            .into_iter()
+                            .skip(1)
+                            .map(strip_sign)
+                            .filter(|(key, _)| key == name)
+                            .map(|(_, span)| span),
+                    );
+                }
+            }
+        }
+
+        spans.sort_by_key(|span| (span.line, span.start));
+        spans
+    }
+
+    /// Gets the spans to replace with the new name to rename the symbol at the column,
+    /// or a description of why the symbol can not be renamed.
+    pub fn rename(&self, line: usize, column: usize, name: &str) -> Result<Vec<Span>, String> {
+        let (symbol, _) = self
+            .symbol_at(line, column)
+            .ok_or("there is no function or data key to rename here")?;
+
+        match &symbol {
+            Symbol::Function(_) => {
+                if name.is_empty() || !is_snake_case(name) {
+                    return Err(format!("function name '{name}' is not snake case"));
+                }
+                if name == FUNCTION || DECODE_TABLE.contains_key(name) {
+                    return Err(format!("'{name}' is the name of an instruction"));
+                }
+                if self.functions().iter().any(|(f, _)| *f == name) {
+                    return Err(format!("function name '{name}' is already defined"));
+                }
+            }
+            Symbol::DataKey(_) => {
+                if name.is_empty() || !is_screaming_snake_case(name) {
+                    return Err(format!("the format for the key '{name}' is invalid"));
+                }
+                // keys are substituted as text, so one may not be part of another
+                let keys = self.data_keys();
+                if keys.iter().any(|(k, _)| *k == name) {
+                    return Err(format!("the key '{name}' is already defined"));
+                }
+                if let Some((key, _)) = keys.iter().find(|(k, _)| {
+                    Symbol::DataKey(k.to_string()) != symbol
+                        && (k.contains(name) || name.contains(k))
+                }) {
+                    return Err(format!("the key '{name}' overlaps with the key '{key}'"));
+                }
+            }
+        }
+
+        Ok(self.references(&symbol, true))
+    }
+
+    /// Gets the span from the first to the last token of the line, or the whole line if there are none.
+    fn code_span(&self, line: usize) -> Span {
+        let tokens = self.tokens(line);
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => Span {
+                line,
+                start: first.span.start,
+                end: last.span.end,
+            },
+            _ => Span {
+                line,
+                start: 0,
+                end: self.line(line).map_or(0, str::len),
+            },
+        }
+    }
+
+    /// Finds the line a preprocessing error was caused by.
+    fn locate(&self, error: &PreProcessError) -> Span {
+        let functions = || (0..self.lines.len()).filter(|line| !self.is_data(*line));
+        let defining = |line: &usize| {
+            self.tokens(*line)
+                .first()
+                .is_some_and(|token| token.text == FUNCTION)
+        };
+        let containing = |name: &str, data: bool| {
+            (0..self.lines.len())
+                .filter(|line| self.is_data(*line) == data)
+                .find(|line| self.tokens(*line).iter().any(|token| token.text == name))
+        };
+
+        let line = match error {
+            PreProcessError::MissingValue(key) | PreProcessError::InvalidKeyFormat(key) => {
+                containing(key, true)
+            }
+            PreProcessError::MissingFunctionName => functions()
+                .filter(defining)
+                .find(|line| self.tokens(*line).len() < 2),
+            PreProcessError::MissingColonSuffix => functions().filter(defining).find(|line| {
+                self.tokens(*line)
+                    .get(1)
+                    .is_some_and(|name| !name.text.ends_with(':'))
+            }),
+            PreProcessError::DuplicateFunctionName(name) => self
+                .functions()
+                .into_iter()
+                .filter(|(f, _)| f == name)
+                .nth(1)
+                .map(|(_, span)| span.line),
+            PreProcessError::InvalidFunctionNameFormat(name) => self
+                .functions()
+                .into_iter()
+                .find(|(f, _)| f == name)
+                .map(|(_, span)| span.line),
+            PreProcessError::FunctionNamedAfterInstruction => self
+                .functions()
+                .into_iter()
+                .find(|(f, _)| DECODE_TABLE.contains_key(*f))
+                .map(|(_, span)| span.line),
+            PreProcessError::UndefinedFunctionCalled(name)
+            | PreProcessError::UndefinedExternalFunction(name) => containing(name, false),
+        };
+
+        self.code_span(line.unwrap_or_default())
+    }
+
+    /// Finds the preprocessing and decoding errors of the program, followed by its lints.
+    pub fn problems(&self) -> Vec<Problem> {
+        let error = |span: Span, message: String| Problem {
+            span,
+            severity: Severity::Error,
+            code: None,
+            message,
+        };
+
+        let program = match preprocess(self.text, &self.externals()) {
+            Ok(program) => program,
+            Err(InterpreterError::PreProcess(e)) => {
+                return vec![error(self.locate(&e), e.to_string())];
+            }
+            Err(e) => return vec![error(self.code_span(0), e.to_string())],
+        };
+
+        let mut problems = Vec::new();
+        // the data section is not part of the preprocessed program
+        let code = (0..self.lines.len())
+            .filter(|line| !self.is_data(*line) && Some(*line) != self.data_section);
+        for line in code {
+            let first = self.tokens(line).first().map(|token| token.text);
+            if first.is_none_or(|first| first == FUNCTION) {
+                continue;
+            }
+
+            match program.instruction(line + 1) {
+                Err(InterpreterError::Decode(_, DecodeError::EmptyLine)) | Ok(_) => {}
+                Err(InterpreterError::Decode(_, e)) => {
+                    problems.push(error(self.code_span(line), e.to_string()));
+                }
+                Err(e) => problems.push(error(self.code_span(line), e.to_string())),
+            }
+        }
+
+        // linting preprocesses without any host functions, so only programs without them are linted
+        if problems.is_empty() {
+            if let Ok(warnings) = lint(self.text) {
+                problems.extend(warnings.into_iter().map(|warning| Problem {
+                    span: self.code_span(warning.line.saturating_sub(1)),
+                    severity: Severity::Warning,
+                    code: Some(warning.lint.to_string()),
+                    message: warning.message,
+                }));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Strips the sign of a relative jump from a token, which may precede a data key.
+fn strip_sign(token: Token<'_>) -> (&str, Span) {
+    match token.text.strip_prefix(['+', '-']) {
+        Some(text) => (
+            text,
+            Span {
+                start: token.span.start + 1,
+                ..token.span
+            },
+        ),
+        None => (token.text, token.span),
+    }
+}
+
+#[cfg(test)]
+mod symbols {
+    use super::{Source, Span, Symbol};
+
+    const PROGRAM: &str = "\
+setb ra ONE
+call add // adds one
+jiz +SKIP
+stop
+
+fn add:
+  addb ra ONE
+  ret
+
+DATA:
+  ONE 1
+  SKIP 2
+";
+
+    fn span(line: usize, start: usize, end: usize) -> Span {
+        Span { line, start, end }
+    }
+
+    #[test]
+    fn tokens_exclude_comments() {
+        let expected = vec![(span(1, 0, 4), "call"), (span(1, 5, 8), "add")];
+
+        let actual: Vec<(Span, &str)> = Source::new(PROGRAM)
+            .tokens(1)
+            .into_iter()
+            .map(|token| (token.span, token.text))
+            .collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn function_at_call() {
+        let expected = Some((Symbol::Function("add".to_string()), span(1, 5, 8)));
+
+        let actual = Source::new(PROGRAM).symbol_at(1, 6);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn data_key_after_sign() {
+        let expected = Some((Symbol::DataKey("SKIP".to_string()), span(2, 5, 9)));
+
+        let actual = Source::new(PROGRAM).symbol_at(2, 7);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn no_symbol_at_mnemonic() {
+        let actual = Source::new(PROGRAM).symbol_at(0, 1);
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn function_definition() {
+        let expected = Some(span(5, 3, 6));
+
+        let actual = Source::new(PROGRAM).definition(&Symbol::Function("add".to_string()));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn data_key_references() {
+        let expected = vec![span(0, 8, 11), span(6, 10, 13), span(10, 2, 5)];
+
+        let actual = Source::new(PROGRAM).references(&Symbol::DataKey("ONE".to_string()), true);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rename_function() {
+        let expected = Ok(vec![span(1, 5, 8), span(5, 3, 6)]);
+
+        let actual = Source::new(PROGRAM).rename(5, 4, "increment");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rename_to_instruction() {
+        let actual = Source::new(PROGRAM).rename(1, 6, "stop");
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn rename_key_to_overlapping_key() {
+        let actual = Source::new(PROGRAM).rename(10, 2, "SKIP_TWO");
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn rename_key() {
+        let expected = Ok(vec![span(0, 8, 11), span(6, 10, 13), span(10, 2, 5)]);
+
+        let actual = Source::new(PROGRAM).rename(0, 9, "INCREMENT");
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod problems {
+    use super::{Problem, Severity, Source, Span};
+
+    fn problems(text: &str) -> Vec<(usize, Severity, Option<String>)> {
+        Source::new(text)
+            .problems()
+            .into_iter()
+            .map(
+                |Problem {
+                     span,
+                     severity,
+                     code,
+                     ..
+                 }: Problem| (span.line, severity, code),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn clean_program() {
+        let actual = problems("setb ra 1\nprrb ra\nstop\n");
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let text = "setb ra 1\n\nsetb ra 300\nfoo ra\nstop\n";
+        let expected = vec![(2, Severity::Error, None), (3, Severity::Error, None)];
+
+        let actual = problems(text);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn decode_error_span() {
+        let expected = Span {
+            line: 0,
+            start: 2,
+            end: 13,
+        };
+
+        let actual = Source::new("  setb ra 300 // too big\nstop\n").problems();
+
+        assert_eq!(actual.first().map(|problem| problem.span), Some(expected));
+    }
+
+    #[test]
+    fn undefined_function() {
+        let text = "setb ra 1\ncall missing\nstop\n";
+        let expected = vec![(1, Severity::Error, None)];
+
+        let actual = problems(text);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_key() {
+        let text = "setb ra 1\nstop\n\nDATA:\n  ONE 1\n  two 2\n";
+        let expected = vec![(5, Severity::Error, None)];
+
+        let actual = problems(text);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn external_functions_are_defined() {
+        let actual = problems("setb ra 1\next print\nstop\n");
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn lints() {
+        let text = "setb ra 1\nstop\nprrb ra\n";
+        let expected = vec![(2, Severity::Warning, Some("unreachable-code".to_string()))];
+
+        let actual = problems(text);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,63 +1,138 @@
 use clap::Parser;
-use kasm::{ArgumentError, Arguments, Interpreter, InterpreterError};
-use std::{fs::File, io::Read};
+use kasm::{
+    assemble, disassemble, export_graphs, format_files, language_server, lint_files, read_program,
+    run_tests, stack_usage, verify_output, Arguments, Cli, Command, Interpreter, InterpreterError,
+    ProgramFile,
+};
+use std::process::ExitCode;
 
-fn main() {
-    let args = Arguments::parse();
-    let file_name = args.file_name.clone();
-    if !file_name.is_file() {
-        eprintln!(
-            "{}",
-            InterpreterError::Argument(ArgumentError::NotAFile(file_name))
-        );
-        return;
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Run(args) => run(*args),
+        Command::Test(args) => match run_tests(args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Assemble(args) => match assemble(&args) {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Fmt(args) => match format_files(&args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Lint(args) => match lint_files(&args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Graph(args) => match export_graphs(&args) {
+            Ok(paths) => {
+                for path in paths {
+                    println!("wrote '{}'", path.display());
+                }
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Stack(args) => match stack_usage(&args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Lsp => match language_server() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Disasm(args) => match disassemble(&args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        },
     }
+}
+
+fn run(args: Arguments) -> ExitCode {
+    let program = match read_program(&args.file_name) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}", InterpreterError::Argument(err));
+            return ExitCode::FAILURE;
+        }
+    };
 
+    let file_name = args.file_name.clone();
+    let golden = args.golden;
+    let dump = args.dump_on_error.clone();
     let mut i = match Interpreter::try_new(args) {
         Ok(p) => p,
         Err(err) => {
             eprintln!("{err}");
-            return;
+            return ExitCode::FAILURE;
         }
     };
 
-    let mut file = match File::options()
-        .read(true)
-        .write(false)
-        .truncate(false)
-        .create(false)
-        .open(file_name.clone())
-    {
-        Ok(f) => f,
-        Err(err) => {
-            let error_msg = match err.kind() {
-                std::io::ErrorKind::NotFound => {
-                    InterpreterError::Argument(ArgumentError::FileNotFound(file_name))
-                }
-                std::io::ErrorKind::PermissionDenied => {
-                    InterpreterError::Argument(ArgumentError::LackingPermissions(
-                        "open the specified program file".to_string(),
-                    ))
+    let result = match &program {
+        ProgramFile::Source(content) => i.run(content),
+        ProgramFile::Assembled(bytes) => i.run_assembled(bytes),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+
+        if let InterpreterError::Execute(_, _) = err {
+            if let Some(instruction) = i.failed_instruction() {
+                eprintln!("while executing '{instruction}'");
+            }
+            eprintln!("{}", i.backtrace());
+
+            if let Some(path) = dump {
+                if let Err(dump_err) = i.dump(&err, &path) {
+                    eprintln!("{dump_err}");
                 }
-                _ => InterpreterError::Argument(ArgumentError::UnknownProgramFileIssue(
-                    err.to_string(),
-                )),
-            };
-            eprintln!("{error_msg}");
-            return;
+            }
         }
-    };
 
-    let mut content = String::new();
-    if file.read_to_string(&mut content).is_err() {
-        eprintln!(
-            "{}",
-            InterpreterError::Argument(ArgumentError::ProgramFileInvalidEncoding)
-        );
-        return;
+        return ExitCode::FAILURE;
     }
 
-    if let Err(err) = i.run(&content) {
-        eprintln!("{err}");
+    if golden.expect {
+        let actual = i.captured_output().unwrap_or_default();
+        // assembled programs have no embedded expectation, so they are compared against a sidecar file
+        let content = match &program {
+            ProgramFile::Source(content) => content.as_str(),
+            ProgramFile::Assembled(_) => "",
+        };
+        if let Err(err) = verify_output(&file_name, content, &actual, golden.bless) {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
     }
+
+    ExitCode::SUCCESS
 }
@@ -1,16 +1,35 @@
 use crate::{error::DecodeError, register::Register};
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 /// Represents a register or an immediate value.
 ///
 /// This type is used when an argument of an instruction
 /// could be either a register, or an immediately defined value.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Operand<Size> {
     Register(Register),
     Immediate(Size),
 }
 
+impl<T> Operand<T> {
+    /// Gets the register of the operand, if it is one.
+    pub fn register(&self) -> Option<Register> {
+        match self {
+            Operand::Register(register) => Some(*register),
+            Operand::Immediate(_) => None,
+        }
+    }
+}
+
+impl<T: Display> Display for Operand<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{register}"),
+            Operand::Immediate(value) => write!(f, "{value}"),
+        }
+    }
+}
+
 fn is_possible_register(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_lowercase())
 }
@@ -6,7 +6,8 @@ use crate::{
     error::PreProcessError,
 };
 
-const DATA_SECTION: &str = "DATA:";
+pub const DATA_SECTION: &str = "DATA:";
+const EXTERNAL: &str = "ext";
 
 pub fn expand_data_section(s: &str) -> Result<String, PreProcessError> {
     if let Some(data_section_start) = s.rfind(DATA_SECTION) {
@@ -48,7 +49,10 @@ pub fn expand_data_section(s: &str) -> Result<String, PreProcessError> {
 // This implementation is quite poor IMO - ideally this is changed to something clearer
 // and more performant in the future. Test cases are written so similar behaviour can be
 // asserted with a new implementation.
-pub fn expand_function_calls(s_program: String) -> Result<Box<[String]>, PreProcessError> {
+pub fn expand_function_calls(
+    s_program: String,
+    externals: &[&str],
+) -> Result<Box<[String]>, PreProcessError> {
     let mut program: Vec<String> = s_program.lines().map(|l| l.to_string()).collect();
     let mut f_index = HashMap::new();
 
@@ -94,7 +98,8 @@ pub fn expand_function_calls(s_program: String) -> Result<Box<[String]>, PreProc
         }
 
         let mut trim_iter = trim.split_ascii_whitespace();
-        if trim_iter.next().is_some_and(|s| s == "call") {
+        let keyword = trim_iter.next();
+        if keyword == Some("call") {
             if let Some(possible_function) = trim_iter.next() {
                 let Some(destination) = f_index.get(possible_function) else {
                     if possible_function.parse::<Word>().is_ok() {
@@ -108,6 +113,19 @@ pub fn expand_function_calls(s_program: String) -> Result<Box<[String]>, PreProc
 
                 *line = line.replacen(possible_function, &destination.to_string(), 1);
             }
+        } else if keyword == Some(EXTERNAL) {
+            // host functions are substituted for their index, as registered by the embedding program
+            if let Some(name) = trim_iter.next() {
+                let index = externals
+                    .iter()
+                    .position(|external| *external == name)
+                    .ok_or(PreProcessError::UndefinedExternalFunction(name.to_string()))?;
+
+                let name = name.to_string();
+                let parameters_start = line.len() - trim.len() + EXTERNAL.len();
+                let parameters = line.split_off(parameters_start);
+                line.push_str(&parameters.replacen(&name, &index.to_string(), 1));
+            }
         }
     }
 
@@ -115,13 +133,13 @@ pub fn expand_function_calls(s_program: String) -> Result<Box<[String]>, PreProc
 }
 
 #[inline]
-fn is_snake_case(s: &str) -> bool {
+pub(crate) fn is_snake_case(s: &str) -> bool {
     s.chars()
         .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
 }
 
 #[inline]
-fn is_screaming_snake_case(s: &str) -> bool {
+pub(crate) fn is_screaming_snake_case(s: &str) -> bool {
     s.chars()
         .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
 }
@@ -321,7 +339,7 @@ mod expand_function_calls {
         let input = ["fn", "addq ra 1"].join("\n");
         let expected = Err(PreProcessError::MissingFunctionName);
 
-        let actual = expand_function_calls(input);
+        let actual = expand_function_calls(input, &[]);
 
         assert_eq!(actual, expected);
     }
@@ -331,7 +349,7 @@ mod expand_function_calls {
         let input = ["fn add_number", "addq ra 1"].join("\n");
         let expected = Err(PreProcessError::MissingColonSuffix);
 
-        let actual = expand_function_calls(input);
+        let actual = expand_function_calls(input, &[]);
 
         assert_eq!(actual, expected);
     }
@@ -352,7 +370,7 @@ mod expand_function_calls {
             "add_number".to_string(),
         ));
 
-        let actual = expand_function_calls(input);
+        let actual = expand_function_calls(input, &[]);
 
         assert_eq!(actual, expected);
     }
@@ -364,7 +382,7 @@ mod expand_function_calls {
             "sub_number".to_string(),
         ));
 
-        let actual = expand_function_calls(input);
+        let actual = expand_function_calls(input, &[]);
 
         assert_eq!(actual, expected);
     }
@@ -376,7 +394,7 @@ mod expand_function_calls {
             "ADD_NUMBER".to_string(),
         ));
 
-        let actual = expand_function_calls(input);
+        let actual = expand_function_calls(input, &[]);
 
         assert_eq!(actual, expected);
     }
@@ -386,7 +404,7 @@ mod expand_function_calls {
         let input = ["fn addq:", "addq ra 1", "ret"].join("\n");
         let expected = Err(PreProcessError::FunctionNamedAfterInstruction);
 
-        let actual = expand_function_calls(input);
+        let actual = expand_function_calls(input, &[]);
 
         assert_eq!(actual, expected);
     }
@@ -400,7 +418,7 @@ mod expand_function_calls {
             String::from("  ret"),
         ]);
 
-        let actual = expand_function_calls(input)?;
+        let actual = expand_function_calls(input, &[])?;
 
         assert_eq!(actual, expected);
 
@@ -418,13 +436,41 @@ mod expand_function_calls {
             String::from("call 2"),
         ]);
 
-        let actual = expand_function_calls(input)?;
+        let actual = expand_function_calls(input, &[])?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn external_substituted_for_index() -> Result<(), PreProcessError> {
+        let input = ["ext read_file", "  ext e // ext e", "stop"].join("\n");
+        let expected: Box<[String]> = Box::new([
+            String::from("ext 1"),
+            String::from("  ext 0 // ext e"),
+            String::from("stop"),
+        ]);
+
+        let actual = expand_function_calls(input, &["e", "read_file"])?;
 
         assert_eq!(actual, expected);
 
         Ok(())
     }
 
+    #[test]
+    fn undefined_external_function_error() {
+        let input = ["fn inc_ra:", "  ret", "ext inc_ra"].join("\n");
+        let expected = Err(PreProcessError::UndefinedExternalFunction(
+            "inc_ra".to_string(),
+        ));
+
+        let actual = expand_function_calls(input, &["dec_ra"]);
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn call_before_function_definition() -> Result<(), PreProcessError> {
         let input = ["call inc_ra", "", "fn inc_ra:", "  addb ra 1", "  ret"].join("\n");
@@ -436,7 +482,7 @@ mod expand_function_calls {
             String::from("  ret"),
         ]);
 
-        let actual = expand_function_calls(input)?;
+        let actual = expand_function_calls(input, &[])?;
 
         assert_eq!(actual, expected);
 
@@ -471,7 +517,7 @@ mod expand_function_calls {
             String::from("call 6"),
         ]);
 
-        let actual = expand_function_calls(input)?;
+        let actual = expand_function_calls(input, &[])?;
 
         assert_eq!(actual, expected);
 
@@ -500,7 +546,7 @@ mod expand_function_calls {
             String::from("call 3"),
         ]);
 
-        let actual = expand_function_calls(input)?;
+        let actual = expand_function_calls(input, &[])?;
 
         assert_eq!(actual, expected);
 
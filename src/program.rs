@@ -1,10 +1,28 @@
-use crate::InterpreterError;
+use crate::{
+    bytecode::Line, call_stack::Functions, constant::COMMENT, decode::decode,
+    instruction::Instruction, InterpreterError,
+};
 
-pub struct Program(Box<[String]>);
+pub struct Program {
+    lines: Box<[String]>,
+    /// The decoded lines, if the program was loaded from an assembled file.
+    assembled: Option<Box<[Line]>>,
+}
 
 impl Program {
     pub fn new(program: Box<[String]>) -> Self {
-        Self(program)
+        Self {
+            lines: program,
+            assembled: None,
+        }
+    }
+
+    /// Creates a program from the lines of an assembled file, where the text of each line is only shown.
+    pub fn assembled(text: Box<[String]>, lines: Box<[Line]>) -> Self {
+        Self {
+            lines: text,
+            assembled: Some(lines),
+        }
     }
 
     pub fn get(&self, index: usize) -> Result<&str, InterpreterError> {
@@ -13,10 +31,95 @@ impl Program {
         }
 
         let line = self
-            .0
+            .lines
             .get(index - 1)
             .ok_or(InterpreterError::InvalidProgramCounter(index))?;
 
         Ok(line)
     }
+
+    /// Gets the instruction on the line, or none if the line is a comment.
+    ///
+    /// Lines of assembled programs are already decoded, while the lines of source code are decoded here.
+    pub fn instruction(&self, index: usize) -> Result<Option<Instruction>, InterpreterError> {
+        let line = self.get(index)?;
+        let line = match self
+            .assembled
+            .as_ref()
+            .and_then(|lines| lines.get(index - 1))
+        {
+            Some(Line::Comment) => return Ok(None),
+            Some(Line::Instruction(instruction)) => return Ok(Some(instruction.clone())),
+            Some(Line::Text(text)) => text,
+            None => line,
+        };
+
+        // comments are indented along with the instructions of a function
+        if line.trim_start().starts_with(COMMENT) {
+            return Ok(None);
+        }
+
+        decode(line)
+            .map(Some)
+            .map_err(|e| InterpreterError::Decode(index, e))
+    }
+
+    /// Gets all lines of the program, where the first line is at index 0.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Indexes the functions of the program by the line of their first instruction.
+    ///
+    /// The function definitions are expected to already be validated during preprocessing.
+    pub fn functions(&self) -> Functions {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let mut line_iter = line.split_ascii_whitespace();
+                if line_iter.next() != Some("fn") {
+                    return None;
+                }
+                let name = line_iter.next()?.strip_suffix(':')?;
+
+                // adding 2 because we are one-indexing the source code
+                Some((index + 2, name.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod functions {
+    use super::Program;
+    use crate::call_stack::Functions;
+
+    #[test]
+    fn no_functions() {
+        let p = Program::new(Box::new([String::from("setb ra 1"), String::from("stop")]));
+
+        let actual = p.functions();
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn indexes_first_instruction() {
+        let p = Program::new(Box::new([
+            String::from("call 3"),
+            String::from("fn inc_ra:"),
+            String::from("  addb ra 1"),
+            String::from("  ret"),
+            String::from("  // fn not_a_function:"),
+            String::from("fn dec_ra:"),
+            String::from("  subb ra 1"),
+            String::from("  ret"),
+        ]));
+        let expected = Functions::from([(3, String::from("inc_ra")), (7, String::from("dec_ra"))]);
+
+        let actual = p.functions();
+
+        assert_eq!(actual, expected);
+    }
 }
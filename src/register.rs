@@ -18,6 +18,18 @@ pub enum Register {
 }
 
 impl Register {
+    /// Every register, in the order they are laid out.
+    pub const ALL: [Register; Register::VARIANT_COUNT] = [
+        Register::A,
+        Register::B,
+        Register::C,
+        Register::D,
+        Register::E,
+        Register::F,
+        Register::G,
+        Register::H,
+    ];
+
     const REG_A: &'static str = "ra";
     const REG_B: &'static str = "rb";
     const REG_C: &'static str = "rc";
@@ -0,0 +1,299 @@
+//! Consistency of the sizes registers and stack values are written and read at,
+//! found without running the program.
+//!
+//! Writing a register at a size clears the bytes above it, so reading it at a wider size afterwards
+//! silently reads zeroes rather than whatever it held before, and popping a value at a wider size
+//! than it was pushed at reads the bytes of the values pushed before it.
+
+use crate::{
+    cfg::ControlFlowGraph,
+    instruction::{Instruction, Read},
+    lint::{Lint, Warning},
+    register::Register,
+};
+
+/// A register or stack value, along with the size it was written at and the line it was written on.
+type Written = Option<(usize, usize)>;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Sizes {
+    /// The narrowest size each register may have last been written at, in the order of `Register::ALL`.
+    registers: [Written; Register::VARIANT_COUNT],
+    /// The size and line of every value on the stack since the start of the function,
+    /// which is unknown once paths with different values meet.
+    stack: Option<Vec<(usize, usize)>>,
+}
+
+impl Sizes {
+    fn new() -> Self {
+        Self {
+            registers: [None; Register::VARIANT_COUNT],
+            stack: Some(Vec::new()),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let mut registers = self.registers;
+        for (register, other) in registers.iter_mut().zip(other.registers) {
+            *register = match (*register, other) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        Self {
+            registers,
+            stack: self
+                .stack
+                .clone()
+                .filter(|stack| Some(stack) == other.stack.as_ref()),
+        }
+    }
+}
+
+/// Describes a size in bytes by the name used in the suffixes of the instructions.
+fn size_name(size: usize) -> &'static str {
+    match size {
+        1 => "byte",
+        2 => "quarter",
+        4 => "half",
+        _ => "word",
+    }
+}
+
+/// Applies the instruction to the sizes, adding warnings for reads at a wider size than written.
+fn apply(
+    sizes: &mut Sizes,
+    line: usize,
+    instruction: &Instruction,
+    mut warnings: Option<&mut Vec<Warning>>,
+) {
+    for (register, size) in instruction.reads() {
+        let written = sizes.registers.get(register as usize).copied().flatten();
+        if let (Some(size), Some((written, on))) = (size, written) {
+            if size > written {
+                if let Some(warnings) = warnings.as_deref_mut() {
+                    warnings.push(Warning {
+                        lint: Lint::RegisterSizeMismatch,
+                        line,
+                        message: format!(
+                            "'{instruction}' reads {register} as a {}, but it may have been written as a {} on line {on}, which cleared its upper bytes",
+                            size_name(size),
+                            size_name(written)
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    match instruction {
+        // called functions and host functions may write any register
+        Instruction::Call(_) | Instruction::External(_) => {
+            sizes.registers = [None; Register::VARIANT_COUNT];
+        }
+        Instruction::Push(push) => {
+            if let Some(stack) = &mut sizes.stack {
+                stack.push((push.size(), line));
+            }
+        }
+        Instruction::Pop(pop) => {
+            let size = pop.size();
+            let top = sizes.stack.as_mut().and_then(Vec::pop);
+            match top {
+                Some((pushed, _)) if pushed == size => {}
+                Some((pushed, on)) => {
+                    if size > pushed {
+                        if let Some(warnings) = warnings {
+                            warnings.push(Warning {
+                                lint: Lint::StackSizeMismatch,
+                                line,
+                                message: format!(
+                                    "'{instruction}' pops a {}, but the value on top of the stack is a {} pushed on line {on}",
+                                    size_name(size),
+                                    size_name(pushed)
+                                ),
+                            });
+                        }
+                    }
+                    // the values below are now partially popped
+                    sizes.stack = None;
+                }
+                None => sizes.stack = None,
+            }
+        }
+        // reading a line pushes as many bytes as the line is long
+        Instruction::Read(Read::Line(_)) => sizes.stack = None,
+        _ => {}
+    }
+
+    if let Some((register, size)) = instruction.writes() {
+        if let Some(written) = sizes.registers.get_mut(register as usize) {
+            *written = Some((size, line));
+        }
+    }
+}
+
+/// Finds reads of registers and pops of stack values at a wider size than they may have been
+/// written at, in the top level and every function of the program.
+pub fn analyse_sizes(cfg: &ControlFlowGraph) -> Vec<Warning> {
+    let mut functions: Vec<Option<usize>> = vec![None];
+    functions.extend(cfg.functions().keys().map(|entry| Some(*entry)));
+
+    let mut warnings = Vec::new();
+    for function in functions {
+        let states = cfg.dataflow(
+            function,
+            Sizes::new(),
+            |sizes, line, instruction| apply(sizes, line, instruction, None),
+            Sizes::join,
+        );
+
+        let mut states: Vec<(usize, Sizes)> = states.into_iter().collect();
+        states.sort_by_key(|(index, _)| *index);
+        for (index, mut sizes) in states {
+            let Some(block) = cfg.blocks().get(index) else {
+                continue;
+            };
+            for line in block.start..=block.end {
+                if let Some(instruction) = cfg.instruction(line) {
+                    apply(&mut sizes, line, instruction, Some(&mut warnings));
+                }
+            }
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.line);
+    warnings
+}
+
+#[cfg(test)]
+mod registers {
+    use super::analyse_sizes;
+    use crate::{cfg::ControlFlowGraph, lint::Lint, preprocess};
+
+    fn warnings(source: &str) -> Vec<(Lint, usize)> {
+        analyse_sizes(&ControlFlowGraph::new(&preprocess(source, &[]).unwrap()))
+            .into_iter()
+            .map(|warning| (warning.lint, warning.line))
+            .collect()
+    }
+
+    #[test]
+    fn same_size() {
+        let source = "setb ra 1\naddb ra 1\nsetw rb 1\naddb rb 1\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn read_wider_than_written() {
+        let source = "setb ra 1\naddw ra 1\naddw ra 1\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::RegisterSizeMismatch, 2)]);
+    }
+
+    #[test]
+    fn operand_read_wider_than_written() {
+        let source = "setq ra 1\nsetw rb ra\ncmph rb ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(
+            actual,
+            vec![
+                (Lint::RegisterSizeMismatch, 2),
+                (Lint::RegisterSizeMismatch, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn written_narrower_on_one_path() {
+        let source = "setw ra 1\ncmpw rb 0\njiz +2\nsetb ra 1\naddw ra 1\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::RegisterSizeMismatch, 5)]);
+    }
+
+    #[test]
+    fn call_forgets_sizes() {
+        let source = "setb ra 1\ncall f\naddw ra 1\nstop\n\nfn f:\n  setw ra 1\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn unsized_read() {
+        let source = "setb ra 3\njmp ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+}
+
+#[cfg(test)]
+mod stack {
+    use super::analyse_sizes;
+    use crate::{cfg::ControlFlowGraph, lint::Lint, preprocess};
+
+    fn warnings(source: &str) -> Vec<(Lint, usize)> {
+        analyse_sizes(&ControlFlowGraph::new(&preprocess(source, &[]).unwrap()))
+            .into_iter()
+            .map(|warning| (warning.lint, warning.line))
+            .collect()
+    }
+
+    #[test]
+    fn same_size() {
+        let source = "pshw 1\npshb 2\npopb ra\npopw rb\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn popped_wider_than_pushed() {
+        let source = "pshb 1\npshb 2\npopq ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackSizeMismatch, 3)]);
+    }
+
+    #[test]
+    fn popped_narrower_than_pushed() {
+        let source = "pshw 1\npopb ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn different_paths_are_unknown() {
+        let source = "cmpw ra 0\njiz +3\npshb 1\njmp +2\npshw 1\npopw ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn within_function() {
+        let source = "call f\nstop\n\nfn f:\n  pshb 1\n  popw ra\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackSizeMismatch, 6)]);
+    }
+}
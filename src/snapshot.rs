@@ -0,0 +1,426 @@
+//! Saving the complete state of the machine to a file, such that execution can later be resumed from it.
+//!
+//! A snapshot records a fingerprint of the program it was taken of, and is only restored against the same program.
+
+use crate::{
+    call_stack::{CallStack, Frame},
+    constant::{Byte, Word},
+    error::InterpreterError,
+    flags::Flags,
+    hook::Machine,
+    registers::Registers,
+    stack::Stack,
+    Interpreter,
+};
+use serde_json::{json, Value};
+use std::{fmt::Write, fs, path::Path};
+
+/// The version of the snapshot format, incremented whenever the format changes.
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+/// Computes a fingerprint of the source code using 64-bit FNV-1a, which is stable across builds.
+pub fn fingerprint(source: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    source.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// The complete state of the machine at a point during execution.
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+    /// The fingerprint of the source code of the program.
+    program: u64,
+    registers: Registers,
+    flags: Flags,
+    program_counter: Word,
+    running: bool,
+    instructions_executed: u64,
+    stack_size: usize,
+    /// The bytes below the stack pointer.
+    stack: Vec<Byte>,
+    call_stack: Vec<Frame>,
+}
+
+impl Snapshot {
+    /// Captures the state of the machine, which has executed the amount of instructions.
+    pub(crate) fn capture(
+        program: u64,
+        machine: &Machine<'_>,
+        running: bool,
+        instructions_executed: u64,
+    ) -> Self {
+        Self {
+            program,
+            registers: *machine.registers,
+            flags: machine.flags.clone(),
+            program_counter: machine.pc as Word,
+            running,
+            instructions_executed,
+            stack_size: machine.stack.bytes().len(),
+            stack: machine
+                .stack
+                .bytes()
+                .get(..machine.stack.sp())
+                .unwrap_or_default()
+                .to_vec(),
+            call_stack: machine.call_stack.frames().to_vec(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let frames: Vec<Value> = self
+            .call_stack
+            .iter()
+            .map(|frame| {
+                json!({
+                    "entry": frame.entry,
+                    "call_site": frame.call_site,
+                    "stack_pointer": frame.stack_pointer,
+                })
+            })
+            .collect();
+
+        json!({
+            "version": SNAPSHOT_VERSION,
+            "program": format!("{:016x}", self.program),
+            "program_counter": self.program_counter,
+            "running": self.running,
+            "instructions_executed": self.instructions_executed,
+            "registers": self.registers,
+            "flags": {
+                "sf": self.flags.sign,
+                "of": self.flags.overflow,
+                "zf": self.flags.zero,
+            },
+            "stack_size": self.stack_size,
+            "stack_pointer": self.stack.len(),
+            "stack": encode_hex(&self.stack),
+            "call_stack": frames,
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self, InterpreterError> {
+        let version = field(value, "version")?
+            .as_u64()
+            .ok_or_else(|| invalid("version"))?;
+        if version != SNAPSHOT_VERSION {
+            return Err(InterpreterError::SnapshotVersionMismatch(version));
+        }
+
+        let program = field(value, "program")?
+            .as_str()
+            .and_then(|s| u64::from_str_radix(s, 16).ok())
+            .ok_or_else(|| invalid("program"))?;
+
+        let registers: Vec<Word> = field(value, "registers")?
+            .as_array()
+            .ok_or_else(|| invalid("registers"))?
+            .iter()
+            .map(|register| register.as_u64().ok_or_else(|| invalid("registers")))
+            .collect::<Result<_, _>>()?;
+        let registers: Registers = registers.try_into().map_err(|_| invalid("registers"))?;
+
+        let flags = field(value, "flags")?;
+        let flags = Flags {
+            sign: bool_field(flags, "sf")?,
+            overflow: bool_field(flags, "of")?,
+            zero: bool_field(flags, "zf")?,
+        };
+
+        let stack = field(value, "stack")?
+            .as_str()
+            .and_then(decode_hex)
+            .ok_or_else(|| invalid("stack"))?;
+        let stack_size = usize_field(value, "stack_size")?;
+        if usize_field(value, "stack_pointer")? != stack.len() || stack.len() > stack_size {
+            return Err(invalid("stack_pointer"));
+        }
+
+        let call_stack = field(value, "call_stack")?
+            .as_array()
+            .ok_or_else(|| invalid("call_stack"))?
+            .iter()
+            .map(|frame| {
+                Ok(Frame {
+                    entry: usize_field(frame, "entry")?,
+                    call_site: usize_field(frame, "call_site")?,
+                    stack_pointer: usize_field(frame, "stack_pointer")?,
+                })
+            })
+            .collect::<Result<_, InterpreterError>>()?;
+
+        Ok(Self {
+            program,
+            registers,
+            flags,
+            program_counter: field(value, "program_counter")?
+                .as_u64()
+                .ok_or_else(|| invalid("program_counter"))?,
+            running: bool_field(value, "running")?,
+            instructions_executed: field(value, "instructions_executed")?
+                .as_u64()
+                .ok_or_else(|| invalid("instructions_executed"))?,
+            stack_size,
+            stack,
+            call_stack,
+        })
+    }
+
+    /// Writes the snapshot to the file as JSON.
+    ///
+    /// # Errors
+    /// Returns [`InterpreterError::FailedSnapshotAccess`] if the file could not be written.
+    pub fn write(&self, path: &Path) -> Result<(), InterpreterError> {
+        fs::write(path, format!("{:#}\n", self.to_json()))
+            .map_err(|err| InterpreterError::FailedSnapshotAccess(err.to_string()))
+    }
+
+    /// Reads a snapshot from the file.
+    ///
+    /// # Errors
+    /// Returns an error if the file could not be read, is not a valid snapshot,
+    /// or was written by a different version of the snapshot format.
+    pub fn read(path: &Path) -> Result<Self, InterpreterError> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| InterpreterError::FailedSnapshotAccess(err.to_string()))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|err| InterpreterError::InvalidSnapshot(err.to_string()))?;
+
+        Self::from_json(&value)
+    }
+}
+
+fn invalid(name: &str) -> InterpreterError {
+    InterpreterError::InvalidSnapshot(format!("missing or invalid field '{name}'"))
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, InterpreterError> {
+    value.get(name).ok_or_else(|| invalid(name))
+}
+
+fn bool_field(value: &Value, name: &str) -> Result<bool, InterpreterError> {
+    field(value, name)?.as_bool().ok_or_else(|| invalid(name))
+}
+
+fn usize_field(value: &Value, name: &str) -> Result<usize, InterpreterError> {
+    field(value, name)?
+        .as_u64()
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| invalid(name))
+}
+
+/// Encodes the bytes as lowercase hexadecimal digits, two per byte.
+pub(crate) fn encode_hex(bytes: &[Byte]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn decode_hex(s: &str) -> Option<Vec<Byte>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|index| {
+            s.get(index..index + 2)
+                .and_then(|pair| Byte::from_str_radix(pair, 16).ok())
+        })
+        .collect()
+}
+
+impl Interpreter {
+    /// Captures the complete state of the machine, which is running the source code.
+    #[must_use]
+    pub fn snapshot(&self, source_code: &str) -> Snapshot {
+        Snapshot::capture(
+            fingerprint(source_code),
+            &self.machine(),
+            self.running,
+            self.config.instructions_executed,
+        )
+    }
+
+    /// Replaces the state of the machine with the snapshot, such that running the source code resumes from it.
+    ///
+    /// # Errors
+    /// Returns [`InterpreterError::SnapshotProgramMismatch`] if the snapshot was taken of a different source code.
+    pub fn restore(
+        &mut self,
+        snapshot: &Snapshot,
+        source_code: &str,
+    ) -> Result<(), InterpreterError> {
+        if snapshot.program != fingerprint(source_code) {
+            return Err(InterpreterError::SnapshotProgramMismatch);
+        }
+
+        let mut stack = Stack::new(snapshot.stack_size);
+        for byte in &snapshot.stack {
+            stack.push(*byte).map_err(|_| {
+                InterpreterError::InvalidSnapshot(String::from("the stack overflows"))
+            })?;
+        }
+
+        self.registers = snapshot.registers;
+        self.flags = snapshot.flags.clone();
+        self.program_counter = snapshot.program_counter;
+        self.running = snapshot.running;
+        self.config.instructions_executed = snapshot.instructions_executed;
+        self.stack = stack;
+        self.call_stack = CallStack::from_frames(snapshot.call_stack.clone());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod round_trip {
+    use super::Snapshot;
+    use crate::{bytecode::Assembled, error::InterpreterError, hook::Snapshotter, Interpreter};
+    use std::{fs, path::PathBuf};
+
+    fn file(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("kasm_snapshot_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        directory.join(name)
+    }
+
+    const PROGRAM: &str = "setb ra 5
+pshb ra
+call set_rb
+popb rc
+stop
+fn set_rb:
+  setb rb 7
+  ret";
+
+    /// Runs the program until it is about to execute the line, and takes a snapshot there.
+    fn snapshot_at(line: usize, name: &str) -> Result<Snapshot, InterpreterError> {
+        let path = file(name);
+        let mut i = Interpreter::new_test();
+        i.config
+            .hooks
+            .push(Box::new(Snapshotter::new(path.clone(), line)));
+        i.run(PROGRAM)?;
+
+        Snapshot::read(&path)
+    }
+
+    #[test]
+    fn file_round_trip() -> Result<(), InterpreterError> {
+        let path = file("round_trip.json");
+        let mut i = Interpreter::new_test();
+        i.run(PROGRAM)?;
+        let expected = i.snapshot(PROGRAM);
+
+        expected.write(&path)?;
+        let actual = Snapshot::read(&path)?;
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn taken_before_line() -> Result<(), InterpreterError> {
+        let actual = snapshot_at(8, "before_line.json")?;
+
+        assert_eq!(actual.program_counter, 8);
+        assert_eq!(actual.instructions_executed, 4);
+        assert_eq!(actual.registers.first(), Some(&5));
+        assert_eq!(actual.stack.len(), 9);
+        assert_eq!(actual.call_stack.len(), 1);
+        assert!(actual.running);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resumed_execution_matches() -> Result<(), InterpreterError> {
+        let mut uninterrupted = Interpreter::new_test();
+        uninterrupted.run(PROGRAM)?;
+        let snapshot = snapshot_at(8, "resumed.json")?;
+
+        let mut resumed = Interpreter::new_test();
+        resumed.restore(&snapshot, PROGRAM)?;
+        resumed.run(PROGRAM)?;
+
+        assert_eq!(resumed.snapshot(PROGRAM), uninterrupted.snapshot(PROGRAM));
+
+        Ok(())
+    }
+
+    #[test]
+    fn modified_program_refused() {
+        let snapshot = Interpreter::new_test().snapshot(PROGRAM);
+        let mut i = Interpreter::new_test();
+
+        let actual = i.restore(&snapshot, &PROGRAM.replace("setb ra 5", "setb ra 6"));
+
+        assert_eq!(actual, Err(InterpreterError::SnapshotProgramMismatch));
+    }
+
+    #[test]
+    fn assembled_program_without_debug_info_refused() -> Result<(), InterpreterError> {
+        let assembled = |program: &str| Assembled::assemble(program, false).map(|a| a.to_bytes());
+        let mut i = Interpreter::new_test();
+        i.run_assembled(&assembled(PROGRAM)?)?;
+        let mut restored = Interpreter::new_test();
+        restored.config.restore =
+            Some(i.snapshot(&Assembled::from_bytes(&assembled(PROGRAM)?)?.source()));
+
+        let actual =
+            restored.run_assembled(&assembled(&PROGRAM.replace("setb ra 5", "setb ra 6"))?);
+
+        assert_eq!(actual, Err(InterpreterError::SnapshotProgramMismatch));
+        Ok(())
+    }
+
+    #[test]
+    fn version_mismatch_error() {
+        let path = file("version.json");
+        fs::write(&path, r#"{"version": 99}"#).unwrap();
+
+        let actual = Snapshot::read(&path);
+
+        assert_eq!(actual, Err(InterpreterError::SnapshotVersionMismatch(99)));
+    }
+
+    #[test]
+    fn invalid_stack_error() {
+        let path = file("invalid_stack.json");
+        Interpreter::new_test()
+            .snapshot(PROGRAM)
+            .write(&path)
+            .unwrap();
+        let content = fs::read_to_string(&path)
+            .unwrap()
+            .replace(r#""stack": """#, r#""stack": "0""#);
+        fs::write(&path, content).unwrap();
+
+        let actual = Snapshot::read(&path);
+
+        assert_eq!(
+            actual,
+            Err(InterpreterError::InvalidSnapshot(String::from(
+                "missing or invalid field 'stack'"
+            )))
+        );
+    }
+}
+
+#[cfg(test)]
+mod fingerprint {
+    use super::fingerprint;
+
+    #[test]
+    fn known_values() {
+        assert_eq!(fingerprint(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -23,6 +23,11 @@ impl Stack {
         self.pointer as usize
     }
 
+    /// Gets every byte of the stack, including the ones above the stack pointer.
+    pub fn bytes(&self) -> &[Byte] {
+        &self.bytes
+    }
+
     #[expect(
         clippy::indexing_slicing,
         reason = "the indexing in the for loop is guarded by the condition at the start of the function"
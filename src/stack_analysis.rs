@@ -0,0 +1,522 @@
+//! The stack usage of every function, found without running the program.
+//!
+//! Depths are counted in bytes from the start of the function, which is right after its return
+//! address has been pushed, and are tracked as the range of depths over every path reaching a line.
+//! Calls have no effect on the depth of the caller, as the return address they push is popped by
+//! the return of the called function, which is checked to be balanced by its own analysis.
+
+use crate::{
+    cfg::ControlFlowGraph,
+    cli::{parse_stack_size, read_program_file, StackArguments},
+    constant::{Byte, Half, Quarter, Word},
+    error::InterpreterError,
+    instruction::{Instruction, PrintStack, Read},
+    lint::{Lint, Warning},
+    operand::Operand,
+    preprocess,
+};
+use std::collections::{HashMap, HashSet};
+
+/// The range of depths of the stack over every path reaching a line,
+/// where a missing bound means the depth may shrink or grow without limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Depth {
+    min: Option<isize>,
+    max: Option<isize>,
+}
+
+impl Depth {
+    const ZERO: Self = Self {
+        min: Some(0),
+        max: Some(0),
+    };
+
+    fn add(self, amount: isize) -> Self {
+        Self {
+            min: self.min.map(|min| min + amount),
+            max: self.max.map(|max| max + amount),
+        }
+    }
+
+    fn join(self, other: Self) -> Self {
+        Self {
+            min: self.min.zip(other.min).map(|(a, b)| a.min(b)),
+            max: self.max.zip(other.max).map(|(a, b)| a.max(b)),
+        }
+    }
+
+    /// Drops the bounds which differ from the previous depth.
+    fn widen(self, previous: Self) -> Self {
+        Self {
+            min: self.min.filter(|_| self.min == previous.min),
+            max: self.max.filter(|_| self.max == previous.max),
+        }
+    }
+}
+
+/// The stack usage of a function, in bytes, where a missing usage is unbounded or unknown.
+#[derive(Debug, PartialEq)]
+pub struct FunctionStack {
+    /// The first line of the function, or none at the top level.
+    pub function: Option<usize>,
+    /// The most bytes the function pushes on top of its return address.
+    pub own: Option<usize>,
+    /// The most bytes used by the function along with the functions it calls,
+    /// including their return addresses.
+    pub total: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StackAnalysis {
+    /// The usage of the top level, followed by every function in order.
+    pub functions: Vec<FunctionStack>,
+    pub warnings: Vec<Warning>,
+}
+
+impl StackAnalysis {
+    /// Gets the most bytes used by the program, if it is bounded.
+    #[must_use]
+    pub fn max_usage(&self) -> Option<usize> {
+        self.functions.first().and_then(|function| function.total)
+    }
+}
+
+/// What is found by analysing a single function.
+struct Frame {
+    own: Option<usize>,
+    /// The depth right before every call, along with the first line of the called function.
+    calls: Vec<(Option<isize>, Option<usize>)>,
+}
+
+/// Gets the depth after executing the instruction.
+fn step(depth: Depth, instruction: &Instruction) -> Depth {
+    match instruction {
+        Instruction::Push(push) => depth.add(push.size().cast_signed()),
+        Instruction::Pop(pop) => depth.add(-pop.size().cast_signed()),
+        // reading a line pushes as many bytes as the line is long
+        Instruction::Read(Read::Line(_)) => Depth {
+            min: depth.min,
+            max: None,
+        },
+        _ => depth,
+    }
+}
+
+/// Gets the amount of bytes the instruction needs on the stack, if it is known.
+fn needed(instruction: &Instruction) -> Option<isize> {
+    let (amount, size) = match instruction {
+        Instruction::Pop(pop) => return isize::try_from(pop.size()).ok(),
+        Instruction::PrintStack(print) => match print {
+            PrintStack::Byte(amount) | PrintStack::Str(amount) => (amount, size_of::<Byte>()),
+            PrintStack::Quarter(amount) => (amount, size_of::<Quarter>()),
+            PrintStack::Half(amount) => (amount, size_of::<Half>()),
+            PrintStack::Word(amount) => (amount, size_of::<Word>()),
+        },
+        _ => return None,
+    };
+
+    let Operand::Immediate(amount) = amount else {
+        return None;
+    };
+    isize::try_from(*amount)
+        .ok()?
+        .checked_mul(isize::try_from(size).ok()?)
+}
+
+/// Finds the range of depths at the start of every block of the function reached from its entry.
+fn block_depths(cfg: &ControlFlowGraph, function: Option<usize>) -> HashMap<usize, Depth> {
+    cfg.dataflow_with_widening(
+        function,
+        Depth::ZERO,
+        |depth, _, instruction| *depth = step(*depth, instruction),
+        |previous, depth| previous.join(*depth),
+        |previous, joined| joined.widen(*previous),
+    )
+}
+
+/// Analyses the function, adding warnings for every line which may underflow the stack
+/// or return with an unbalanced stack.
+fn analyse_function(
+    cfg: &ControlFlowGraph,
+    function: Option<usize>,
+    warnings: &mut Vec<Warning>,
+) -> Frame {
+    let name = cfg.function_name(function);
+    let mut own = Some(0);
+    let mut calls = Vec::new();
+
+    let mut depths: Vec<(usize, Depth)> = block_depths(cfg, function).into_iter().collect();
+    depths.sort_by_key(|(index, _)| *index);
+
+    for (index, mut depth) in depths {
+        let Some(block) = cfg.blocks().get(index) else {
+            continue;
+        };
+        for line in block.start..=block.end {
+            let Some(instruction) = cfg.instruction(line) else {
+                continue;
+            };
+
+            if let Some(needed) = needed(instruction) {
+                if depth.min.is_none_or(|min| min < needed) {
+                    let message = match (function, depth.min) {
+                        (Some(_), _) => {
+                            format!("'{instruction}' may need more bytes than were pushed since '{name}' was called")
+                        }
+                        (None, Some(min)) => format!(
+                            "'{instruction}' needs {} on the stack but may only have {}",
+                            amount_of_bytes(needed.unsigned_abs()),
+                            min.max(0)
+                        ),
+                        (None, None) => format!(
+                            "'{instruction}' needs {} on the stack but may have fewer",
+                            amount_of_bytes(needed.unsigned_abs())
+                        ),
+                    };
+                    warnings.push(Warning {
+                        lint: Lint::StackUnderflow,
+                        line,
+                        message,
+                    });
+                }
+            }
+
+            match instruction {
+                Instruction::Return if function.is_some() && depth != Depth::ZERO => {
+                    let message = match (depth.min, depth.max) {
+                        (Some(min), Some(max)) if min == max && min > 0 => {
+                            format!("'{name}' returns with {} left on the stack", amount_of_bytes(min.unsigned_abs()))
+                        }
+                        (Some(min), Some(max)) if min == max => format!(
+                            "'{name}' returns having taken {} from its caller",
+                            amount_of_bytes(min.unsigned_abs())
+                        ),
+                        _ => format!(
+                            "'{name}' may return with a different amount of bytes on the stack than it was called with"
+                        ),
+                    };
+                    warnings.push(Warning {
+                        lint: Lint::UnbalancedReturn,
+                        line,
+                        message,
+                    });
+                }
+                Instruction::Call(_) => {
+                    let callee = cfg
+                        .call(line)
+                        .and_then(|call| call.callee)
+                        .and_then(|callee| cfg.function(callee));
+                    calls.push((depth.max, callee));
+                }
+                _ => {}
+            }
+
+            depth = step(depth, instruction);
+            own = own.zip(depth.max).map(|(own, max)| own.max(max));
+        }
+    }
+
+    Frame {
+        own: own.map(|own| own.max(0).unsigned_abs()),
+        calls,
+    }
+}
+
+/// Gets the most bytes used by the function along with the functions it calls,
+/// which is unbounded if the function is recursive.
+fn total(
+    function: Option<usize>,
+    frames: &HashMap<Option<usize>, Frame>,
+    visiting: &mut HashSet<Option<usize>>,
+) -> Option<usize> {
+    let frame = frames.get(&function)?;
+    if !visiting.insert(function) {
+        return None;
+    }
+
+    let mut usage = frame.own;
+    for (depth, callee) in &frame.calls {
+        let called = depth.zip(*callee).and_then(|(depth, callee)| {
+            let callee = total(Some(callee), frames, visiting)?;
+            Some(depth.max(0).unsigned_abs() + size_of::<Word>() + callee)
+        });
+        usage = usage.zip(called).map(|(usage, called)| usage.max(called));
+    }
+
+    visiting.remove(&function);
+    usage
+}
+
+/// Analyses the stack usage of the top level and every function of the program.
+#[must_use]
+pub fn analyse_stack(cfg: &ControlFlowGraph) -> StackAnalysis {
+    let mut functions: Vec<Option<usize>> = vec![None];
+    functions.extend(cfg.functions().keys().map(|entry| Some(*entry)));
+
+    let mut warnings = Vec::new();
+    let frames: HashMap<Option<usize>, Frame> = functions
+        .iter()
+        .map(|function| (*function, analyse_function(cfg, *function, &mut warnings)))
+        .collect();
+
+    let functions = functions
+        .into_iter()
+        .map(|function| FunctionStack {
+            function,
+            own: frames.get(&function).and_then(|frame| frame.own),
+            total: total(function, &frames, &mut HashSet::new()),
+        })
+        .collect();
+    warnings.sort_by_key(|warning| warning.line);
+
+    StackAnalysis {
+        functions,
+        warnings,
+    }
+}
+
+/// Describes an amount of bytes, in singular for a single byte.
+fn amount_of_bytes(amount: usize) -> String {
+    if amount == 1 {
+        String::from("1 byte")
+    } else {
+        format!("{amount} bytes")
+    }
+}
+
+/// Describes an amount of bytes, which is unknown if it is unbounded.
+fn bytes(amount: Option<usize>) -> String {
+    amount.map_or_else(|| String::from("unknown"), amount_of_bytes)
+}
+
+/// Prints the stack usage of every function of the program and any warnings,
+/// along with the most bytes used by the program compared to the size of the stack.
+///
+/// Returns whether there are no warnings and the program is not known to exceed the stack.
+///
+/// # Errors
+/// Returns an error if the stack size is invalid, or the program cannot be read or preprocessed.
+pub fn stack_usage(args: &StackArguments) -> Result<bool, InterpreterError> {
+    let stack_size = parse_stack_size(&args.stack_size).map_err(InterpreterError::Argument)?;
+    let source_code = read_program_file(&args.file_name).map_err(InterpreterError::Argument)?;
+    let program = preprocess(&source_code, &[])?;
+    let cfg = ControlFlowGraph::new(&program);
+    let analysis = analyse_stack(&cfg);
+
+    for function in &analysis.functions {
+        println!(
+            "{}: {}, {} including calls",
+            cfg.function_name(function.function),
+            bytes(function.own),
+            bytes(function.total)
+        );
+    }
+    for warning in &analysis.warnings {
+        println!("{warning}");
+    }
+
+    let fits = if let Some(usage) = analysis.max_usage() {
+        println!("maximum stack usage is {usage} of {stack_size} bytes");
+        usage <= stack_size
+    } else {
+        println!(
+            "maximum stack usage is unknown, and may exceed the {stack_size} bytes of the stack"
+        );
+        true
+    };
+
+    Ok(fits && analysis.warnings.is_empty())
+}
+
+#[cfg(test)]
+mod usage {
+    use super::{analyse_stack, FunctionStack};
+    use crate::{cfg::ControlFlowGraph, preprocess};
+
+    fn usage(source: &str) -> Vec<FunctionStack> {
+        analyse_stack(&ControlFlowGraph::new(&preprocess(source, &[]).unwrap())).functions
+    }
+
+    #[test]
+    fn top_level() {
+        let source = "pshw 1\npshb 2\npopb ra\npopw rb\npshq 3\nstop\n";
+        let expected = vec![FunctionStack {
+            function: None,
+            own: Some(9),
+            total: Some(9),
+        }];
+
+        let actual = usage(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn calls_add_return_address_and_callee() {
+        let source = "pshb 1\ncall f\npopb ra\nstop\n\nfn f:\n  pshh 1\n  call g\n  poph ra\n  ret\n\nfn g:\n  ret\n";
+        let expected = vec![
+            FunctionStack {
+                function: None,
+                own: Some(1),
+                total: Some(1 + 8 + 4 + 8),
+            },
+            FunctionStack {
+                function: Some(7),
+                own: Some(4),
+                total: Some(4 + 8),
+            },
+            FunctionStack {
+                function: Some(13),
+                own: Some(0),
+                total: Some(0),
+            },
+        ];
+
+        let actual = usage(source);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn branches_use_deepest_path() {
+        let source = "cmpb ra 0\njiz +3\npshw 1\npopw ra\npshb 1\npopb ra\nstop\n";
+        let expected = Some(8);
+
+        let actual = usage(source).first().and_then(|function| function.own);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn growing_loop_is_unbounded() {
+        let source = "pshb 1\ncmpb ra 0\njnz -2\nstop\n";
+        let expected = None;
+
+        let actual = usage(source).first().and_then(|function| function.own);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn balanced_loop_is_bounded() {
+        let source = "pshb 1\npopb ra\ncmpb ra 0\njnz -3\nstop\n";
+        let expected = Some(1);
+
+        let actual = usage(source).first().and_then(|function| function.own);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn recursion_is_unbounded() {
+        let source = "call f\nstop\n\nfn f:\n  call f\n  ret\n";
+
+        let actual: Vec<Option<usize>> = usage(source).iter().map(|f| f.total).collect();
+
+        assert_eq!(actual, vec![None, None]);
+    }
+}
+
+#[cfg(test)]
+mod warnings {
+    use super::analyse_stack;
+    use crate::{cfg::ControlFlowGraph, lint::Lint, preprocess};
+
+    fn warnings(source: &str) -> Vec<(Lint, usize)> {
+        analyse_stack(&ControlFlowGraph::new(&preprocess(source, &[]).unwrap()))
+            .warnings
+            .into_iter()
+            .map(|warning| (warning.lint, warning.line))
+            .collect()
+    }
+
+    #[test]
+    fn balanced_program() {
+        let source = "pshw 1\ncall f\npopw ra\nprsb 0\nstop\n\nfn f:\n  pshb 1\n  popb ra\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn pop_from_empty_stack() {
+        let source = "pshb 1\npopq ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackUnderflow, 2)]);
+    }
+
+    #[test]
+    fn print_more_than_pushed() {
+        let source = "pshh 1\nprsh 1\nprsw 1\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackUnderflow, 3)]);
+    }
+
+    #[test]
+    fn underflow_on_one_path() {
+        let source = "cmpb ra 0\njiz +2\npshb 1\npopb ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackUnderflow, 4)]);
+    }
+
+    #[test]
+    fn pop_return_address() {
+        let source = "call f\nstop\n\nfn f:\n  popw ra\n  pshw ra\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackUnderflow, 5)]);
+    }
+
+    #[test]
+    fn bytes_left_on_return() {
+        let source = "call f\nstop\n\nfn f:\n  pshb 1\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::UnbalancedReturn, 6)]);
+    }
+
+    #[test]
+    fn single_byte_in_messages() {
+        let source = "popb ra\ncall f\nstop\n\nfn f:\n  pshb 1\n  ret\n";
+        let expected = vec![
+            String::from("'popb ra' needs 1 byte on the stack but may only have 0"),
+            String::from("'f' returns with 1 byte left on the stack"),
+        ];
+
+        let actual: Vec<String> =
+            analyse_stack(&ControlFlowGraph::new(&preprocess(source, &[]).unwrap()))
+                .warnings
+                .into_iter()
+                .map(|warning| warning.message)
+                .collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn unbalanced_on_one_path() {
+        let source = "call f\nstop\n\nfn f:\n  pshb 1\n  cmpb ra 0\n  jiz +2\n  popb ra\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::UnbalancedReturn, 9)]);
+    }
+
+    #[test]
+    fn read_line_is_unknown() {
+        let source = "rdl ra\nprss 4\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![(Lint::StackUnderflow, 2)]);
+    }
+}
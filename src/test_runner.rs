@@ -0,0 +1,423 @@
+//! Discovery and execution of test functions, which are functions named 'test_*'.
+//!
+//! Every test runs in a fresh interpreter, as if it was called from the top level of its program.
+//! Returning from the test function or stopping passes the test, while any error fails it.
+
+use crate::{
+    cli::{parse_stack_size, read_program_file, Configuration, LimitArguments, TestArguments},
+    constant::Word,
+    error::InterpreterError,
+    preprocess,
+    program::Program,
+    utils::SharedBuffer,
+    Interpreter,
+};
+use serde_json::{json, Value};
+use std::{fmt::Write, fs, path::PathBuf, time::Instant};
+
+pub(crate) const TEST_PREFIX: &str = "test_";
+
+/// The test functions of a single program.
+struct TestFile {
+    file: PathBuf,
+    program: Program,
+    /// The first line and name of every test function.
+    tests: Vec<(usize, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Passed,
+    Failed(String),
+}
+
+#[derive(Debug, PartialEq)]
+struct TestResult {
+    /// The file and function name, separated by '::'.
+    name: String,
+    outcome: Outcome,
+    instructions: u64,
+    /// Everything the test printed.
+    output: String,
+}
+
+impl TestResult {
+    fn passed(&self) -> bool {
+        self.outcome == Outcome::Passed
+    }
+
+    fn to_json(&self) -> Value {
+        let error = match &self.outcome {
+            Outcome::Passed => None,
+            Outcome::Failed(error) => Some(error),
+        };
+
+        json!({
+            "name": self.name,
+            "passed": self.passed(),
+            "instructions": self.instructions,
+            "error": error,
+            "output": self.output,
+        })
+    }
+}
+
+impl Interpreter {
+    /// Executes the function starting at the entry line as if it was called from the top level.
+    ///
+    /// The return address points just past the end of the program, such that returning from the function ends the test.
+    fn run_test(&mut self, program: &Program, entry: usize) -> Result<(), InterpreterError> {
+        let start = Instant::now();
+        let end = program.lines().len() as Word + 1;
+        self.functions = program.functions();
+        self.stack
+            .push::<Word>(end)
+            .map_err(|e| InterpreterError::Execute(entry, e))?;
+        self.program_counter = entry as Word;
+
+        while self.running && self.program_counter != end {
+            self.check_limits(start)?;
+            self.advance(program)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the first line and name of every test function in the program.
+fn discover(program: &Program) -> Vec<(usize, String)> {
+    program
+        .functions()
+        .into_iter()
+        .filter(|(_, name)| name.starts_with(TEST_PREFIX))
+        .collect()
+}
+
+fn run_test(
+    stack_size: usize,
+    limits: LimitArguments,
+    name: String,
+    program: &Program,
+    entry: usize,
+) -> TestResult {
+    let buffer = SharedBuffer::default();
+    let mut config = Configuration::captured(buffer.clone());
+    config.instruction_limit = limits.max_instructions;
+    config.time_limit = limits.time_limit;
+    let mut i = Interpreter::with_config(stack_size, config);
+
+    let outcome = match i.run_test(program, entry) {
+        Ok(()) => Outcome::Passed,
+        Err(err) => Outcome::Failed(err.to_string()),
+    };
+    let output = String::from_utf8_lossy(&buffer.borrow()).into_owned();
+
+    TestResult {
+        name,
+        outcome,
+        instructions: i.config.instructions_executed,
+        output,
+    }
+}
+
+/// Formats the result of a single test as it is printed right after the test has run.
+fn result_line(result: &TestResult) -> String {
+    let status = if result.passed() { "ok" } else { "FAILED" };
+    format!(
+        "test {} ... {status} ({} instructions)",
+        result.name, result.instructions
+    )
+}
+
+/// Formats the details of the failed tests and the final counts.
+fn summary(results: &[TestResult], filtered_out: usize) -> String {
+    let failed: Vec<&TestResult> = results.iter().filter(|r| !r.passed()).collect();
+    let mut summary = String::new();
+
+    if !failed.is_empty() {
+        summary.push_str("\nfailures:\n");
+        for result in &failed {
+            let _ = writeln!(summary, "\n---- {} ----", result.name);
+            if let Outcome::Failed(error) = &result.outcome {
+                let _ = writeln!(summary, "{error}");
+            }
+            if !result.output.is_empty() {
+                let _ = write!(summary, "output:\n{}", result.output);
+                if !result.output.ends_with('\n') {
+                    summary.push('\n');
+                }
+            }
+        }
+
+        summary.push_str("\nfailures:\n");
+        for result in &failed {
+            let _ = writeln!(summary, "    {}", result.name);
+        }
+    }
+
+    let status = if failed.is_empty() { "ok" } else { "FAILED" };
+    let _ = writeln!(
+        summary,
+        "\ntest result: {status}. {} passed; {} failed; {filtered_out} filtered out",
+        results.len() - failed.len(),
+        failed.len(),
+    );
+
+    summary
+}
+
+/// Runs all tests matching the filter, printing the results as they finish.
+///
+/// Returns whether every test passed.
+///
+/// # Errors
+/// Returns an error if a program file cannot be read or preprocessed, or if the JSON report cannot be written.
+pub fn run_tests(args: TestArguments) -> Result<bool, InterpreterError> {
+    let stack_size = parse_stack_size(&args.stack_size).map_err(InterpreterError::Argument)?;
+    let mut files = Vec::with_capacity(args.files.len());
+    let mut filtered_out = 0;
+
+    for file in args.files {
+        let source = read_program_file(&file).map_err(InterpreterError::Argument)?;
+        let program = preprocess(&source, &[])?;
+
+        let mut tests = discover(&program);
+        let total = tests.len();
+        tests.retain(|(_, name)| {
+            let full_name = format!("{}::{name}", file.display());
+            args.filter
+                .as_ref()
+                .is_none_or(|filter| full_name.contains(filter))
+        });
+        filtered_out += total - tests.len();
+
+        files.push(TestFile {
+            file,
+            program,
+            tests,
+        });
+    }
+
+    let count: usize = files.iter().map(|f| f.tests.len()).sum();
+    println!("running {count} tests");
+
+    let mut results = Vec::with_capacity(count);
+    for test_file in &files {
+        for (entry, function) in &test_file.tests {
+            let name = format!("{}::{function}", test_file.file.display());
+            let result = run_test(stack_size, args.limits, name, &test_file.program, *entry);
+            println!("{}", result_line(&result));
+            results.push(result);
+        }
+    }
+
+    print!("{}", summary(&results, filtered_out));
+
+    if let Some(path) = args.json {
+        let passed = results.iter().filter(|r| r.passed()).count();
+        let report = json!({
+            "tests": results.iter().map(TestResult::to_json).collect::<Vec<Value>>(),
+            "passed": passed,
+            "failed": results.len() - passed,
+            "filtered_out": filtered_out,
+        });
+        fs::write(path, format!("{report}\n"))
+            .map_err(|err| InterpreterError::FailedReport(err.to_string()))?;
+    }
+
+    Ok(results.iter().all(TestResult::passed))
+}
+
+#[cfg(test)]
+mod discover {
+    use super::discover;
+    use crate::preprocess;
+
+    #[test]
+    fn only_test_functions() {
+        let program = preprocess(
+            "stop
+
+fn helper:
+  ret
+
+fn test_first:
+  ret
+
+fn test_second:
+  stop",
+            &[],
+        )
+        .unwrap();
+        let expected = vec![
+            (7, String::from("test_first")),
+            (10, String::from("test_second")),
+        ];
+
+        let actual = discover(&program);
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod run_test {
+    use super::{run_test, Outcome, TestResult};
+    use crate::{cli::LimitArguments, constant::TEST_STACK_SIZE, preprocess};
+
+    const PROGRAM: &str = "stop
+
+fn add_two:
+  addb ra 2
+  ret
+
+fn test_ret:
+  setb ra 1
+  call add_two
+  prrb ra
+  ret
+
+fn test_stop:
+  stop
+
+fn test_error:
+  divb ra 0
+  ret";
+
+    fn run(entry: usize, name: &str) -> TestResult {
+        let program = preprocess(PROGRAM, &[]).unwrap();
+        run_test(
+            TEST_STACK_SIZE,
+            LimitArguments::default(),
+            name.to_string(),
+            &program,
+            entry,
+        )
+    }
+
+    #[test]
+    fn passes_on_return() {
+        let expected = TestResult {
+            name: String::from("test_ret"),
+            outcome: Outcome::Passed,
+            instructions: 6,
+            output: String::from("ra: 3\n"),
+        };
+
+        let actual = run(8, "test_ret");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn passes_on_stop() {
+        let actual = run(14, "test_stop");
+
+        assert_eq!(actual.outcome, Outcome::Passed);
+        assert_eq!(actual.instructions, 1);
+    }
+
+    #[test]
+    fn fails_on_execute_error() {
+        let actual = run(17, "test_error");
+
+        assert!(
+            matches!(&actual.outcome, Outcome::Failed(error) if error.starts_with("failed to execute line 17"))
+        );
+    }
+
+    #[test]
+    fn fails_on_instruction_limit() {
+        let program = preprocess("stop\n\nfn test_loop:\n  jmp 4", &[]).unwrap();
+        let limits = LimitArguments {
+            max_instructions: Some(100),
+            time_limit: None,
+        };
+
+        let actual = run_test(
+            TEST_STACK_SIZE,
+            limits,
+            String::from("test_loop"),
+            &program,
+            4,
+        );
+
+        assert_eq!(
+            actual.outcome,
+            Outcome::Failed(String::from(
+                "stopped at line 4 after reaching the limit of 100 executed instructions"
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod summary {
+    use super::{result_line, summary, Outcome, TestResult};
+
+    fn results() -> Vec<TestResult> {
+        vec![
+            TestResult {
+                name: String::from("math.kasm::test_add"),
+                outcome: Outcome::Passed,
+                instructions: 4,
+                output: String::new(),
+            },
+            TestResult {
+                name: String::from("math.kasm::test_div"),
+                outcome: Outcome::Failed(String::from(
+                    "failed to execute line 9: division by zero",
+                )),
+                instructions: 2,
+                output: String::from("7\n"),
+            },
+        ]
+    }
+
+    #[test]
+    fn result_lines() {
+        let expected = [
+            "test math.kasm::test_add ... ok (4 instructions)",
+            "test math.kasm::test_div ... FAILED (2 instructions)",
+        ];
+
+        let actual: Vec<String> = results().iter().map(result_line).collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn with_failures() {
+        let expected = [
+            "",
+            "failures:",
+            "",
+            "---- math.kasm::test_div ----",
+            "failed to execute line 9: division by zero",
+            "output:",
+            "7",
+            "",
+            "failures:",
+            "    math.kasm::test_div",
+            "",
+            "test result: FAILED. 1 passed; 1 failed; 3 filtered out",
+            "",
+        ]
+        .join("\n");
+
+        let actual = summary(&results(), 3);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn all_passed() {
+        let mut results = results();
+        results.pop();
+        let expected = "\ntest result: ok. 1 passed; 0 failed; 0 filtered out\n";
+
+        let actual = summary(&results, 0);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,308 @@
+//! Reads of registers which may not have been written yet, found without running the program.
+//!
+//! Registers are tracked as the set which may be unwritten along some path reaching a line.
+//! The registers unwritten at the start of a function are those which may be unwritten at any
+//! of its calls, and every register is unwritten at the start of the top level and of test functions,
+//! as each test is run on a new machine. A call is assumed to write every register the called function,
+//! or any function it calls in turn, writes anywhere.
+
+use crate::{
+    cfg::ControlFlowGraph,
+    instruction::Instruction,
+    lint::{Lint, Warning},
+    register::Register,
+    test_runner::TEST_PREFIX,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A set of registers, where the register at index `i` of `Register::ALL` is in the set if bit `i` is set.
+type Registers = u32;
+
+const ALL: Registers = (1 << Register::VARIANT_COUNT) - 1;
+
+fn bit(register: Register) -> Registers {
+    1 << register as usize
+}
+
+/// Gets the registers every function may write, including those written by the functions it calls.
+fn written(cfg: &ControlFlowGraph) -> HashMap<Option<usize>, Registers> {
+    let mut own: HashMap<Option<usize>, Registers> = HashMap::new();
+    let mut callees: HashMap<Option<usize>, HashSet<Option<usize>>> = HashMap::new();
+    for (line, instruction) in cfg.instructions() {
+        let function = cfg.function(line);
+        let registers = own.entry(function).or_default();
+        if let Some((register, _)) = instruction.writes() {
+            *registers |= bit(register);
+        }
+
+        match instruction {
+            // host functions may write any register, as may the target of a computed call
+            Instruction::External(_) | Instruction::Call(_) => {
+                let callee = cfg.call(line).and_then(|call| call.callee);
+                match callee {
+                    Some(callee) => {
+                        callees
+                            .entry(function)
+                            .or_default()
+                            .insert(cfg.function(callee));
+                    }
+                    None => *registers = ALL,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // spread the writes of every function to its callers, until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (caller, called) in &callees {
+            let registers = called
+                .iter()
+                .filter_map(|callee| own.get(callee))
+                .fold(0, |registers, callee| registers | callee);
+            let caller = own.entry(*caller).or_default();
+            if *caller | registers != *caller {
+                *caller |= registers;
+                changed = true;
+            }
+        }
+    }
+
+    own
+}
+
+/// Applies the instruction to the unwritten registers, adding a warning for every read of one.
+fn apply(
+    cfg: &ControlFlowGraph,
+    written: &HashMap<Option<usize>, Registers>,
+    unwritten: &mut Registers,
+    line: usize,
+    instruction: &Instruction,
+    mut warnings: Option<&mut Vec<Warning>>,
+) {
+    for (register, _) in instruction.reads() {
+        if *unwritten & bit(register) == 0 {
+            continue;
+        }
+
+        if let Some(warnings) = warnings.as_deref_mut() {
+            warnings.push(Warning {
+                lint: Lint::UninitialisedRegister,
+                line,
+                message: format!(
+                    "'{instruction}' reads {register}, which may not have been written"
+                ),
+            });
+        }
+        // a single warning is enough for every later read of the register
+        *unwritten &= !bit(register);
+    }
+
+    match instruction {
+        Instruction::External(_) => *unwritten = 0,
+        Instruction::Call(_) => {
+            let callee = cfg.call(line).and_then(|call| call.callee);
+            *unwritten &= match callee {
+                Some(callee) => !written.get(&cfg.function(callee)).copied().unwrap_or(ALL),
+                None => 0,
+            };
+        }
+        _ => {}
+    }
+
+    if let Some((register, _)) = instruction.writes() {
+        *unwritten &= !bit(register);
+    }
+}
+
+/// Visits every instruction of the function reached from its entry, in order of their blocks,
+/// along with the registers which may be unwritten before it.
+fn walk(
+    cfg: &ControlFlowGraph,
+    written: &HashMap<Option<usize>, Registers>,
+    function: Option<usize>,
+    entry: Registers,
+    mut visit: impl FnMut(usize, &Instruction, &mut Registers),
+) {
+    let states = cfg.dataflow(
+        function,
+        entry,
+        |unwritten, line, instruction| apply(cfg, written, unwritten, line, instruction, None),
+        |a, b| a | b,
+    );
+
+    let mut states: Vec<(usize, Registers)> = states.into_iter().collect();
+    states.sort_unstable();
+    for (index, mut unwritten) in states {
+        let Some(block) = cfg.blocks().get(index) else {
+            continue;
+        };
+        for line in block.start..=block.end {
+            if let Some(instruction) = cfg.instruction(line) {
+                visit(line, instruction, &mut unwritten);
+            }
+        }
+    }
+}
+
+/// Finds reads of registers which may not have been written yet,
+/// in the top level and every function of the program.
+pub fn analyse_uninitialised(cfg: &ControlFlowGraph) -> Vec<Warning> {
+    let written = written(cfg);
+
+    let mut entries: HashMap<Option<usize>, Registers> = HashMap::new();
+    entries.insert(None, ALL);
+    for (entry, name) in cfg.functions() {
+        if name.starts_with(TEST_PREFIX) {
+            entries.insert(Some(*entry), ALL);
+        }
+    }
+
+    // spread the unwritten registers at every call to the called function, until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let reached: Vec<(Option<usize>, Registers)> = entries
+            .iter()
+            .map(|(function, entry)| (*function, *entry))
+            .collect();
+        for (function, entry) in reached {
+            walk(
+                cfg,
+                &written,
+                function,
+                entry,
+                |line, instruction, unwritten| {
+                    let callee = cfg
+                        .call(line)
+                        .and_then(|call| call.callee)
+                        .and_then(|callee| cfg.function(callee));
+                    if let Some(callee) = callee {
+                        let entry = entries.entry(Some(callee)).or_default();
+                        if *entry | *unwritten != *entry {
+                            *entry |= *unwritten;
+                            changed = true;
+                        }
+                    }
+                    apply(cfg, &written, unwritten, line, instruction, None);
+                },
+            );
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let mut entries: Vec<(Option<usize>, Registers)> = entries.into_iter().collect();
+    entries.sort();
+    for (function, entry) in entries {
+        walk(
+            cfg,
+            &written,
+            function,
+            entry,
+            |line, instruction, unwritten| {
+                apply(
+                    cfg,
+                    &written,
+                    unwritten,
+                    line,
+                    instruction,
+                    Some(&mut warnings),
+                );
+            },
+        );
+    }
+
+    warnings.sort_by_key(|warning| warning.line);
+    warnings.dedup();
+    warnings
+}
+
+#[cfg(test)]
+mod uninitialised {
+    use super::analyse_uninitialised;
+    use crate::{cfg::ControlFlowGraph, lint::Lint, preprocess};
+
+    /// Gets the line of every warning of the program.
+    fn warnings(source: &str) -> Vec<usize> {
+        analyse_uninitialised(&ControlFlowGraph::new(&preprocess(source, &[]).unwrap()))
+            .into_iter()
+            .inspect(|warning| assert_eq!(warning.lint, Lint::UninitialisedRegister))
+            .map(|warning| warning.line)
+            .collect()
+    }
+
+    #[test]
+    fn written_before_read() {
+        let source = "setb ra 1\naddb ra 1\npshb 2\npopb rb\ncmpb ra rb\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn read_before_written() {
+        let source = "setb ra rb\naddb rc 1\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![1, 2]);
+    }
+
+    #[test]
+    fn reported_once() {
+        let source = "prrb ra\nprrb ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![1]);
+    }
+
+    #[test]
+    fn written_on_one_path() {
+        let source = "cmpb rb 0\njiz +2\nsetb ra 1\nprrb ra\nstop\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![1, 4]);
+    }
+
+    #[test]
+    fn argument_written_by_caller() {
+        let source = "setb ra 1\ncall f\nstop\n\nfn f:\n  addb ra 1\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn argument_unwritten_by_one_caller() {
+        let source = "call g\nsetb ra 1\ncall f\nstop\n\nfn g:\n  call f\n  ret\n\nfn f:\n  setb rb ra\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![11]);
+    }
+
+    #[test]
+    fn result_written_by_callee() {
+        let source =
+            "call f\nprrb ra\nstop\n\nfn f:\n  call g\n  ret\n\nfn g:\n  setb ra 1\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_functions_start_unwritten() {
+        let source = "fn test_add:\n  addb ra 1\n  ret\n\nfn unused:\n  addb ra 1\n  ret\n";
+
+        let actual = warnings(source);
+
+        assert_eq!(actual, vec![2]);
+    }
+}
@@ -0,0 +1,82 @@
+use super::Writer;
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+/// A buffer that can be shared between a [`Capture`] writer and its reader.
+pub type SharedBuffer = Rc<RefCell<Vec<u8>>>;
+
+/// A writer that keeps a copy of everything written to it.
+///
+/// The written bytes are optionally forwarded to another writer, such that
+/// output can both be inspected while the program runs and end up in a file.
+pub struct Capture {
+    buffer: SharedBuffer,
+    forward: Option<Box<dyn Writer>>,
+}
+
+impl Capture {
+    pub fn new(buffer: SharedBuffer, forward: Option<Box<dyn Writer>>) -> Self {
+        Self { buffer, forward }
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(forward) = &mut self.forward {
+            forward.write_all(buf)?;
+        }
+
+        self.buffer.borrow_mut().extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.forward {
+            Some(forward) => forward.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Writer for Capture {
+    #[cfg(test)]
+    fn get_buffer(&self) -> Option<String> {
+        Some(String::from_utf8(self.buffer.borrow().clone()).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod write {
+    use super::{Capture, SharedBuffer};
+    use std::io::Write;
+
+    #[test]
+    fn keeps_copy_without_forward() -> std::io::Result<()> {
+        let buffer = SharedBuffer::default();
+        let mut c = Capture::new(buffer.clone(), None);
+
+        write!(c, "ra: 10")?;
+
+        assert_eq!(*buffer.borrow(), b"ra: 10");
+
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_copy_and_forwards() -> std::io::Result<()> {
+        let buffer = SharedBuffer::default();
+        let mut c = Capture::new(buffer.clone(), Some(Box::new(Vec::new())));
+
+        write!(c, "ra: 10")?;
+        let forwarded = c
+            .forward
+            .as_ref()
+            .and_then(|f| f.get_buffer())
+            .expect("forward writer should be a buffer");
+
+        assert_eq!(*buffer.borrow(), b"ra: 10");
+        assert_eq!(forwarded, "ra: 10");
+
+        Ok(())
+    }
+}
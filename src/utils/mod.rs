@@ -1,6 +1,8 @@
 pub use arithmetic::Arithmetic;
 pub use bitwise::BitWise;
+pub use capture::{Capture, SharedBuffer};
 pub use from_bytes::FromBytes;
+pub use reader::{stdin_reader, Reader};
 pub use setable::Setable;
 pub use to_bytes::ToBytes;
 pub use to_word::ToWord;
@@ -9,8 +11,10 @@ pub use writer::Writer;
 mod arithmetic;
 mod bit_ops;
 mod bitwise;
+mod capture;
 mod from_bytes;
 mod overflow;
+mod reader;
 mod setable;
 mod to_bytes;
 mod to_word;
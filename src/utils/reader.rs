@@ -0,0 +1,159 @@
+use std::{
+    fs::File,
+    io::{stdin, BufRead, BufReader, Cursor, Stdin},
+};
+
+/// Reads from stdin without holding on to its lock, as the line debugger reads its actions from stdin as well.
+///
+/// Only a single byte is buffered, such that no input meant for the debugger is taken ahead of time,
+/// while stdin itself still buffers the underlying reads.
+pub fn stdin_reader() -> BufReader<Stdin> {
+    BufReader::with_capacity(1, stdin())
+}
+
+/// The source of input for read instructions.
+pub trait Reader: BufRead {
+    /// Reads the next sequence of non-whitespace characters, along with the single whitespace character following it.
+    ///
+    /// Returns none if the end of the input is reached before any non-whitespace character.
+    ///
+    /// # Errors
+    /// Returns an error if reading from the underlying input fails.
+    fn read_token(&mut self) -> std::io::Result<Option<String>> {
+        loop {
+            let buffer = self.fill_buf()?;
+            if buffer.is_empty() {
+                return Ok(None);
+            }
+
+            let whitespace = buffer
+                .iter()
+                .take_while(|byte| byte.is_ascii_whitespace())
+                .count();
+            let found = whitespace < buffer.len();
+            self.consume(whitespace);
+
+            if found {
+                break;
+            }
+        }
+
+        let mut token = Vec::new();
+        loop {
+            let buffer = self.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+
+            let length = buffer
+                .iter()
+                .take_while(|byte| !byte.is_ascii_whitespace())
+                .count();
+            token.extend_from_slice(buffer.get(..length).unwrap_or_default());
+
+            if length < buffer.len() {
+                self.consume(length + 1);
+                break;
+            }
+            self.consume(length);
+        }
+
+        Ok(Some(String::from_utf8_lossy(&token).into_owned()))
+    }
+
+    /// Reads a single byte, returning none at the end of the input.
+    ///
+    /// # Errors
+    /// Returns an error if reading from the underlying input fails.
+    fn read_byte(&mut self) -> std::io::Result<Option<u8>> {
+        let byte = self.fill_buf()?.first().copied();
+        if byte.is_some() {
+            self.consume(1);
+        }
+
+        Ok(byte)
+    }
+
+    /// Reads a line without its line ending, returning none at the end of the input.
+    ///
+    /// # Errors
+    /// Returns an error if reading from the underlying input fails.
+    fn read_line_bytes(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if self.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+
+        Ok(Some(line))
+    }
+}
+
+impl Reader for BufReader<Stdin> {}
+
+impl Reader for BufReader<File> {}
+
+impl Reader for Cursor<Vec<u8>> {}
+
+#[cfg(test)]
+mod read_token {
+    use super::Reader;
+    use std::io::Cursor;
+
+    #[test]
+    fn tokens_across_lines() -> std::io::Result<()> {
+        let mut r = Cursor::new(b"  12 -5\n\n 300\n".to_vec());
+
+        assert_eq!(r.read_token()?, Some(String::from("12")));
+        assert_eq!(r.read_token()?, Some(String::from("-5")));
+        assert_eq!(r.read_token()?, Some(String::from("300")));
+        assert_eq!(r.read_token()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn consumes_single_delimiter() -> std::io::Result<()> {
+        let mut r = Cursor::new(b"12\nhello\n".to_vec());
+
+        r.read_token()?;
+
+        assert_eq!(r.read_line_bytes()?, Some(b"hello".to_vec()));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod read_line_bytes {
+    use super::Reader;
+    use std::io::Cursor;
+
+    #[test]
+    fn strips_line_endings() -> std::io::Result<()> {
+        let mut r = Cursor::new(b"first\r\nsecond\nlast".to_vec());
+
+        assert_eq!(r.read_line_bytes()?, Some(b"first".to_vec()));
+        assert_eq!(r.read_line_bytes()?, Some(b"second".to_vec()));
+        assert_eq!(r.read_line_bytes()?, Some(b"last".to_vec()));
+        assert_eq!(r.read_line_bytes()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_line() -> std::io::Result<()> {
+        let mut r = Cursor::new(b"\n".to_vec());
+
+        assert_eq!(r.read_line_bytes()?, Some(Vec::new()));
+        assert_eq!(r.read_byte()?, None);
+
+        Ok(())
+    }
+}
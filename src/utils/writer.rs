@@ -1,6 +1,6 @@
 use std::{
     fs::File,
-    io::{Stdout, Write},
+    io::{BufWriter, Stderr, Stdout, Write},
 };
 
 pub trait Writer: Write {
@@ -15,6 +15,13 @@ impl Writer for Stdout {
     }
 }
 
+impl Writer for Stderr {
+    #[cfg(test)]
+    fn get_buffer(&self) -> Option<String> {
+        None
+    }
+}
+
 impl Writer for Vec<u8> {
     #[cfg(test)]
     fn get_buffer(&self) -> Option<String> {
@@ -28,3 +35,10 @@ impl Writer for File {
         None
     }
 }
+
+impl Writer for BufWriter<File> {
+    #[cfg(test)]
+    fn get_buffer(&self) -> Option<String> {
+        None
+    }
+}
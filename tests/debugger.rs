@@ -0,0 +1,67 @@
+//! Runs the line debugger through the binary, as it reads its actions from the real stdin.
+
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs the program in debug mode with the given stdin, killing it if it does not finish in time.
+fn debug(name: &str, program: &str, stdin: &str) -> Output {
+    let path =
+        std::env::temp_dir().join(format!("kasm-debugger-{name}-{}.kasm", std::process::id()));
+    fs::write(&path, program).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kasm"))
+        .arg("run")
+        .arg("--debug")
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    let start = Instant::now();
+    while child.try_wait().unwrap().is_none() {
+        if start.elapsed() > TIMEOUT {
+            child.kill().unwrap();
+            panic!("the debugger did not finish within {TIMEOUT:?}");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let output = child.wait_with_output().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    output
+}
+
+#[test]
+fn steps_with_stdin_as_default_input() {
+    let output = debug("steps", "setb ra 1\nprrb ra\nstop\n", "n\nn\nn\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("ra: 1\n"));
+    assert!(stdout.contains("next: stop\n"));
+}
+
+#[test]
+fn program_input_between_actions() {
+    let output = debug("input", "rduw ra\nprrw ra\nstop\n", "n\n42\nn\nn\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("ra: 42\n"));
+}